@@ -0,0 +1,288 @@
+use std::{collections::VecDeque, mem};
+
+use serde::{Deserialize, Serialize};
+
+use super::{Addressable, CoreError};
+
+pub const SOUND_REGISTERS_START: u32 = 0x4000060;
+pub const SOUND_REGISTERS_END: u32 = 0x40000A7;
+
+const CPU_FREQUENCY: u64 = 1 << 24;
+/// The rate the hardware mixes at with the default SOUNDBIAS.
+pub const DEFAULT_SAMPLE_RATE: u32 = 32768;
+/// Samples older than this many seconds are dropped if nothing pulls them.
+const BUFFERED_SECONDS: usize = 1;
+
+/// The frame sequencer clocks length at 256Hz, sweep at 128Hz and the envelope at 64Hz.
+const LENGTH_PERIOD: u64 = CPU_FREQUENCY / 256;
+const SWEEP_PERIOD: u64 = CPU_FREQUENCY / 128;
+const ENVELOPE_PERIOD: u64 = CPU_FREQUENCY / 64;
+
+const MASTER_ENABLE: u8 = 1 << 7;
+/// Amplitude of one step of channel volume.
+const VOLUME_SCALE: i16 = 0x800;
+
+/// Which of the eight steps of each period are high, for each of the four duty cycles.
+const DUTY_PATTERNS: [u8; 4] = [0b0000_0001, 0b1000_0001, 0b1000_0111, 0b0111_1110];
+
+/// Sound channel 1, a square wave with frequency sweep and a volume envelope.
+#[derive(Clone, Default, Serialize, Deserialize)]
+struct SquareChannel {
+    enabled: bool,
+    /// The 11 bit frequency value, giving 131072 / (2048 - frequency) Hz.
+    frequency: u16,
+    duty: u8,
+    /// Which of the eight duty steps is playing and how far into it the channel is, in cycles.
+    duty_step: u8,
+    step_cycles: u64,
+    volume: u8,
+    envelope_increase: bool,
+    envelope_period: u8,
+    envelope_timer: u8,
+    sweep_period: u8,
+    sweep_decrease: bool,
+    sweep_shift: u8,
+    sweep_timer: u8,
+    length: u8,
+    length_enabled: bool,
+}
+
+impl SquareChannel {
+    fn cycles_per_step(&self) -> u64 {
+        16 * (2048 - self.frequency as u64)
+    }
+
+    fn step(&mut self, cycles: u64) {
+        if !self.enabled {
+            return;
+        }
+        self.step_cycles += cycles;
+        let period = self.cycles_per_step();
+        self.duty_step = ((self.duty_step as u64 + self.step_cycles / period) % 8) as u8;
+        self.step_cycles %= period;
+    }
+
+    fn output(&self) -> i16 {
+        if !self.enabled {
+            return 0;
+        }
+        let high = DUTY_PATTERNS[self.duty as usize] & (1 << self.duty_step) > 0;
+        let amplitude = self.volume as i16 * VOLUME_SCALE / 2;
+        if high {
+            amplitude
+        } else {
+            -amplitude
+        }
+    }
+
+    fn clock_length(&mut self) {
+        if self.length_enabled && self.length > 0 {
+            self.length -= 1;
+            if self.length == 0 {
+                self.enabled = false;
+            }
+        }
+    }
+
+    fn clock_sweep(&mut self) {
+        if self.sweep_period == 0 {
+            return;
+        }
+        self.sweep_timer = self.sweep_timer.saturating_sub(1);
+        if self.sweep_timer > 0 {
+            return;
+        }
+        self.sweep_timer = self.sweep_period;
+
+        let change = self.frequency >> self.sweep_shift;
+        if self.sweep_decrease {
+            self.frequency = self.frequency.saturating_sub(change);
+        } else if self.frequency + change > 0x7FF {
+            self.enabled = false;
+        } else {
+            self.frequency += change;
+        }
+    }
+
+    fn clock_envelope(&mut self) {
+        if self.envelope_period == 0 {
+            return;
+        }
+        self.envelope_timer = self.envelope_timer.saturating_sub(1);
+        if self.envelope_timer > 0 {
+            return;
+        }
+        self.envelope_timer = self.envelope_period;
+        if self.envelope_increase && self.volume < 15 {
+            self.volume += 1;
+        } else if !self.envelope_increase && self.volume > 0 {
+            self.volume -= 1;
+        }
+    }
+
+    /// Restarts the channel with the settings in its registers.
+    fn trigger(&mut self, sweep: u8, control: u16) {
+        self.enabled = true;
+        self.duty_step = 0;
+        self.step_cycles = 0;
+        self.sweep_shift = sweep & 0b111;
+        self.sweep_decrease = sweep & (1 << 3) > 0;
+        self.sweep_period = (sweep >> 4) & 0b111;
+        self.sweep_timer = self.sweep_period;
+        self.duty = ((control >> 6) & 0b11) as u8;
+        self.envelope_period = ((control >> 8) & 0b111) as u8;
+        self.envelope_increase = control & (1 << 11) > 0;
+        self.envelope_timer = self.envelope_period;
+        self.volume = (control >> 12) as u8;
+        if self.length == 0 {
+            self.length = 64 - (control & 0x3F) as u8;
+        }
+    }
+}
+
+/// The sound registers and the sample output. Only channel 1 generates sound so far. The other
+/// registers are stored so they read back what was written.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Apu {
+    registers: Vec<u8>,
+    channel1: SquareChannel,
+    /// The output rate and buffered samples belong to whoever plays the sound, so they are not
+    /// part of a save state.
+    #[serde(skip)]
+    sample_rate: u32,
+    /// CPU cycles multiplied by the sample rate since the last sample, so that samples are taken
+    /// at exactly the sample rate without rounding.
+    sample_cycles: u64,
+    sequencer_cycles: u64,
+    #[serde(skip)]
+    samples: VecDeque<i16>,
+}
+
+impl Default for Apu {
+    fn default() -> Self {
+        Self::new(DEFAULT_SAMPLE_RATE)
+    }
+}
+
+impl Apu {
+    pub fn new(sample_rate: u32) -> Self {
+        Self {
+            registers: vec![0; (SOUND_REGISTERS_END - SOUND_REGISTERS_START + 1) as usize],
+            channel1: SquareChannel::default(),
+            sample_rate,
+            sample_cycles: 0,
+            sequencer_cycles: 0,
+            samples: VecDeque::new(),
+        }
+    }
+
+    /// A rate of 0 turns the output off. The sound hardware keeps running either way.
+    pub fn set_sample_rate(&mut self, sample_rate: u32) {
+        self.sample_rate = sample_rate;
+        self.sample_cycles = 0;
+    }
+
+    /// Loads the sound hardware from a save state, keeping the output rate and any samples not
+    /// pulled yet.
+    pub fn restore(&mut self, state: Apu) {
+        *self = Self {
+            sample_rate: self.sample_rate,
+            samples: mem::take(&mut self.samples),
+            ..state
+        };
+    }
+
+    fn register(&self, address: u32) -> u8 {
+        self.registers[(address - SOUND_REGISTERS_START) as usize]
+    }
+
+    fn register_halfword(&self, address: u32) -> u16 {
+        self.register(address) as u16 | ((self.register(address + 1) as u16) << 8)
+    }
+
+    fn master_enabled(&self) -> bool {
+        self.register(0x4000084) & MASTER_ENABLE > 0
+    }
+
+    /// Runs the sound hardware for `cycles` CPU cycles, adding the samples that fall in that time
+    /// to the output buffer.
+    pub fn step(&mut self, cycles: usize) {
+        let sample_rate = self.sample_rate as u64;
+        let mut remaining = cycles as u64;
+        // Runs up to the next frame sequencer clock or sample at a time.
+        while remaining > 0 {
+            let until_sequencer = LENGTH_PERIOD - self.sequencer_cycles % LENGTH_PERIOD;
+            let until_sample = if sample_rate == 0 {
+                u64::MAX
+            } else {
+                (CPU_FREQUENCY - self.sample_cycles).div_ceil(sample_rate)
+            };
+            let cycles = remaining.min(until_sequencer).min(until_sample);
+            remaining -= cycles;
+
+            self.channel1.step(cycles);
+            self.sequencer_cycles += cycles;
+            self.clock_sequencer();
+            self.sample_cycles += cycles * sample_rate;
+            self.take_sample();
+        }
+    }
+
+    fn clock_sequencer(&mut self) {
+        if self.sequencer_cycles.is_multiple_of(LENGTH_PERIOD) {
+            self.channel1.clock_length();
+        }
+        if self.sequencer_cycles.is_multiple_of(SWEEP_PERIOD) {
+            self.channel1.clock_sweep();
+        }
+        if self.sequencer_cycles.is_multiple_of(ENVELOPE_PERIOD) {
+            self.channel1.clock_envelope();
+            self.sequencer_cycles = 0;
+        }
+    }
+
+    fn take_sample(&mut self) {
+        if self.sample_cycles >= CPU_FREQUENCY {
+            self.sample_cycles -= CPU_FREQUENCY;
+            let sample = if self.master_enabled() {
+                self.channel1.output()
+            } else {
+                0
+            };
+            if self.samples.len() >= self.sample_rate as usize * BUFFERED_SECONDS {
+                self.samples.pop_front();
+            }
+            self.samples.push_back(sample);
+        }
+    }
+
+    /// Takes up to `count` of the oldest samples generated so far.
+    pub fn pull_samples(&mut self, count: usize) -> Vec<i16> {
+        let count = count.min(self.samples.len());
+        self.samples.drain(..count).collect()
+    }
+}
+
+impl Addressable for Apu {
+    fn read_byte(&mut self, address: u32) -> Result<u8, CoreError> {
+        Ok(self.register(address))
+    }
+
+    fn write_byte(&mut self, address: u32, data: u8) {
+        self.registers[(address - SOUND_REGISTERS_START) as usize] = data;
+        match address {
+            // Writing a new length reloads the counter.
+            0x4000062 => self.channel1.length = 64 - (data & 0x3F),
+            0x4000064 | 0x4000065 => {
+                let control = self.register_halfword(0x4000064);
+                self.channel1.frequency = control & 0x7FF;
+                self.channel1.length_enabled = control & (1 << 14) > 0;
+                if address == 0x4000065 && data & 0x80 > 0 {
+                    self.channel1
+                        .trigger(self.register(0x4000060), self.register_halfword(0x4000062));
+                }
+            }
+            _ => {}
+        }
+    }
+}
@@ -1,7 +1,57 @@
-use super::Addressable;
+use super::{Addressable, CoreError};
 use anyhow::{anyhow, Result};
+use num_enum::TryFromPrimitive;
 use std::fs;
 
+/// BIOS calls selectable through the SWI comment field.
+#[repr(u8)]
+#[derive(Debug, Eq, PartialEq, TryFromPrimitive)]
+pub enum BiosFunction {
+    SoftReset = 0x00,
+    RegisterRamReset,
+    Halt,
+    Stop,
+    IntrWait,
+    VBlankIntrWait,
+    Div,
+    DivArm,
+    Sqrt,
+    ArcTan,
+    ArcTan2,
+    CpuSet,
+    CpuFastSet,
+    GetBiosChecksum,
+    BgAffineSet,
+    ObjAffineSet,
+    BitUnPack,
+    LZ77UnCompWram,
+    LZ77UnCompVram,
+    HuffUnComp,
+    RLUnCompWram,
+    RLUnCompVram,
+    Diff8bitUnFilterWram,
+    Diff8bitUnFilterVram,
+    Diff16bitUnFilter,
+    SoundBias,
+    SoundDriverInit,
+    SoundDriverMode,
+    SoundDriverMain,
+    SoundDriverVSync,
+    SoundChannelClear,
+    MidiKey2Freq,
+    SoundWhatever0,
+    SoundWhatever1,
+    SoundWhatever2,
+    SoundWhatever3,
+    SoundWhatever4,
+    MultiBoot,
+    HardReset,
+    CustomHalt,
+    SoundDriverVSyncOff,
+    SoundDriverVSyncOn,
+    SoundGetJumpList,
+}
+
 pub struct Bios([u8; 0x4000]);
 
 impl Bios {
@@ -11,17 +61,21 @@ impl Bios {
             Err(_) => return Err(anyhow!("Unable to find bios file {}", filename)),
         };
 
-        if file.len() != 0x4000 {
+        Self::from_buffer(&file)
+    }
+
+    pub fn from_buffer(buffer: &[u8]) -> Result<Self> {
+        if buffer.len() != 0x4000 {
             return Err(anyhow!("Bios files must be 0x4000 bytes"));
         }
 
-        Ok(Self(file[0..0x4000].try_into()?))
+        Ok(Self(buffer[0..0x4000].try_into()?))
     }
 }
 
 impl Addressable for Bios {
-    fn read_byte(&mut self, address: u32) -> u8 {
-        self.0[address as usize]
+    fn read_byte(&mut self, address: u32) -> Result<u8, CoreError> {
+        Ok(self.0[address as usize])
     }
 
     fn write_byte(&mut self, address: u32, data: u8) {
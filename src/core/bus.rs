@@ -1,12 +1,38 @@
-use super::CoreError;
+use super::{memory::waitstate::WaitStates, CoreError, InstructionMode};
 use std::cell::RefCell;
 use std::fmt::Display;
 use std::ops::RangeInclusive;
 use std::rc::Rc;
 
 pub trait Addressable {
-    fn read_byte(&mut self, address: u32) -> u8;
+    /// Reads a byte. Components return an error for reads the hardware would fault on.
+    fn read_byte(&mut self, address: u32) -> Result<u8, CoreError>;
     fn write_byte(&mut self, address: u32, data: u8);
+
+    /// Reads the halfword at an even address. Components with registers that act differently
+    /// when accessed 16 bits at a time override this, otherwise it is made of two byte reads.
+    fn read_word(&mut self, address: u32) -> Result<u16, CoreError> {
+        Ok(u16::from_le_bytes([
+            self.read_byte(address)?,
+            self.read_byte(address + 1)?,
+        ]))
+    }
+
+    fn write_word(&mut self, address: u32, data: u16) {
+        let [low, high] = data.to_le_bytes();
+        self.write_byte(address, low);
+        self.write_byte(address + 1, high);
+    }
+
+    /// Reads 32 bits as two halfwords, which is how the 16-bit buses carry them anyway.
+    fn read_dword(&mut self, address: u32) -> Result<u32, CoreError> {
+        Ok(self.read_word(address)? as u32 | ((self.read_word(address + 2)? as u32) << 16))
+    }
+
+    fn write_dword(&mut self, address: u32, data: u32) {
+        self.write_word(address, data as u16);
+        self.write_word(address + 2, (data >> 16) as u16);
+    }
 }
 
 pub struct MemoryMapping {
@@ -14,9 +40,54 @@ pub struct MemoryMapping {
     component: Rc<RefCell<dyn Addressable>>,
 }
 
+/// The memory map is split into 16MiB pages by the top nibble of the address, with one more
+/// page for everything above it.
+const PAGE_COUNT: usize = 17;
+/// The last address decoded by the hardware. Unmapped reads below it see open bus.
+const MEMORY_MAP_END: u32 = 0x0FFFFFFF;
+
+fn page(address: u32) -> usize {
+    ((address >> 24) as usize).min(PAGE_COUNT - 1)
+}
+
+/// The accesses a watchpoint stops on. Hits are reported as `Read` or `Write`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WatchKind {
+    Read,
+    Write,
+    ReadWrite,
+}
+
+impl WatchKind {
+    fn matches(self, access: WatchKind) -> bool {
+        self == WatchKind::ReadWrite || self == access
+    }
+}
+
+struct Watchpoint {
+    region: RangeInclusive<u32>,
+    kind: WatchKind,
+}
+
 #[derive(Default)]
 pub struct Bus {
     regions: Vec<MemoryMapping>,
+    /// For each page, the regions overlapping it in the order they were registered.
+    pages: [Vec<usize>; PAGE_COUNT],
+    /// The region owning the whole of a page, for pages that one region covers completely.
+    /// Accesses there skip the search.
+    whole_pages: [Option<usize>; PAGE_COUNT],
+    strict_alignment: bool,
+    open_bus: u32,
+    last_thumb_fetch: u32,
+    wait_states: WaitStates,
+    /// The address that would make the next access sequential.
+    next_sequential_address: u32,
+    /// Wait states accumulated by accesses since they were last taken.
+    wait_cycles: usize,
+    watchpoints: Vec<Watchpoint>,
+    /// The first access to trip a watchpoint since this was last taken.
+    watchpoint_hit: Option<(u32, WatchKind)>,
 }
 
 impl Display for Bus {
@@ -37,49 +108,266 @@ impl Bus {
         region: RangeInclusive<u32>,
         component: Rc<RefCell<dyn Addressable>>,
     ) {
+        for page in page(*region.start())..=page(*region.end()) {
+            let page_start = (page as u32) << 24;
+            let page_end = page_start | 0xFFFFFF;
+            let covers_page =
+                page < PAGE_COUNT - 1 && region.contains(&page_start) && region.contains(&page_end);
+            // Earlier regions take precedence wherever they overlap later ones.
+            if covers_page && self.pages[page].is_empty() {
+                self.whole_pages[page] = Some(self.regions.len());
+            }
+            self.pages[page].push(self.regions.len());
+        }
         self.regions.push(MemoryMapping { region, component });
     }
 
-    pub fn read_byte(&mut self, address: u32) -> Result<u8, CoreError> {
-        for mapping in &self.regions {
-            if mapping.region.contains(&address) {
-                return Ok(mapping.component.borrow_mut().read_byte(address));
+    /// When enabled, unaligned 16 and 32-bit accesses are reported as errors instead of being
+    /// handled the way the hardware would. Useful for catching badly computed addresses.
+    pub fn set_strict_alignment(&mut self, enabled: bool) {
+        self.strict_alignment = enabled;
+    }
+
+    /// Updates the wait states from a new WAITCNT value.
+    pub fn set_wait_control(&mut self, control: u16) {
+        if control != self.wait_states.control() {
+            self.wait_states = WaitStates::from_control(control);
+        }
+    }
+
+    /// Takes the wait states that accesses have added since the last call, in cycles.
+    pub fn take_wait_cycles(&mut self) -> usize {
+        std::mem::take(&mut self.wait_cycles)
+    }
+
+    fn count_access(&mut self, address: u32, width: u32) {
+        let sequential = address == self.next_sequential_address;
+        self.wait_cycles += self.wait_states.wait_cycles(address, width, sequential);
+        self.next_sequential_address = address.wrapping_add(width);
+    }
+
+    /// Watches accesses to `region` for the debugger. Instruction fetches are not data accesses
+    /// and never trip a watchpoint.
+    pub fn add_watchpoint(&mut self, region: RangeInclusive<u32>, kind: WatchKind) {
+        self.watchpoints.push(Watchpoint { region, kind });
+    }
+
+    /// Returns the address and kind of the first access to hit a watchpoint since the last call.
+    pub fn take_watchpoint_hit(&mut self) -> Option<(u32, WatchKind)> {
+        self.watchpoint_hit.take()
+    }
+
+    fn check_watchpoints(&mut self, address: u32, size: u32, access: WatchKind) {
+        if self.watchpoint_hit.is_some() {
+            return;
+        }
+        let last_address = address.wrapping_add(size - 1);
+        let hit = self.watchpoints.iter().any(|watchpoint| {
+            watchpoint.kind.matches(access)
+                && *watchpoint.region.start() <= last_address
+                && address <= *watchpoint.region.end()
+        });
+        if hit {
+            self.watchpoint_hit = Some((address, access));
+        }
+    }
+
+    fn check_alignment(&self, address: u32, alignment: u32) -> Result<(), CoreError> {
+        if self.strict_alignment && address & (alignment - 1) != 0 {
+            Err(CoreError::UnalignedAccess(address))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Reads an opcode for the CPU and latches it as the value left on the bus.
+    pub fn fetch(
+        &mut self,
+        address: u32,
+        instruction_mode: InstructionMode,
+    ) -> Result<u32, CoreError> {
+        let opcode = self.peek_opcode(address, instruction_mode)?;
+        self.latch_prefetch(address, opcode, instruction_mode);
+        let width = match instruction_mode {
+            InstructionMode::Arm => 4,
+            InstructionMode::Thumb => 2,
+        };
+        self.count_access(address, width);
+        Ok(opcode)
+    }
+
+    /// Reads a byte without it counting as an access for watchpoints. Used to show memory in a
+    /// debugger.
+    pub fn peek_byte(&mut self, address: u32) -> Result<u8, CoreError> {
+        self.component(address)?.borrow_mut().read_byte(address)
+    }
+
+    /// Reads the opcode at `address` without it counting as an access, so watchpoints and open
+    /// bus are left alone. Used to disassemble code that is not running.
+    pub fn peek_opcode(
+        &mut self,
+        address: u32,
+        instruction_mode: InstructionMode,
+    ) -> Result<u32, CoreError> {
+        Ok(match instruction_mode {
+            InstructionMode::Arm => self.load_dword(address)?,
+            InstructionMode::Thumb => self.load_word(address)? as u32,
+        })
+    }
+
+    /// Records an instruction fetch. The fetched opcode is what is left on the bus, so it is what
+    /// reads of open bus see. In Thumb state the upper halfword depends on the region the code
+    /// runs from.
+    fn latch_prefetch(&mut self, address: u32, opcode: u32, instruction_mode: InstructionMode) {
+        self.open_bus = match instruction_mode {
+            InstructionMode::Arm => opcode,
+            InstructionMode::Thumb => {
+                let opcode = opcode & 0xFFFF;
+                match address >> 24 {
+                    // BIOS and OAM have the following halfword on the other half of the bus.
+                    0x00 | 0x07 => {
+                        let next = self.load_word(address.wrapping_add(2)).unwrap_or(0) as u32;
+                        opcode | (next << 16)
+                    }
+                    // IWRAM is 32-bit so the previous fetch shares the bus.
+                    0x03 => {
+                        if address & 2 == 0 {
+                            opcode | (self.last_thumb_fetch << 16)
+                        } else {
+                            self.last_thumb_fetch | (opcode << 16)
+                        }
+                    }
+                    _ => opcode | (opcode << 16),
+                }
             }
+        };
+        self.last_thumb_fetch = opcode & 0xFFFF;
+    }
+
+    /// The byte an open bus read of `address` returns.
+    pub fn open_bus_byte(&self, address: u32) -> u8 {
+        (self.open_bus >> (8 * (address & 0b11))) as u8
+    }
+
+    /// The component mapped at `address`. Wider accesses go entirely to the component that
+    /// owns their first byte.
+    fn mapped_component(&self, address: u32) -> Option<&RefCell<dyn Addressable>> {
+        let page = page(address);
+        if let Some(index) = self.whole_pages[page] {
+            return Some(&*self.regions[index].component);
+        }
+        self.pages[page]
+            .iter()
+            .map(|index| &self.regions[*index])
+            .find(|mapping| mapping.region.contains(&address))
+            .map(|mapping| &*mapping.component)
+    }
+
+    fn component(&self, address: u32) -> Result<&RefCell<dyn Addressable>, CoreError> {
+        self.mapped_component(address)
+            .ok_or(CoreError::InvalidRegion(address))
+    }
+
+    /// Reads from nothing inside the memory map return what was last left on the bus, shifted to
+    /// the lane `address` reads from. Past the end of the map the address is a bug.
+    fn unmapped_read(&self, address: u32) -> Result<u32, CoreError> {
+        if address <= MEMORY_MAP_END {
+            Ok(self.open_bus.rotate_right(8 * (address & 0b11)))
+        } else {
+            Err(CoreError::InvalidRegion(address))
+        }
+    }
+
+    pub fn read_byte(&mut self, address: u32) -> Result<u8, CoreError> {
+        if !self.watchpoints.is_empty() {
+            self.check_watchpoints(address, 1, WatchKind::Read);
+        }
+        self.count_access(address, 1);
+        match self.mapped_component(address) {
+            Some(component) => component.borrow_mut().read_byte(address),
+            None => Ok(self.unmapped_read(address)? as u8),
         }
-        Err(CoreError::InvalidRegion(address))
     }
 
+    /// Reads the halfword containing `address`. Like the hardware, an odd address reads the
+    /// aligned halfword rotated by a byte.
     pub fn read_word(&mut self, address: u32) -> Result<u16, CoreError> {
-        let low_byte = self.read_byte(address)? as u16;
-        let high_byte = self.read_byte(address + 1)? as u16;
-        Ok(low_byte | (high_byte << 8))
+        self.check_alignment(address, 2)?;
+        if !self.watchpoints.is_empty() {
+            self.check_watchpoints(address & !1, 2, WatchKind::Read);
+        }
+        self.count_access(address & !1, 2);
+        self.load_word(address)
+    }
+
+    fn load_word(&mut self, address: u32) -> Result<u16, CoreError> {
+        let aligned_address = address & !1;
+        let data = match self.mapped_component(aligned_address) {
+            Some(component) => component.borrow_mut().read_word(aligned_address)?,
+            None => self.unmapped_read(aligned_address)? as u16,
+        };
+        Ok(data.rotate_right(8 * (address & 1)))
     }
 
+    /// Reads the word containing `address`. Like the hardware, an unaligned address reads the
+    /// aligned word rotated by the misalignment.
     pub fn read_dword(&mut self, address: u32) -> Result<u32, CoreError> {
-        let low_word = self.read_word(address)? as u32;
-        let high_word = self.read_word(address + 2)? as u32;
-        Ok(low_word | (high_word << 16))
+        self.check_alignment(address, 4)?;
+        if !self.watchpoints.is_empty() {
+            self.check_watchpoints(address & !0b11, 4, WatchKind::Read);
+        }
+        self.count_access(address & !0b11, 4);
+        self.load_dword(address)
+    }
+
+    fn load_dword(&mut self, address: u32) -> Result<u32, CoreError> {
+        let aligned_address = address & !0b11;
+        let data = match self.mapped_component(aligned_address) {
+            Some(component) => component.borrow_mut().read_dword(aligned_address)?,
+            None => self.unmapped_read(aligned_address)?,
+        };
+        Ok(data.rotate_right(8 * (address & 0b11)))
     }
 
     pub fn write_byte(&mut self, address: u32, data: u8) -> Result<(), CoreError> {
-        for mapping in &self.regions {
-            if mapping.region.contains(&address) {
-                mapping.component.borrow_mut().write_byte(address, data);
-                return Ok(());
-            }
+        if !self.watchpoints.is_empty() {
+            self.check_watchpoints(address, 1, WatchKind::Write);
         }
-        Err(CoreError::InvalidRegion(address))
+        self.count_access(address, 1);
+        self.component(address)?
+            .borrow_mut()
+            .write_byte(address, data);
+        Ok(())
     }
 
+    /// Writes a halfword to `address`. The hardware ignores the low bit of the address so the
+    /// write always lands on the aligned halfword.
     pub fn write_word(&mut self, address: u32, data: u16) -> Result<(), CoreError> {
-        self.write_byte(address, data as u8)?;
-        self.write_byte(address + 1, (data >> 8) as u8)?;
+        self.check_alignment(address, 2)?;
+
+        let aligned_address = address & !1;
+        if !self.watchpoints.is_empty() {
+            self.check_watchpoints(aligned_address, 2, WatchKind::Write);
+        }
+        self.count_access(aligned_address, 2);
+        self.component(aligned_address)?
+            .borrow_mut()
+            .write_word(aligned_address, data);
         Ok(())
     }
 
+    /// Writes a word to `address`. The low two bits of the address are ignored.
     pub fn write_dword(&mut self, address: u32, data: u32) -> Result<(), CoreError> {
-        self.write_word(address, data as u16)?;
-        self.write_word(address + 2, (data >> 16) as u16)?;
+        self.check_alignment(address, 4)?;
+
+        let aligned_address = address & !0b11;
+        if !self.watchpoints.is_empty() {
+            self.check_watchpoints(aligned_address, 4, WatchKind::Write);
+        }
+        self.count_access(aligned_address, 4);
+        self.component(aligned_address)?
+            .borrow_mut()
+            .write_dword(aligned_address, data);
         Ok(())
     }
 }
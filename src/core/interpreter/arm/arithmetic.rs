@@ -54,6 +54,7 @@ pub struct DataProcessingInstruction {
     operand: Operand,
     destination_register_index: Option<u32>,
     operation: DataProcessingOperation,
+    force_word_alignment: bool,
 }
 
 impl DataProcessingInstruction {
@@ -70,17 +71,23 @@ impl DataProcessingInstruction {
             operand,
             destination_register_index,
             operation,
+            force_word_alignment: false,
         }
     }
 
-    pub fn decode(registers: &mut RegisterBank, opcode: u32) -> Self {
+    /// Clears the low two bits of the source register before using it, like the Thumb load
+    /// address instruction does to the PC.
+    pub fn with_word_aligned_source(mut self) -> Self {
+        self.force_word_alignment = true;
+        self
+    }
+
+    pub fn decode(opcode: u32) -> Self {
         let operand = if opcode & (1 << 25) > 0 {
-            Operand::Immediate((rotated_immediate(opcode), false))
+            // A rotation of zero leaves the carry flag alone.
+            Operand::Immediate((rotated_immediate(opcode), (opcode >> 8) & 0xF != 0))
         } else {
-            match Shift::from_opcode(opcode) {
-                Shift::Immediate(shift) => Operand::Immediate(shift.shift(registers)),
-                Shift::Register(shift) => Operand::RegisterShifted(Shift::Register(shift)),
-            }
+            Operand::RegisterShifted(Shift::from_opcode(opcode))
         };
 
         let source_register_index = (opcode >> 16) & 0xF;
@@ -101,53 +108,55 @@ impl DataProcessingInstruction {
             operand,
             operation,
             destination_register_index,
+            force_word_alignment: false,
         }
     }
 }
 
 impl InstructionExecutor for DataProcessingInstruction {
     fn execute(&self, registers: &mut RegisterBank, _bus: &mut Bus) -> Result<usize, CoreError> {
-        let source = registers.reg(self.source_register_index as usize);
+        let mut source = if let Operand::RegisterShifted(Shift::Register(_)) = self.operand {
+            registers.reg_with_shift_prefetch(self.source_register_index as usize)
+        } else {
+            registers.reg(self.source_register_index as usize)
+        };
+        if self.force_word_alignment {
+            source &= !0b11;
+        }
         let (operand, carry) = self.operand.value(registers);
-        let (result, overflow) = match self.operation {
+        let carry_in = registers.cpsr.carry as u32;
+        // Subtractions set the carry flag when no borrow occurs, additions when the unsigned
+        // result overflows.
+        let (result, arithmetic_carry) = match self.operation {
             DataProcessingOperation::And => (source & operand, false),
             DataProcessingOperation::Test => (source & operand, false),
             DataProcessingOperation::ExclusiveOr => (source ^ operand, false),
             DataProcessingOperation::TestEqual => (source ^ operand, false),
-            DataProcessingOperation::Subtract => {
-                let (result, overflow) = source.overflowing_sub(operand);
-                (result, overflow)
+            DataProcessingOperation::Subtract | DataProcessingOperation::Compare => {
+                let (result, borrow) = source.overflowing_sub(operand);
+                (result, !borrow)
             }
             DataProcessingOperation::ReverseSubtract => {
-                let (result, overflow) = operand.overflowing_sub(source);
-                (result, overflow)
+                let (result, borrow) = operand.overflowing_sub(source);
+                (result, !borrow)
             }
-            DataProcessingOperation::Add => {
-                let (result, overflow) = source.overflowing_add(operand);
-                (result, overflow)
+            DataProcessingOperation::Add | DataProcessingOperation::CompareNegate => {
+                source.overflowing_add(operand)
             }
             DataProcessingOperation::AddWithCarry => {
-                let (result, overflow1) = source.overflowing_add(operand);
-                let (result, overflow2) = result.overflowing_add(registers.cpsr.carry as u32);
-                (result, overflow1 || overflow2)
+                let (result, carry1) = source.overflowing_add(operand);
+                let (result, carry2) = result.overflowing_add(carry_in);
+                (result, carry1 || carry2)
             }
             DataProcessingOperation::SubtractWithCarry => {
-                let (result, overflow1) = source.overflowing_sub(operand);
-                let (result, overflow2) = result.overflowing_add(registers.cpsr.carry as u32 - 1);
-                (result, overflow1 || overflow2)
+                let (result, borrow1) = source.overflowing_sub(operand);
+                let (result, borrow2) = result.overflowing_sub(1 - carry_in);
+                (result, !(borrow1 || borrow2))
             }
             DataProcessingOperation::ReverseSubtractWithCarry => {
-                let (result, overflow1) = operand.overflowing_sub(source);
-                let (result, overflow2) = result.overflowing_add(registers.cpsr.carry as u32 - 1);
-                (result, overflow1 || overflow2)
-            }
-            DataProcessingOperation::Compare => {
-                let (result, overflow) = source.overflowing_sub(operand);
-                (result, overflow)
-            }
-            DataProcessingOperation::CompareNegate => {
-                let (result, overflow) = source.overflowing_add(operand);
-                (result, overflow)
+                let (result, borrow1) = operand.overflowing_sub(source);
+                let (result, borrow2) = result.overflowing_sub(1 - carry_in);
+                (result, !(borrow1 || borrow2))
             }
             DataProcessingOperation::Or => (source | operand, false),
             DataProcessingOperation::Move => (operand, false),
@@ -156,7 +165,22 @@ impl InstructionExecutor for DataProcessingInstruction {
         };
 
         if let Some(destination_register_index) = self.destination_register_index {
-            *registers.reg_mut(destination_register_index as usize) = result;
+            if destination_register_index == 15 {
+                // With the S bit set this returns from an exception. The CPSR is restored from
+                // the SPSR instead of the flags being updated, and before the jump so the target
+                // is aligned for the state being returned to. Modes without an SPSR keep the
+                // CPSR as it is.
+                if self.update_conditions {
+                    if registers.cpsr.mode.has_spsr() {
+                        registers.cpsr = registers.spsr();
+                    }
+                    registers.set_pc(result);
+                    return Ok(1);
+                }
+                registers.set_pc(result);
+            } else {
+                *registers.reg_mut(destination_register_index as usize) = result;
+            }
         }
 
         // Check if condition code should be updated.
@@ -171,19 +195,24 @@ impl InstructionExecutor for DataProcessingInstruction {
                 | DataProcessingOperation::AndNot
                 | DataProcessingOperation::MoveNegate => registers.cpsr.carry = carry,
                 DataProcessingOperation::Subtract
-                | DataProcessingOperation::ReverseSubtract
                 | DataProcessingOperation::SubtractWithCarry
-                | DataProcessingOperation::ReverseSubtractWithCarry
-                | DataProcessingOperation::Compare
-                | DataProcessingOperation::CompareNegate => {
-                    registers.cpsr.overflow = ((source ^ operand) & 0x80000000 != 0)
-                        && ((source ^ result) & 0x80000000 == 0);
-                    registers.cpsr.carry = overflow;
+                | DataProcessingOperation::Compare => {
+                    registers.cpsr.overflow =
+                        (source ^ operand) & (source ^ result) & 0x80000000 != 0;
+                    registers.cpsr.carry = arithmetic_carry;
+                }
+                DataProcessingOperation::ReverseSubtract
+                | DataProcessingOperation::ReverseSubtractWithCarry => {
+                    registers.cpsr.overflow =
+                        (operand ^ source) & (operand ^ result) & 0x80000000 != 0;
+                    registers.cpsr.carry = arithmetic_carry;
                 }
-                DataProcessingOperation::Add | DataProcessingOperation::AddWithCarry => {
-                    registers.cpsr.overflow = ((source ^ operand) & 0x80000000 == 0)
-                        && ((source ^ result) & 0x80000000 != 0);
-                    registers.cpsr.carry = overflow;
+                DataProcessingOperation::Add
+                | DataProcessingOperation::AddWithCarry
+                | DataProcessingOperation::CompareNegate => {
+                    registers.cpsr.overflow =
+                        !(source ^ operand) & (source ^ result) & 0x80000000 != 0;
+                    registers.cpsr.carry = arithmetic_carry;
                 }
             }
             registers.cpsr.zero = result == 0;
@@ -217,7 +246,6 @@ impl InstructionExecutor for DataProcessingInstruction {
             "{mnemonic}{}",
             if self.update_conditions { "s" } else { "" }
         )
-        .into()
     }
 
     fn description(&self, _registers: &RegisterBank, _bus: &mut Bus) -> String {
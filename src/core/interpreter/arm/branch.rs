@@ -40,19 +40,19 @@ impl InstructionExecutor for BranchInstruction {
         if let Some(link) = self.link {
             *registers.reg_mut(14) = link;
         }
-        registers.set_pc((registers.pc() as i32 + self.offset as i32) as u32);
+        registers.set_pc((registers.pc() as i32 + self.offset) as u32);
         Ok(BRANCH_CYCLE_COUNT)
     }
 
     fn mnemonic(&self) -> String {
-        if let Some(_) = self.link { "bl" } else { "b" }.into()
+        if self.link.is_some() { "bl" } else { "b" }.into()
     }
 
     fn description(&self, registers: &RegisterBank, _bus: &mut Bus) -> String {
         format!(
-            "{} (=${:X})",
+            "{} (=${:08X})",
             print_offset_as_immediate(self.offset),
-            (registers.pc() as i32 + self.offset as i32) as u32
+            registers.pc().wrapping_add(self.offset as u32)
         )
     }
 }
@@ -76,12 +76,12 @@ impl BranchAndExchangeInstruction {
 impl InstructionExecutor for BranchAndExchangeInstruction {
     fn execute(&self, registers: &mut RegisterBank, _bus: &mut Bus) -> Result<usize, CoreError> {
         let target_address = registers.reg(self.target_register as usize);
-        registers.set_pc(target_address & !1);
         registers.cpsr.instruction_mode = if target_address & 1 > 0 {
             InstructionMode::Thumb
         } else {
             InstructionMode::Arm
         };
+        registers.set_pc(target_address);
 
         Ok(BRANCH_CYCLE_COUNT)
     }
@@ -92,7 +92,7 @@ impl InstructionExecutor for BranchAndExchangeInstruction {
 
     fn description(&self, registers: &RegisterBank, _bus: &mut Bus) -> String {
         format!(
-            "r{} (=${:X})",
+            "r{} (=${:08X})",
             self.target_register,
             registers.reg(self.target_register as usize)
         )
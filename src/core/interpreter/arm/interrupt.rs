@@ -1,33 +1,179 @@
-use crate::core::{Bus, CoreError};
+use crate::core::{BiosFunction, Bus, CoreError};
 
 use crate::core::interpreter::{
-    disasm::print_offset_as_immediate, instruction::InstructionExecutor, register::RegisterBank,
+    disasm::print_offset_as_immediate,
+    instruction::InstructionExecutor,
+    register::RegisterBank,
+    status::{CpuMode, InstructionMode},
 };
 
 pub const SOFTWARE_INTERRUPT_MASK: u32 = 0b0000_1111_0000_0000_0000_0000_0000_0000;
 pub const SOFTWARE_INTERRUPT_FORMAT: u32 = 0b0000_1111_0000_0000_0000_0000_0000_0000;
 
-const SOFTWARE_INTERRUPT_PC_OFFSET: u32 = 8;
+const SOFTWARE_INTERRUPT_VECTOR: u32 = 8;
+
+/// SoftReset clears the top of IWRAM, where the BIOS keeps its variables and the stacks start.
+const SOFT_RESET_CLEAR_START: u32 = 0x3007E00;
+const SOFT_RESET_CLEAR_END: u32 = 0x3007FFF;
+/// A non-zero byte here makes SoftReset restart from EWRAM instead of the cartridge.
+const SOFT_RESET_FLAG: u32 = 0x3007FFA;
+
+const CPU_SET_COUNT_MASK: u32 = 0x1FFFFF;
+const CPU_SET_FILL: u32 = 1 << 24;
+const CPU_SET_WORD: u32 = 1 << 26;
 
 pub struct SoftwareInterruptInstruction {
     past_address: u32,
     comment: u32,
+    instruction_mode: InstructionMode,
+    /// Whether common BIOS calls are carried out here rather than by the BIOS.
+    high_level: bool,
 }
 
 impl SoftwareInterruptInstruction {
     pub fn decode(registers: &mut RegisterBank, opcode: u32) -> Self {
         Self {
             past_address: registers.pc(),
-            comment: opcode & 0x00FF_FFFF,
+            comment: match registers.cpsr.instruction_mode {
+                InstructionMode::Arm => opcode & 0x00FF_FFFF,
+                InstructionMode::Thumb => opcode & 0xFF,
+            },
+            instruction_mode: registers.cpsr.instruction_mode,
+            high_level: false,
+        }
+    }
+
+    /// Carries out the BIOS calls that have a high level implementation directly, without
+    /// entering the BIOS.
+    pub fn with_high_level_bios(mut self, high_level: bool) -> Self {
+        self.high_level = high_level;
+        self
+    }
+
+    /// Runs the selected BIOS function in place of the BIOS. Returns false for the functions that
+    /// are left to the BIOS, which include those that wait for interrupts and division by zero.
+    fn call_bios_function(
+        &self,
+        registers: &mut RegisterBank,
+        bus: &mut Bus,
+    ) -> Result<bool, CoreError> {
+        let Ok(function) = BiosFunction::try_from(self.bios_function()) else {
+            return Ok(false);
+        };
+        match function {
+            BiosFunction::SoftReset => soft_reset(registers, bus)?,
+            BiosFunction::Div | BiosFunction::DivArm => {
+                let (mut numerator, mut denominator) = (registers.reg(0), registers.reg(1));
+                if function == BiosFunction::DivArm {
+                    (numerator, denominator) = (denominator, numerator);
+                }
+                let (numerator, denominator) = (numerator as i32, denominator as i32);
+                if denominator == 0 {
+                    return Ok(false);
+                }
+                let quotient = numerator.wrapping_div(denominator);
+                *registers.reg_mut(0) = quotient as u32;
+                *registers.reg_mut(1) = numerator.wrapping_rem(denominator) as u32;
+                *registers.reg_mut(3) = quotient.unsigned_abs();
+            }
+            BiosFunction::Sqrt => *registers.reg_mut(0) = registers.reg(0).isqrt(),
+            BiosFunction::CpuSet => {
+                let control = registers.reg(2);
+                let word = control & CPU_SET_WORD > 0;
+                copy_or_fill(registers, bus, control & CPU_SET_COUNT_MASK, word, control)?;
+            }
+            BiosFunction::CpuFastSet => {
+                // Copies in blocks of eight words, so the count is rounded up.
+                let control = registers.reg(2);
+                let count = (control & CPU_SET_COUNT_MASK).next_multiple_of(8);
+                copy_or_fill(registers, bus, count, true, control)?;
+            }
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+
+    /// The BIOS function selected by the comment field. ARM code passes it in bits 16-23 of the
+    /// comment while Thumb code has only the 8 bit comment to put it in.
+    pub fn bios_function(&self) -> u8 {
+        match self.instruction_mode {
+            InstructionMode::Arm => (self.comment >> 16) as u8,
+            InstructionMode::Thumb => self.comment as u8,
         }
     }
 }
 
+/// The copy behind CpuSet and CpuFastSet, from the address in r0 to the one in r1. With
+/// `CPU_SET_FILL` set in `control` the first unit at the source is repeated instead.
+fn copy_or_fill(
+    registers: &RegisterBank,
+    bus: &mut Bus,
+    count: u32,
+    word: bool,
+    control: u32,
+) -> Result<(), CoreError> {
+    let unit_size = if word { 4 } else { 2 };
+    let mut source = registers.reg(0) & !(unit_size - 1);
+    let mut destination = registers.reg(1) & !(unit_size - 1);
+    let source_step = if control & CPU_SET_FILL > 0 {
+        0
+    } else {
+        unit_size
+    };
+    for _ in 0..count {
+        if word {
+            let data = bus.read_dword(source)?;
+            bus.write_dword(destination, data)?;
+        } else {
+            let data = bus.read_word(source)?;
+            bus.write_word(destination, data)?;
+        }
+        source = source.wrapping_add(source_step);
+        destination = destination.wrapping_add(unit_size);
+    }
+    Ok(())
+}
+
+/// Clears the BIOS's area of IWRAM and the registers, sets up the stacks like the boot sequence
+/// does and restarts the program.
+fn soft_reset(registers: &mut RegisterBank, bus: &mut Bus) -> Result<(), CoreError> {
+    let entry = if bus.read_byte(SOFT_RESET_FLAG)? != 0 {
+        0x2000000
+    } else {
+        0x8000000
+    };
+    for address in (SOFT_RESET_CLEAR_START..=SOFT_RESET_CLEAR_END).step_by(4) {
+        bus.write_dword(address, 0)?;
+    }
+
+    for (mode, stack) in [
+        (CpuMode::Supervisor, 0x3007FE0),
+        (CpuMode::Irq, 0x3007FA0),
+        (CpuMode::System, 0x3007F00),
+    ] {
+        *registers.reg_with_mode_mut(13, mode) = stack;
+        *registers.reg_with_mode_mut(14, mode) = 0;
+    }
+    for index in 0..13 {
+        *registers.reg_with_mode_mut(index, CpuMode::System) = 0;
+    }
+    registers.cpsr = Default::default();
+    registers.cpsr.mode = CpuMode::System;
+    registers.set_pc(entry);
+    Ok(())
+}
+
 impl InstructionExecutor for SoftwareInterruptInstruction {
-    fn execute(&self, registers: &mut RegisterBank, _bus: &mut Bus) -> Result<usize, CoreError> {
-        *registers.reg_mut(14) = self.past_address;
-        registers.set_pc(SOFTWARE_INTERRUPT_PC_OFFSET);
-        *registers.spsr_mut() = registers.cpsr;
+    fn execute(&self, registers: &mut RegisterBank, bus: &mut Bus) -> Result<usize, CoreError> {
+        if self.high_level && self.call_bios_function(registers, bus)? {
+            return Ok(1);
+        }
+
+        registers.enter_exception(
+            CpuMode::Supervisor,
+            SOFTWARE_INTERRUPT_VECTOR,
+            self.past_address,
+        );
 
         Ok(1)
     }
@@ -37,6 +183,13 @@ impl InstructionExecutor for SoftwareInterruptInstruction {
     }
 
     fn description(&self, _registers: &RegisterBank, _bus: &mut Bus) -> String {
-        print_offset_as_immediate(self.comment as i32)
+        match BiosFunction::try_from(self.bios_function()) {
+            Ok(function) => format!(
+                "{} ; {:?}",
+                print_offset_as_immediate(self.comment as i32),
+                function
+            ),
+            Err(_) => print_offset_as_immediate(self.comment as i32),
+        }
     }
 }
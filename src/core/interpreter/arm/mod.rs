@@ -8,6 +8,8 @@ mod multiply;
 pub use multiply::*;
 mod transfer;
 pub use transfer::*;
+mod undefined;
+pub use undefined::*;
 
 #[cfg(test)]
 mod tests;
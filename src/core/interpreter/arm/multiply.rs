@@ -1,3 +1,177 @@
+use crate::core::{Bus, CoreError};
+
+use crate::core::interpreter::{instruction::InstructionExecutor, register::RegisterBank};
+
 pub const MULTIPLY_MASK: u32 = 0b0000_1111_1000_0000_0000_0000_1111_0000;
 pub const MULTIPLY_FORMAT: u32 = 0b0000_0000_0000_0000_0000_0000_1001_0000;
 pub const MULTIPLY_LONG_FORMAT: u32 = 0b0000_0000_1000_0000_0000_0000_1001_0000;
+
+/// Number of internal cycles the multiplier array needs to finish based on how many of the top
+/// bytes of the multiplier are all zeroes, or all ones for a signed multiply.
+fn multiplier_cycles(multiplier: u32, signed: bool) -> usize {
+    let finished = |mask: u32| multiplier & mask == 0 || (signed && multiplier & mask == mask);
+    if finished(0xFFFF_FF00) {
+        1
+    } else if finished(0xFFFF_0000) {
+        2
+    } else if finished(0xFF00_0000) {
+        3
+    } else {
+        4
+    }
+}
+
+pub struct MultiplyInstruction {
+    accumulate: bool,
+    update_conditions: bool,
+    destination_register_index: u32,
+    accumulate_register_index: u32,
+    multiplier_register_index: u32,
+    multiplicand_register_index: u32,
+}
+
+impl MultiplyInstruction {
+    /// A plain multiply without accumulate, `rd := rm * rs`.
+    pub fn new(
+        update_conditions: bool,
+        destination_register_index: u32,
+        multiplicand_register_index: u32,
+        multiplier_register_index: u32,
+    ) -> Self {
+        Self {
+            accumulate: false,
+            update_conditions,
+            destination_register_index,
+            accumulate_register_index: 0,
+            multiplier_register_index,
+            multiplicand_register_index,
+        }
+    }
+
+    pub fn decode(opcode: u32) -> Self {
+        Self {
+            accumulate: opcode & (1 << 21) > 0,
+            update_conditions: opcode & (1 << 20) > 0,
+            destination_register_index: (opcode >> 16) & 0xF,
+            accumulate_register_index: (opcode >> 12) & 0xF,
+            multiplier_register_index: (opcode >> 8) & 0xF,
+            multiplicand_register_index: opcode & 0xF,
+        }
+    }
+}
+
+impl InstructionExecutor for MultiplyInstruction {
+    fn execute(&self, registers: &mut RegisterBank, _bus: &mut Bus) -> Result<usize, CoreError> {
+        let multiplier = registers.reg(self.multiplier_register_index as usize);
+        let mut result = registers
+            .reg(self.multiplicand_register_index as usize)
+            .wrapping_mul(multiplier);
+        if self.accumulate {
+            result = result.wrapping_add(registers.reg(self.accumulate_register_index as usize));
+        }
+
+        *registers.reg_mut(self.destination_register_index as usize) = result;
+
+        // The carry flag is left unchanged as its value is meaningless after a multiply and V is
+        // never affected.
+        if self.update_conditions {
+            registers.cpsr.zero = result == 0;
+            registers.cpsr.signed = result & (1 << 31) > 0;
+        }
+
+        Ok(1 + multiplier_cycles(multiplier, true) + self.accumulate as usize)
+    }
+
+    fn mnemonic(&self) -> String {
+        format!(
+            "{}{}",
+            if self.accumulate { "mla" } else { "mul" },
+            if self.update_conditions { "s" } else { "" }
+        )
+    }
+
+    fn description(&self, _registers: &RegisterBank, _bus: &mut Bus) -> String {
+        let mut desc = format!(
+            "r{}, r{}, r{}",
+            self.destination_register_index,
+            self.multiplicand_register_index,
+            self.multiplier_register_index
+        );
+        if self.accumulate {
+            desc += &format!(", r{}", self.accumulate_register_index);
+        }
+
+        desc
+    }
+}
+
+pub struct MultiplyLongInstruction {
+    signed: bool,
+    accumulate: bool,
+    update_conditions: bool,
+    destination_high_register_index: u32,
+    destination_low_register_index: u32,
+    multiplier_register_index: u32,
+    multiplicand_register_index: u32,
+}
+
+impl MultiplyLongInstruction {
+    pub fn decode(opcode: u32) -> Self {
+        Self {
+            signed: opcode & (1 << 22) > 0,
+            accumulate: opcode & (1 << 21) > 0,
+            update_conditions: opcode & (1 << 20) > 0,
+            destination_high_register_index: (opcode >> 16) & 0xF,
+            destination_low_register_index: (opcode >> 12) & 0xF,
+            multiplier_register_index: (opcode >> 8) & 0xF,
+            multiplicand_register_index: opcode & 0xF,
+        }
+    }
+}
+
+impl InstructionExecutor for MultiplyLongInstruction {
+    fn execute(&self, registers: &mut RegisterBank, _bus: &mut Bus) -> Result<usize, CoreError> {
+        let multiplier = registers.reg(self.multiplier_register_index as usize);
+        let multiplicand = registers.reg(self.multiplicand_register_index as usize);
+        let mut result = if self.signed {
+            (multiplicand as i32 as i64).wrapping_mul(multiplier as i32 as i64) as u64
+        } else {
+            (multiplicand as u64).wrapping_mul(multiplier as u64)
+        };
+
+        if self.accumulate {
+            let high = registers.reg(self.destination_high_register_index as usize) as u64;
+            let low = registers.reg(self.destination_low_register_index as usize) as u64;
+            result = result.wrapping_add((high << 32) | low);
+        }
+
+        *registers.reg_mut(self.destination_low_register_index as usize) = result as u32;
+        *registers.reg_mut(self.destination_high_register_index as usize) = (result >> 32) as u32;
+
+        if self.update_conditions {
+            registers.cpsr.zero = result == 0;
+            registers.cpsr.signed = result & (1 << 63) > 0;
+        }
+
+        Ok(2 + multiplier_cycles(multiplier, self.signed) + self.accumulate as usize)
+    }
+
+    fn mnemonic(&self) -> String {
+        format!(
+            "{}{}{}",
+            if self.signed { "s" } else { "u" },
+            if self.accumulate { "mlal" } else { "mull" },
+            if self.update_conditions { "s" } else { "" }
+        )
+    }
+
+    fn description(&self, _registers: &RegisterBank, _bus: &mut Bus) -> String {
+        format!(
+            "r{}, r{}, r{}, r{}",
+            self.destination_low_register_index,
+            self.destination_high_register_index,
+            self.multiplicand_register_index,
+            self.multiplier_register_index
+        )
+    }
+}
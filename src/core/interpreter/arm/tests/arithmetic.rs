@@ -0,0 +1,239 @@
+use std::{cell::RefCell, rc::Rc};
+
+use crate::core::{
+    interpreter::{
+        arm::DataProcessingInstruction, instruction::InstructionExecutor, register::RegisterBank,
+    },
+    memory::wram::Wram,
+    Bus, CoreError,
+};
+
+fn setup() -> (Bus, RegisterBank) {
+    let wram = Wram::new(0, 1024);
+
+    let mut bus = Bus::default();
+    bus.register_region(0..=1023, Rc::new(RefCell::new(wram)));
+
+    (bus, RegisterBank::default())
+}
+
+fn execute(registers: &mut RegisterBank, bus: &mut Bus, opcode: u32) -> Result<usize, CoreError> {
+    DataProcessingInstruction::decode(opcode).execute(registers, bus)
+}
+
+fn compare(left: u32, right: u32) -> Result<RegisterBank, CoreError> {
+    let (mut bus, mut registers) = setup();
+    *registers.reg_mut(0) = left;
+    *registers.reg_mut(1) = right;
+
+    // cmp r0, r1
+    execute(&mut registers, &mut bus, 0xE1500001)?;
+
+    Ok(registers)
+}
+
+#[test]
+fn cmp_equal() -> Result<(), CoreError> {
+    let registers = compare(5, 5)?;
+
+    assert!(registers.cpsr.zero);
+    assert!(registers.cpsr.carry);
+    assert!(!registers.cpsr.signed);
+
+    Ok(())
+}
+
+#[test]
+fn cmp_greater() -> Result<(), CoreError> {
+    let registers = compare(5, 3)?;
+
+    assert!(!registers.cpsr.zero);
+    assert!(registers.cpsr.carry);
+    assert!(!registers.cpsr.signed);
+
+    let registers = compare(0xFFFF_FFFF, 1)?;
+
+    assert!(registers.cpsr.carry);
+    assert!(registers.cpsr.signed);
+
+    Ok(())
+}
+
+#[test]
+fn cmp_less() -> Result<(), CoreError> {
+    let registers = compare(3, 5)?;
+
+    assert!(!registers.cpsr.zero);
+    assert!(!registers.cpsr.carry);
+    assert!(registers.cpsr.signed);
+
+    Ok(())
+}
+
+#[test]
+fn sbc_borrows_inverted_carry() -> Result<(), CoreError> {
+    let (mut bus, mut registers) = setup();
+    *registers.reg_mut(1) = 5;
+    *registers.reg_mut(2) = 5;
+
+    // sbcs r0, r1, r2
+    execute(&mut registers, &mut bus, 0xE0D10002)?;
+
+    assert_eq!(registers.reg(0), 0xFFFF_FFFF);
+    assert!(!registers.cpsr.carry);
+
+    // With the carry clear a borrow is taken, with it set the subtraction is exact.
+    registers.cpsr.carry = true;
+    execute(&mut registers, &mut bus, 0xE0D10002)?;
+
+    assert_eq!(registers.reg(0), 0);
+    assert!(registers.cpsr.carry);
+    assert!(registers.cpsr.zero);
+
+    Ok(())
+}
+
+#[test]
+fn sub_signed_overflow() -> Result<(), CoreError> {
+    let (mut bus, mut registers) = setup();
+    *registers.reg_mut(1) = 0x8000_0000;
+    *registers.reg_mut(2) = 1;
+
+    // subs r0, r1, r2
+    execute(&mut registers, &mut bus, 0xE0510002)?;
+
+    assert_eq!(registers.reg(0), 0x7FFF_FFFF);
+    assert!(registers.cpsr.overflow);
+    assert!(registers.cpsr.carry);
+
+    let registers = compare(5, 3)?;
+    assert!(!registers.cpsr.overflow);
+
+    Ok(())
+}
+
+#[test]
+fn add_signed_overflow() -> Result<(), CoreError> {
+    let (mut bus, mut registers) = setup();
+    *registers.reg_mut(1) = 0x7FFF_FFFF;
+    *registers.reg_mut(2) = 1;
+
+    // adds r0, r1, r2
+    execute(&mut registers, &mut bus, 0xE0910002)?;
+
+    assert_eq!(registers.reg(0), 0x8000_0000);
+    assert!(registers.cpsr.overflow);
+    assert!(!registers.cpsr.carry);
+
+    Ok(())
+}
+
+#[test]
+fn logical_preserves_overflow() -> Result<(), CoreError> {
+    let (mut bus, mut registers) = setup();
+    *registers.reg_mut(1) = 0xF0;
+    *registers.reg_mut(2) = 0x0F;
+    registers.cpsr.overflow = true;
+
+    // ands r0, r1, r2
+    execute(&mut registers, &mut bus, 0xE0110002)?;
+
+    assert!(registers.cpsr.zero);
+    assert!(registers.cpsr.overflow);
+
+    Ok(())
+}
+
+#[test]
+fn logical_carry_from_immediate_shift() -> Result<(), CoreError> {
+    let (mut bus, mut registers) = setup();
+    *registers.reg_mut(1) = 0x8000_0000;
+
+    // movs r0, r1, lsl #1
+    execute(&mut registers, &mut bus, 0xE1B00081)?;
+
+    assert_eq!(registers.reg(0), 0);
+    assert!(registers.cpsr.carry);
+
+    // movs r0, r1, lsl #0
+    registers.cpsr.carry = false;
+    execute(&mut registers, &mut bus, 0xE1B00001)?;
+
+    assert_eq!(registers.reg(0), 0x8000_0000);
+    assert!(!registers.cpsr.carry);
+
+    Ok(())
+}
+
+#[test]
+fn logical_carry_from_register_shift() -> Result<(), CoreError> {
+    let (mut bus, mut registers) = setup();
+    *registers.reg_mut(1) = 1;
+    *registers.reg_mut(2) = 1;
+
+    // movs r0, r1, lsr r2
+    execute(&mut registers, &mut bus, 0xE1B00231)?;
+
+    assert_eq!(registers.reg(0), 0);
+    assert!(registers.cpsr.carry);
+
+    // A shift of zero by register keeps the old carry.
+    *registers.reg_mut(2) = 0;
+    registers.cpsr.carry = false;
+    execute(&mut registers, &mut bus, 0xE1B00231)?;
+
+    assert_eq!(registers.reg(0), 1);
+    assert!(!registers.cpsr.carry);
+
+    // Only the bottom byte of the register is used.
+    *registers.reg_mut(1) = 0x8000_0000;
+    *registers.reg_mut(2) = 0x120;
+    execute(&mut registers, &mut bus, 0xE1B00231)?;
+
+    assert_eq!(registers.reg(0), 0);
+    assert!(registers.cpsr.carry);
+
+    Ok(())
+}
+
+#[test]
+fn logical_carry_from_rotated_immediate() -> Result<(), CoreError> {
+    let (mut bus, mut registers) = setup();
+
+    // movs r0, #0x80000000
+    execute(&mut registers, &mut bus, 0xE3B00102)?;
+
+    assert_eq!(registers.reg(0), 0x8000_0000);
+    assert!(registers.cpsr.carry);
+
+    // movs r0, #0x2
+    execute(&mut registers, &mut bus, 0xE3B00002)?;
+
+    assert_eq!(registers.reg(0), 2);
+    assert!(registers.cpsr.carry);
+
+    Ok(())
+}
+
+#[test]
+fn register_shift_reads_pc_plus_twelve() -> Result<(), CoreError> {
+    let (mut bus, mut registers) = setup();
+    // Executing the instruction at 0x100, so r15 reads as 0x108 normally.
+    *registers.reg_mut(15) = 0x108;
+    *registers.reg_mut(1) = 1;
+    *registers.reg_mut(2) = 2;
+
+    // add r0, pc, r1, lsl r2
+    execute(&mut registers, &mut bus, 0xE08F0211)?;
+    assert_eq!(registers.reg(0), 0x10C + 4);
+
+    // mov r0, pc, lsl r3
+    execute(&mut registers, &mut bus, 0xE1A0031F)?;
+    assert_eq!(registers.reg(0), 0x10C);
+
+    // add r0, pc, r1, lsl #2
+    execute(&mut registers, &mut bus, 0xE08F0101)?;
+    assert_eq!(registers.reg(0), 0x108 + 4);
+
+    Ok(())
+}
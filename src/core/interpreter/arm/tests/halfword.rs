@@ -0,0 +1,135 @@
+use std::{cell::RefCell, rc::Rc};
+
+use crate::core::{
+    interpreter::{instruction::Instruction, Interpreter},
+    memory::wram::Wram,
+    Bus, CoreError,
+};
+
+fn setup() -> (Bus, Interpreter) {
+    let wram = Wram::new(0, 1024);
+
+    let mut bus = Bus::default();
+    bus.register_region(0..=1023, Rc::new(RefCell::new(wram)));
+
+    (bus, Interpreter::default())
+}
+
+fn execute(cpu: &mut Interpreter, bus: &mut Bus, opcode: u32) -> Result<usize, CoreError> {
+    cpu.fetched_instruction = Some((opcode, 0));
+    cpu.decode_arm()?;
+
+    let operation = cpu.decoded_instruction.take().unwrap();
+    assert!(matches!(
+        operation.instruction,
+        Instruction::HalfwordDataTransfer(_)
+    ));
+    operation
+        .instruction
+        .executor()
+        .execute(&mut cpu.registers, bus)
+}
+
+#[test]
+fn ldrh_immediate_pre_index() -> Result<(), CoreError> {
+    let (mut bus, mut cpu) = setup();
+    bus.write_word(0x104, 0xBEEF)?;
+    *cpu.registers.reg_mut(1) = 0x100;
+
+    // ldrh r0, [r1, #4]
+    execute(&mut cpu, &mut bus, 0xE1D100B4)?;
+
+    assert_eq!(cpu.registers.reg(0), 0xBEEF);
+    assert_eq!(cpu.registers.reg(1), 0x100);
+
+    Ok(())
+}
+
+#[test]
+fn strh_immediate_post_index() -> Result<(), CoreError> {
+    let (mut bus, mut cpu) = setup();
+    *cpu.registers.reg_mut(0) = 0x1234_5678;
+    *cpu.registers.reg_mut(1) = 0x100;
+
+    // strh r0, [r1], #-6
+    execute(&mut cpu, &mut bus, 0xE04100B6)?;
+
+    assert_eq!(bus.read_dword(0x100)?, 0x5678);
+    assert_eq!(cpu.registers.reg(1), 0xFA);
+
+    Ok(())
+}
+
+#[test]
+fn ldrsb_immediate_write_back() -> Result<(), CoreError> {
+    let (mut bus, mut cpu) = setup();
+    bus.write_byte(0x112, 0x80)?;
+    *cpu.registers.reg_mut(1) = 0x100;
+
+    // ldrsb r0, [r1, #0x12]!
+    execute(&mut cpu, &mut bus, 0xE1F101D2)?;
+
+    assert_eq!(cpu.registers.reg(0), 0xFFFF_FF80);
+    assert_eq!(cpu.registers.reg(1), 0x112);
+
+    Ok(())
+}
+
+#[test]
+fn ldrsh_register_post_index() -> Result<(), CoreError> {
+    let (mut bus, mut cpu) = setup();
+    bus.write_word(0x100, 0x8001)?;
+    *cpu.registers.reg_mut(1) = 0x100;
+    *cpu.registers.reg_mut(2) = 0x20;
+
+    // ldrsh r0, [r1], r2
+    execute(&mut cpu, &mut bus, 0xE09100F2)?;
+
+    assert_eq!(cpu.registers.reg(0), 0xFFFF_8001);
+    assert_eq!(cpu.registers.reg(1), 0x120);
+
+    Ok(())
+}
+
+#[test]
+fn ldrh_zero_extends_ldrsh_sign_extends() -> Result<(), CoreError> {
+    let (mut bus, mut cpu) = setup();
+    bus.write_word(0x100, 0x8000)?;
+    *cpu.registers.reg_mut(1) = 0x100;
+
+    // ldrh r0, [r1]
+    execute(&mut cpu, &mut bus, 0xE1D100B0)?;
+    assert_eq!(cpu.registers.reg(0), 0x0000_8000);
+
+    // ldrsh r0, [r1]
+    execute(&mut cpu, &mut bus, 0xE1D100F0)?;
+    assert_eq!(cpu.registers.reg(0), 0xFFFF_8000);
+
+    Ok(())
+}
+
+#[test]
+fn ldrsh_misaligned_loads_byte() -> Result<(), CoreError> {
+    let (mut bus, mut cpu) = setup();
+    bus.write_word(0x100, 0x8070)?;
+    *cpu.registers.reg_mut(1) = 0x101;
+
+    // ldrsh r0, [r1]
+    execute(&mut cpu, &mut bus, 0xE1D100F0)?;
+    assert_eq!(cpu.registers.reg(0), 0xFFFF_FF80);
+
+    Ok(())
+}
+
+#[test]
+fn load_into_base_wins_over_write_back() -> Result<(), CoreError> {
+    let (mut bus, mut cpu) = setup();
+    bus.write_word(0x102, 0x1234)?;
+    *cpu.registers.reg_mut(1) = 0x100;
+
+    // ldrh r1, [r1, #2]!
+    execute(&mut cpu, &mut bus, 0xE1F110B2)?;
+    assert_eq!(cpu.registers.reg(1), 0x1234);
+
+    Ok(())
+}
@@ -0,0 +1,187 @@
+use std::{cell::RefCell, rc::Rc};
+
+use crate::core::{
+    interpreter::{
+        arm::SoftwareInterruptInstruction,
+        instruction::InstructionExecutor,
+        register::RegisterBank,
+        status::{CpuMode, InstructionMode},
+        Interpreter,
+    },
+    memory::wram::Wram,
+    BiosFunction, Bus, CoreError,
+};
+
+#[test]
+fn thumb_and_arm_select_same_function() {
+    let mut registers = RegisterBank::default();
+
+    // swi 0x060000
+    let arm = SoftwareInterruptInstruction::decode(&mut registers, 0xEF060000);
+    assert_eq!(
+        BiosFunction::try_from(arm.bios_function()),
+        Ok(BiosFunction::Div)
+    );
+
+    // swi 0x06
+    registers.cpsr.instruction_mode = InstructionMode::Thumb;
+    let thumb = SoftwareInterruptInstruction::decode(&mut registers, 0xDF06);
+    assert_eq!(
+        BiosFunction::try_from(thumb.bios_function()),
+        Ok(BiosFunction::Div)
+    );
+}
+
+#[test]
+fn thumb_ignores_upper_bits() {
+    let mut registers = RegisterBank::default();
+    registers.cpsr.instruction_mode = InstructionMode::Thumb;
+
+    // swi 0x0B, with junk above the 16 bit opcode
+    let thumb = SoftwareInterruptInstruction::decode(&mut registers, 0xFFFF_DF0B);
+    assert_eq!(
+        BiosFunction::try_from(thumb.bios_function()),
+        Ok(BiosFunction::CpuSet)
+    );
+}
+
+#[test]
+fn irq_line_enters_irq_mode() -> Result<(), CoreError> {
+    let mut bus = Bus::default();
+    bus.register_region(0..=1023, Rc::new(RefCell::new(Wram::new(0, 1024))));
+
+    let mut cpu = Interpreter::default();
+    cpu.jump_to(0x100, InstructionMode::Thumb);
+    for _ in 0..3 {
+        cpu.tick(&mut bus)?;
+    }
+
+    // Masked in the CPSR, so nothing happens.
+    cpu.registers.cpsr.irq_disable = true;
+    cpu.set_irq_line(true);
+    cpu.tick(&mut bus)?;
+    assert!(matches!(cpu.registers.cpsr.mode, CpuMode::User));
+
+    cpu.registers.cpsr.irq_disable = false;
+    let next_instruction = cpu.decoded_instruction.as_ref().unwrap().location;
+    cpu.tick(&mut bus)?;
+
+    let registers = &mut cpu.registers;
+    assert!(matches!(registers.cpsr.mode, CpuMode::Irq));
+    assert!(matches!(
+        registers.cpsr.instruction_mode,
+        InstructionMode::Arm
+    ));
+    assert!(registers.cpsr.irq_disable);
+    assert_eq!(registers.pc(), 0x1C);
+    assert_eq!(registers.reg(14), next_instruction + 4);
+    assert!(matches!(
+        registers.spsr().instruction_mode,
+        InstructionMode::Thumb
+    ));
+
+    Ok(())
+}
+
+fn call_bios(registers: &mut RegisterBank, bus: &mut Bus, function: u32) -> Result<(), CoreError> {
+    // swi function << 16
+    SoftwareInterruptInstruction::decode(registers, 0xEF000000 | (function << 16))
+        .with_high_level_bios(true)
+        .execute(registers, bus)?;
+    Ok(())
+}
+
+#[test]
+fn high_level_div() -> Result<(), CoreError> {
+    let mut bus = Bus::default();
+    let mut registers = RegisterBank::default();
+
+    *registers.reg_mut(0) = -7i32 as u32;
+    *registers.reg_mut(1) = 2;
+    call_bios(&mut registers, &mut bus, BiosFunction::Div as u32)?;
+    assert_eq!(registers.reg(0) as i32, -3);
+    assert_eq!(registers.reg(1) as i32, -1);
+    assert_eq!(registers.reg(3), 3);
+    // The call returns straight away instead of entering the BIOS.
+    assert!(matches!(registers.cpsr.mode, CpuMode::User));
+
+    // DivArm takes the operands the other way around.
+    *registers.reg_mut(0) = 3;
+    *registers.reg_mut(1) = 100;
+    call_bios(&mut registers, &mut bus, BiosFunction::DivArm as u32)?;
+    assert_eq!(registers.reg(0), 33);
+    assert_eq!(registers.reg(1), 1);
+
+    Ok(())
+}
+
+#[test]
+fn high_level_sqrt() -> Result<(), CoreError> {
+    let mut bus = Bus::default();
+    let mut registers = RegisterBank::default();
+
+    for (value, root) in [(0, 0), (1000, 31), (1024, 32), (u32::MAX, 0xFFFF)] {
+        *registers.reg_mut(0) = value;
+        call_bios(&mut registers, &mut bus, BiosFunction::Sqrt as u32)?;
+        assert_eq!(registers.reg(0), root);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn high_level_cpu_set_fill() -> Result<(), CoreError> {
+    let mut bus = Bus::default();
+    bus.register_region(0..=1023, Rc::new(RefCell::new(Wram::new(0, 1024))));
+    let mut registers = RegisterBank::default();
+    bus.write_dword(0x100, 0x12345678)?;
+
+    *registers.reg_mut(0) = 0x100;
+    *registers.reg_mut(1) = 0x200;
+    // Fill four words.
+    *registers.reg_mut(2) = (1 << 26) | (1 << 24) | 4;
+    call_bios(&mut registers, &mut bus, BiosFunction::CpuSet as u32)?;
+
+    for offset in (0..16).step_by(4) {
+        assert_eq!(bus.read_dword(0x200 + offset)?, 0x12345678);
+    }
+    assert_eq!(bus.read_dword(0x210)?, 0);
+
+    Ok(())
+}
+
+#[test]
+fn fiq_banks_high_registers() -> Result<(), CoreError> {
+    let mut bus = Bus::default();
+    bus.register_region(0..=1023, Rc::new(RefCell::new(Wram::new(0, 1024))));
+
+    let mut cpu = Interpreter::default();
+    cpu.jump_to(0x100, InstructionMode::Arm);
+    for i in 8..=14 {
+        *cpu.registers.reg_mut(i) = i as u32;
+    }
+    for _ in 0..2 {
+        cpu.tick(&mut bus)?;
+    }
+    let next_instruction = cpu.decoded_instruction.as_ref().unwrap().location;
+
+    cpu.enter_fiq();
+    for i in 8..=12 {
+        *cpu.registers.reg_mut(i) = 0x100 + i as u32;
+    }
+
+    let registers = &mut cpu.registers;
+    assert!(matches!(registers.cpsr.mode, CpuMode::Fiq));
+    assert!(registers.cpsr.irq_disable);
+    assert!(registers.cpsr.fiq_disable);
+    assert_eq!(registers.pc(), 0x1C);
+    assert_eq!(registers.reg(14), next_instruction + 4);
+    assert!(matches!(registers.spsr().mode, CpuMode::User));
+    for i in 8..=12 {
+        assert_eq!(registers.reg_with_mode(i, CpuMode::User), i as u32);
+        assert_eq!(registers.reg_with_mode(i, CpuMode::Fiq), 0x100 + i as u32);
+    }
+    assert_eq!(registers.reg_with_mode(14, CpuMode::User), 14);
+
+    Ok(())
+}
@@ -1 +1,9 @@
+pub mod arithmetic;
+pub mod halfword;
+pub mod interrupt;
+pub mod multiply;
+pub mod pipeline;
+// The block transfer tests index their expected values by register number.
+#[allow(clippy::needless_range_loop)]
 pub mod transfer;
+pub mod undefined;
@@ -0,0 +1,163 @@
+use std::{cell::RefCell, rc::Rc};
+
+use crate::core::{
+    interpreter::{
+        arm::{MultiplyInstruction, MultiplyLongInstruction},
+        instruction::InstructionExecutor,
+        register::RegisterBank,
+    },
+    memory::wram::Wram,
+    Bus, CoreError,
+};
+
+fn setup() -> (Bus, RegisterBank) {
+    let wram = Wram::new(0, 1024);
+
+    let mut bus = Bus::default();
+    bus.register_region(0..=1023, Rc::new(RefCell::new(wram)));
+
+    (bus, RegisterBank::default())
+}
+
+#[test]
+fn mul_unsigned_wraparound() -> Result<(), CoreError> {
+    let (mut bus, mut registers) = setup();
+
+    *registers.reg_mut(1) = 0xFFFF_FFFF;
+    *registers.reg_mut(2) = 2;
+    registers.cpsr.overflow = true;
+
+    // muls r0, r1, r2
+    MultiplyInstruction::decode(0xE0100291).execute(&mut registers, &mut bus)?;
+
+    assert_eq!(registers.reg(0), 0xFFFF_FFFE);
+    assert!(registers.cpsr.signed);
+    assert!(!registers.cpsr.zero);
+    assert!(registers.cpsr.overflow);
+
+    Ok(())
+}
+
+#[test]
+fn mul_signed() -> Result<(), CoreError> {
+    let (mut bus, mut registers) = setup();
+
+    *registers.reg_mut(1) = -3i32 as u32;
+    *registers.reg_mut(2) = 4;
+
+    // mul r0, r1, r2
+    MultiplyInstruction::decode(0xE0000291).execute(&mut registers, &mut bus)?;
+
+    assert_eq!(registers.reg(0) as i32, -12);
+    assert!(!registers.cpsr.signed);
+
+    Ok(())
+}
+
+#[test]
+fn mla_accumulates() -> Result<(), CoreError> {
+    let (mut bus, mut registers) = setup();
+
+    *registers.reg_mut(1) = 0x8000_0000;
+    *registers.reg_mut(2) = 2;
+    *registers.reg_mut(3) = 0;
+
+    // mlas r0, r1, r2, r3
+    let instruction = MultiplyInstruction::decode(0xE0303291);
+    instruction.execute(&mut registers, &mut bus)?;
+
+    assert_eq!(registers.reg(0), 0);
+    assert!(registers.cpsr.zero);
+
+    *registers.reg_mut(3) = 7;
+    instruction.execute(&mut registers, &mut bus)?;
+
+    assert_eq!(registers.reg(0), 7);
+    assert!(!registers.cpsr.zero);
+    assert_eq!(instruction.mnemonic(), "mlas");
+
+    Ok(())
+}
+
+#[test]
+fn umull_splits_result() -> Result<(), CoreError> {
+    let (mut bus, mut registers) = setup();
+
+    *registers.reg_mut(2) = 0xFFFF_FFFF;
+    *registers.reg_mut(3) = 0x10;
+
+    // umull r0, r1, r2, r3
+    MultiplyLongInstruction::decode(0xE0810392).execute(&mut registers, &mut bus)?;
+
+    assert_eq!(registers.reg(0), 0xFFFF_FFF0);
+    assert_eq!(registers.reg(1), 0xF);
+
+    Ok(())
+}
+
+#[test]
+fn smull_negative_product() -> Result<(), CoreError> {
+    let (mut bus, mut registers) = setup();
+
+    *registers.reg_mut(2) = -2i32 as u32;
+    *registers.reg_mut(3) = 0x8000_0000;
+
+    // smulls r0, r1, r2, r3
+    MultiplyLongInstruction::decode(0xE0D10392).execute(&mut registers, &mut bus)?;
+
+    // -2 * -2^31 = 2^32
+    assert_eq!(registers.reg(0), 0);
+    assert_eq!(registers.reg(1), 1);
+    assert!(!registers.cpsr.signed);
+    assert!(!registers.cpsr.zero);
+
+    *registers.reg_mut(2) = -3i32 as u32;
+    *registers.reg_mut(3) = 5;
+    MultiplyLongInstruction::decode(0xE0D10392).execute(&mut registers, &mut bus)?;
+
+    assert_eq!(registers.reg(0), -15i32 as u32);
+    assert_eq!(registers.reg(1), 0xFFFF_FFFF);
+    assert!(registers.cpsr.signed);
+
+    Ok(())
+}
+
+#[test]
+fn smlal_accumulates() -> Result<(), CoreError> {
+    let (mut bus, mut registers) = setup();
+
+    *registers.reg_mut(0) = 0xFFFF_FFFF;
+    *registers.reg_mut(1) = 0;
+    *registers.reg_mut(2) = -1i32 as u32;
+    *registers.reg_mut(3) = 1;
+
+    // smlals r0, r1, r2, r3
+    let instruction = MultiplyLongInstruction::decode(0xE0F10392);
+    instruction.execute(&mut registers, &mut bus)?;
+
+    assert_eq!(registers.reg(0), 0xFFFF_FFFE);
+    assert_eq!(registers.reg(1), 0);
+    assert_eq!(instruction.mnemonic(), "smlals");
+
+    Ok(())
+}
+
+#[test]
+fn umull_only_finishes_early_on_leading_zeros() -> Result<(), CoreError> {
+    let (mut bus, mut registers) = setup();
+    *registers.reg_mut(3) = 0xFFFF_FFFF;
+
+    // umull r0, r1, r2, r3
+    let cycles = MultiplyLongInstruction::decode(0xE0810392).execute(&mut registers, &mut bus)?;
+    assert_eq!(cycles, 6);
+
+    // smull r0, r1, r2, r3
+    let cycles = MultiplyLongInstruction::decode(0xE0C10392).execute(&mut registers, &mut bus)?;
+    assert_eq!(cycles, 3);
+
+    *registers.reg_mut(3) = 0xFF;
+    let cycles = MultiplyLongInstruction::decode(0xE0810392).execute(&mut registers, &mut bus)?;
+    assert_eq!(cycles, 3);
+
+    Ok(())
+}
@@ -0,0 +1,103 @@
+use std::{cell::RefCell, rc::Rc};
+
+use crate::core::{
+    interpreter::{
+        status::{CpuMode, InstructionMode},
+        Interpreter,
+    },
+    memory::wram::Wram,
+    Bus, CoreError,
+};
+
+/// Runs `code` from 0x100 until `mov r3, #1` at 0x200 has executed. The two instructions after
+/// the first set r1 and r2, so they show whether the pipeline was flushed.
+fn run_jump(code: u32, setup: impl FnOnce(&mut Interpreter)) -> Result<Interpreter, CoreError> {
+    let mut bus = Bus::default();
+    bus.register_region(0..=1023, Rc::new(RefCell::new(Wram::new(0, 1024))));
+    bus.write_dword(0x100, code)?;
+    // mov r1, #1
+    bus.write_dword(0x104, 0xE3A01001)?;
+    // mov r2, #1
+    bus.write_dword(0x108, 0xE3A02001)?;
+    // mov r3, #1
+    bus.write_dword(0x200, 0xE3A03001)?;
+    bus.write_dword(0x300, 0x200)?;
+
+    let mut cpu = Interpreter::default();
+    cpu.jump_to(0x100, InstructionMode::Arm);
+    setup(&mut cpu);
+    for _ in 0..5 {
+        cpu.tick(&mut bus)?;
+    }
+
+    Ok(cpu)
+}
+
+#[test]
+fn mov_pc_flushes_pipeline() -> Result<(), CoreError> {
+    // mov pc, r0
+    let cpu = run_jump(0xE1A0F000, |cpu| *cpu.registers.reg_mut(0) = 0x200)?;
+
+    assert_eq!(cpu.registers.reg(1), 0);
+    assert_eq!(cpu.registers.reg(2), 0);
+    assert_eq!(cpu.registers.reg(3), 1);
+
+    Ok(())
+}
+
+#[test]
+fn ldr_pc_flushes_pipeline() -> Result<(), CoreError> {
+    // ldr pc, [r4]
+    let cpu = run_jump(0xE594F000, |cpu| *cpu.registers.reg_mut(4) = 0x300)?;
+
+    assert_eq!(cpu.registers.reg(1), 0);
+    assert_eq!(cpu.registers.reg(2), 0);
+    assert_eq!(cpu.registers.reg(3), 1);
+
+    Ok(())
+}
+
+#[test]
+fn mov_pc_stays_in_arm_state() -> Result<(), CoreError> {
+    // mov pc, r0
+    let cpu = run_jump(0xE1A0F000, |cpu| *cpu.registers.reg_mut(0) = 0x201)?;
+
+    assert!(matches!(
+        cpu.registers.cpsr.instruction_mode,
+        InstructionMode::Arm
+    ));
+    assert_eq!(cpu.registers.reg(3), 1);
+
+    Ok(())
+}
+
+#[test]
+fn bx_switches_to_thumb_state() -> Result<(), CoreError> {
+    // bx r0
+    let cpu = run_jump(0xE12FFF10, |cpu| *cpu.registers.reg_mut(0) = 0x201)?;
+
+    assert!(matches!(
+        cpu.registers.cpsr.instruction_mode,
+        InstructionMode::Thumb
+    ));
+    assert_eq!(cpu.registers.reg(1), 0);
+
+    Ok(())
+}
+
+#[test]
+fn movs_pc_without_spsr_keeps_cpsr() -> Result<(), CoreError> {
+    // movs pc, lr
+    let cpu = run_jump(0xE1B0F00E, |cpu| {
+        cpu.registers.cpsr.mode = CpuMode::System;
+        cpu.registers.cpsr.carry = true;
+        *cpu.registers.reg_mut(14) = 0x200;
+    })?;
+
+    assert!(matches!(cpu.registers.cpsr.mode, CpuMode::System));
+    assert!(cpu.registers.cpsr.carry);
+    assert_eq!(cpu.registers.reg(1), 0);
+    assert_eq!(cpu.registers.reg(3), 1);
+
+    Ok(())
+}
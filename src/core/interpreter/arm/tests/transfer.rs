@@ -2,7 +2,13 @@ use std::{cell::RefCell, rc::Rc};
 
 use crate::core::{
     interpreter::{
-        arm::BlockDataTransferInstruction, instruction::InstructionExecutor, register::RegisterBank,
+        arm::{
+            BlockDataTransferInstruction, PsrTransferMrsInstruction, PsrTransferMsrInstruction,
+            SingleDataTransferInstruction,
+        },
+        instruction::InstructionExecutor,
+        register::RegisterBank,
+        status::CpuMode,
     },
     memory::wram::Wram,
     Bus, CoreError,
@@ -92,7 +98,7 @@ fn stmda() -> Result<(), CoreError> {
     for i in 0..(EXPECTED_RESULT.len()) {
         *registers.reg_mut(i) = EXPECTED_RESULT[i];
     }
-    *registers.reg_mut(13) = 12;
+    *registers.reg_mut(13) = 16;
 
     let instruction =
         BlockDataTransferInstruction::new(13, 0b1111, false, true, false, false, false, 4);
@@ -100,10 +106,10 @@ fn stmda() -> Result<(), CoreError> {
     let _ = instruction.execute(&mut registers, &mut bus);
 
     let result = [
-        bus.read_dword(0)?,
         bus.read_dword(4)?,
         bus.read_dword(8)?,
         bus.read_dword(12)?,
+        bus.read_dword(16)?,
     ];
 
     assert_eq!(result[0], EXPECTED_RESULT[0]);
@@ -148,3 +154,272 @@ fn stmdb() -> Result<(), CoreError> {
 
     Ok(())
 }
+
+#[test]
+fn ldmia_round_trip() -> Result<(), CoreError> {
+    const VALUES: [u32; 4] = [0x11111111, 0x22222222, 0x33333333, 0x44444444];
+
+    let (mut bus, mut registers) = setup();
+
+    for (i, value) in VALUES.iter().enumerate() {
+        *registers.reg_mut(i + 1) = *value;
+    }
+    *registers.reg_mut(0) = 0x100;
+
+    // stmia r0!, {r1-r4}
+    let store = BlockDataTransferInstruction::decode(&mut registers, 0xE8A0001E);
+    store.execute(&mut registers, &mut bus)?;
+    assert_eq!(registers.reg(0), 0x110);
+
+    // ldmdb r0!, {r5-r8}
+    let load = BlockDataTransferInstruction::decode(&mut registers, 0xE93001E0);
+    load.execute(&mut registers, &mut bus)?;
+    assert_eq!(registers.reg(0), 0x100);
+
+    // ldmia r0, {r9-r12}
+    let load = BlockDataTransferInstruction::decode(&mut registers, 0xE8901E00);
+    load.execute(&mut registers, &mut bus)?;
+
+    for (i, value) in VALUES.iter().enumerate() {
+        assert_eq!(registers.reg(i + 5), *value);
+        assert_eq!(registers.reg(i + 9), *value);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn ldmia_base_in_list_keeps_loaded_value() -> Result<(), CoreError> {
+    let (mut bus, mut registers) = setup();
+    bus.write_dword(0x100, 0xAAAA)?;
+    bus.write_dword(0x104, 0xBBBB)?;
+    *registers.reg_mut(0) = 0x100;
+
+    // ldmia r0!, {r0, r1}
+    let instruction = BlockDataTransferInstruction::decode(&mut registers, 0xE8B00003);
+    instruction.execute(&mut registers, &mut bus)?;
+
+    assert_eq!(registers.reg(0), 0xAAAA);
+    assert_eq!(registers.reg(1), 0xBBBB);
+
+    // ldmia r1!, {r0, r1} loads the base last, with the same result.
+    *registers.reg_mut(1) = 0x100;
+    let instruction = BlockDataTransferInstruction::decode(&mut registers, 0xE8B10003);
+    instruction.execute(&mut registers, &mut bus)?;
+
+    assert_eq!(registers.reg(0), 0xAAAA);
+    assert_eq!(registers.reg(1), 0xBBBB);
+
+    Ok(())
+}
+
+#[test]
+fn stmia_base_in_list() -> Result<(), CoreError> {
+    let (mut bus, mut registers) = setup();
+
+    // stmia r0!, {r0, r1} stores the original base since it is first.
+    *registers.reg_mut(0) = 0x100;
+    *registers.reg_mut(1) = 0x1111;
+    let instruction = BlockDataTransferInstruction::decode(&mut registers, 0xE8A00003);
+    instruction.execute(&mut registers, &mut bus)?;
+
+    assert_eq!(bus.read_dword(0x100)?, 0x100);
+    assert_eq!(bus.read_dword(0x104)?, 0x1111);
+    assert_eq!(registers.reg(0), 0x108);
+
+    // stmia r1!, {r0, r1} stores the written back base since it is not.
+    *registers.reg_mut(0) = 0x2222;
+    *registers.reg_mut(1) = 0x200;
+    let instruction = BlockDataTransferInstruction::decode(&mut registers, 0xE8A10003);
+    instruction.execute(&mut registers, &mut bus)?;
+
+    assert_eq!(bus.read_dword(0x200)?, 0x2222);
+    assert_eq!(bus.read_dword(0x204)?, 0x208);
+    assert_eq!(registers.reg(1), 0x208);
+
+    Ok(())
+}
+
+#[test]
+fn ldr_pre_indexed_write_back() -> Result<(), CoreError> {
+    let (mut bus, mut registers) = setup();
+    bus.write_dword(0x104, 0xDEADBEEF)?;
+    *registers.reg_mut(1) = 0x100;
+
+    // ldr r0, [r1, #4]!
+    let instruction = SingleDataTransferInstruction::decode(0xE5B10004);
+    instruction.execute(&mut registers, &mut bus)?;
+
+    assert_eq!(registers.reg(0), 0xDEADBEEF);
+    assert_eq!(registers.reg(1), 0x104);
+
+    Ok(())
+}
+
+#[test]
+fn ldr_post_indexed() -> Result<(), CoreError> {
+    let (mut bus, mut registers) = setup();
+    bus.write_dword(0x100, 0xDEADBEEF)?;
+    *registers.reg_mut(1) = 0x100;
+
+    // ldr r0, [r1], #4
+    let instruction = SingleDataTransferInstruction::decode(0xE4910004);
+    instruction.execute(&mut registers, &mut bus)?;
+
+    assert_eq!(registers.reg(0), 0xDEADBEEF);
+    assert_eq!(registers.reg(1), 0x104);
+
+    Ok(())
+}
+
+#[test]
+fn ldr_post_indexed_into_base_keeps_loaded_value() -> Result<(), CoreError> {
+    let (mut bus, mut registers) = setup();
+    bus.write_dword(0x100, 0xDEADBEEF)?;
+    *registers.reg_mut(1) = 0x100;
+
+    // ldr r1, [r1], #4
+    let instruction = SingleDataTransferInstruction::decode(0xE4911004);
+    instruction.execute(&mut registers, &mut bus)?;
+
+    assert_eq!(registers.reg(1), 0xDEADBEEF);
+
+    Ok(())
+}
+
+#[test]
+fn str_pc_stores_address_plus_12() -> Result<(), CoreError> {
+    let (mut bus, mut registers) = setup();
+    // Executing the instruction at 0x100.
+    *registers.reg_mut(15) = 0x108;
+    *registers.reg_mut(0) = 0x200;
+
+    // str r15, [r0]
+    let instruction = SingleDataTransferInstruction::decode(0xE580F000);
+    instruction.execute(&mut registers, &mut bus)?;
+
+    assert_eq!(bus.read_dword(0x200)?, 0x10C);
+
+    Ok(())
+}
+
+#[test]
+fn stmia_pc_stores_address_plus_12() -> Result<(), CoreError> {
+    let (mut bus, mut registers) = setup();
+    // Executing the instruction at 0x100.
+    *registers.reg_mut(15) = 0x108;
+    *registers.reg_mut(0) = 0x200;
+
+    // stmia r0, {r15}
+    let instruction = BlockDataTransferInstruction::decode(&mut registers, 0xE8808000);
+    instruction.execute(&mut registers, &mut bus)?;
+
+    assert_eq!(bus.read_dword(0x200)?, 0x10C);
+
+    Ok(())
+}
+
+#[test]
+fn ldm_pc_with_psr_writes_back_to_the_original_bank() -> Result<(), CoreError> {
+    let (mut bus, mut registers) = setup();
+    bus.write_dword(0x100, 0x200)?;
+    registers.cpsr.mode = CpuMode::Irq;
+    registers.spsr_mut().mode = CpuMode::System;
+    *registers.reg_with_mode_mut(13, CpuMode::System) = 0x300;
+    *registers.reg_mut(13) = 0x100;
+
+    // ldmfd sp!, {pc}^
+    let instruction = BlockDataTransferInstruction::decode(&mut registers, 0xE8FD8000);
+    instruction.execute(&mut registers, &mut bus)?;
+
+    assert!(matches!(registers.cpsr.mode, CpuMode::System));
+    assert_eq!(registers.pc(), 0x200);
+    assert_eq!(registers.reg_with_mode(13, CpuMode::Irq), 0x104);
+    assert_eq!(registers.reg(13), 0x300);
+
+    Ok(())
+}
+
+#[test]
+fn msr_mrs_round_trip_sticky_overflow() -> Result<(), CoreError> {
+    let (mut bus, mut registers) = setup();
+    registers.cpsr.mode = CpuMode::Supervisor;
+    *registers.reg_mut(0) = 0x98000013;
+
+    // msr cpsr_f, r0
+    PsrTransferMsrInstruction::decode(0xE128F000).execute(&mut registers, &mut bus)?;
+    assert!(registers.cpsr.signed);
+    assert!(registers.cpsr.overflow);
+    assert!(registers.cpsr.sticky_overflow);
+
+    // mrs r1, cpsr
+    PsrTransferMrsInstruction::decode(0xE10F1000).execute(&mut registers, &mut bus)?;
+    assert_eq!(registers.reg(1) & 0xF8000000, 0x98000000);
+
+    // msr cpsr_f, #0x08000000
+    PsrTransferMsrInstruction::decode(0xE328F302).execute(&mut registers, &mut bus)?;
+    assert!(!registers.cpsr.signed);
+    assert!(!registers.cpsr.overflow);
+    assert!(registers.cpsr.sticky_overflow);
+
+    Ok(())
+}
+
+#[test]
+fn msr_flags_leaves_control_alone() -> Result<(), CoreError> {
+    let (mut bus, mut registers) = setup();
+    registers.cpsr.mode = CpuMode::Supervisor;
+    *registers.reg_mut(0) = 0x600000DF;
+
+    // msr cpsr_f, r0
+    PsrTransferMsrInstruction::decode(0xE128F000).execute(&mut registers, &mut bus)?;
+
+    assert_eq!(registers.cpsr.to_u32(), 0x60000013);
+
+    Ok(())
+}
+
+#[test]
+fn msr_control_leaves_flags_alone() -> Result<(), CoreError> {
+    let (mut bus, mut registers) = setup();
+    registers.cpsr.mode = CpuMode::Supervisor;
+    registers.cpsr.carry = true;
+    *registers.reg_mut(0) = 0xF00000D2;
+
+    // msr cpsr_c, r0
+    let instruction = PsrTransferMsrInstruction::decode(0xE121F000);
+    instruction.execute(&mut registers, &mut bus)?;
+
+    assert_eq!(registers.cpsr.to_u32(), 0x200000D2);
+    assert_eq!(
+        instruction.description(&registers, &mut bus),
+        "cpsr_c, r0, LSL, #0"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn msr_in_user_mode_only_writes_flags() -> Result<(), CoreError> {
+    let (mut bus, mut registers) = setup();
+    *registers.reg_mut(0) = 0x800000D3;
+
+    // msr cpsr_fc, r0
+    PsrTransferMsrInstruction::decode(0xE129F000).execute(&mut registers, &mut bus)?;
+
+    assert_eq!(registers.cpsr.to_u32(), 0x80000010);
+
+    Ok(())
+}
+
+#[test]
+fn msr_in_user_mode_cannot_change_mode() -> Result<(), CoreError> {
+    let (mut bus, mut registers) = setup();
+
+    // msr cpsr_c, #0x1F
+    PsrTransferMsrInstruction::decode(0xE321F01F).execute(&mut registers, &mut bus)?;
+
+    assert!(matches!(registers.cpsr.mode, CpuMode::User));
+
+    Ok(())
+}
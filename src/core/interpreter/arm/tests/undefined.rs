@@ -0,0 +1,124 @@
+use std::{cell::RefCell, rc::Rc};
+
+use crate::core::{
+    interpreter::{
+        arm::{Armv5Instruction, UndefinedInstruction},
+        instruction::InstructionExecutor,
+        register::RegisterBank,
+        status::{CpuMode, InstructionMode},
+        Interpreter, UnimplementedOpcodePolicy,
+    },
+    memory::wram::Wram,
+    Bus, CoreError,
+};
+
+fn setup() -> (Bus, RegisterBank) {
+    let wram = Wram::new(0, 1024);
+
+    let mut bus = Bus::default();
+    bus.register_region(0..=1023, Rc::new(RefCell::new(wram)));
+
+    (bus, RegisterBank::default())
+}
+
+#[test]
+fn clz_is_unavailable_armv5() -> Result<(), CoreError> {
+    let (mut bus, mut registers) = setup();
+    *registers.reg_mut(15) = 0x104;
+
+    // clz r0, r1
+    let opcode = 0xE16F0F11;
+    assert_eq!(
+        Armv5Instruction::decode(opcode),
+        Some(Armv5Instruction::CountLeadingZeros)
+    );
+
+    let instruction = UndefinedInstruction::decode(&mut registers, opcode);
+    assert!(instruction
+        .description(&registers, &mut bus)
+        .contains("ARMv5 instruction not present on ARMv4T"));
+
+    instruction.execute(&mut registers, &mut bus)?;
+
+    assert!(matches!(registers.cpsr.mode, CpuMode::Undefined));
+    assert!(matches!(registers.spsr().mode, CpuMode::User));
+    assert_eq!(registers.reg(14), 0x104);
+    assert_eq!(registers.pc(), 0x04);
+
+    Ok(())
+}
+
+#[test]
+fn armv4_opcodes_are_not_armv5() {
+    // bx r0
+    assert_eq!(Armv5Instruction::decode(0xE12FFF10), None);
+    // mul r0, r1, r2
+    assert_eq!(Armv5Instruction::decode(0xE0000291), None);
+    // ldrh r0, [r1]
+    assert_eq!(Armv5Instruction::decode(0xE1D100B0), None);
+    // swp r0, r2, [r1]
+    assert_eq!(Armv5Instruction::decode(0xE1010092), None);
+    // ldrsh r0, [r1]
+    assert_eq!(Armv5Instruction::decode(0xE1D100F0), None);
+    // mrs r0, cpsr
+    assert_eq!(Armv5Instruction::decode(0xE10F0000), None);
+}
+
+/// Places an opcode the decoder does not handle followed by `mov r0, #5` and returns a CPU
+/// about to run them.
+fn setup_unimplemented(policy: UnimplementedOpcodePolicy) -> Result<(Bus, Interpreter), CoreError> {
+    let (mut bus, _) = setup();
+    // mcr p0, 0, r0, c0, c0, 0
+    bus.write_dword(0, 0xEE000010)?;
+    // mov r0, #5
+    bus.write_dword(4, 0xE3A00005)?;
+
+    let mut cpu = Interpreter {
+        unimplemented_opcode_policy: policy,
+        ..Default::default()
+    };
+    cpu.jump_to(0, InstructionMode::Arm);
+    Ok((bus, cpu))
+}
+
+#[test]
+fn unimplemented_opcode_aborts_by_default() -> Result<(), CoreError> {
+    let (mut bus, mut cpu) = setup_unimplemented(UnimplementedOpcodePolicy::default())?;
+
+    cpu.tick(&mut bus)?;
+    assert!(matches!(
+        cpu.tick(&mut bus),
+        Err(CoreError::OpcodeNotImplemented(0xEE000010))
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn unimplemented_opcode_can_be_skipped() -> Result<(), CoreError> {
+    let (mut bus, mut cpu) = setup_unimplemented(UnimplementedOpcodePolicy::Skip)?;
+
+    for _ in 0..4 {
+        cpu.tick(&mut bus)?;
+    }
+
+    assert_eq!(cpu.registers.reg(0), 5);
+    assert!(matches!(cpu.registers.cpsr.mode, CpuMode::User));
+
+    Ok(())
+}
+
+#[test]
+fn unimplemented_opcode_can_raise_undefined() -> Result<(), CoreError> {
+    let (mut bus, mut cpu) = setup_unimplemented(UnimplementedOpcodePolicy::UndefinedException)?;
+
+    for _ in 0..3 {
+        cpu.tick(&mut bus)?;
+    }
+
+    assert!(matches!(cpu.registers.cpsr.mode, CpuMode::Undefined));
+    assert_eq!(cpu.registers.reg(14), 4);
+    assert_eq!(cpu.registers.reg(0), 0);
+
+    Ok(())
+}
@@ -4,7 +4,7 @@ use crate::core::{Bus, CoreError};
 use crate::core::interpreter::{
     instruction::{InstructionExecutor, Operand},
     register::RegisterBank,
-    shift::Shift,
+    shift::{rotated_immediate, Shift},
     status::CpuMode,
 };
 
@@ -23,9 +23,16 @@ pub const PSR_TRANSFER_MSR_FORMAT: u32 = 0b0000_0001_0010_0000_1111_0000_0000_00
 pub const SINGLE_DATA_SWAP_MASK: u32 = 0b0000_1111_1000_0000_0000_1111_1111_0000;
 pub const SINGLE_DATA_SWAP_FORMAT: u32 = 0b0000_0001_0000_0000_0000_0000_1001_0000;
 
-pub const HALFWORD_DATA_TRANSFER_REG_MASK: u32 = 0b0000_1110_0000_0000_0000_1111_1001_0000;
+pub const HALFWORD_DATA_TRANSFER_REG_MASK: u32 = 0b0000_1110_0100_0000_0000_1111_1001_0000;
 pub const HALFWORD_DATA_TRANSFER_REG_FORMAT: u32 = 0b0000_0000_0000_0000_0000_0000_1001_0000;
 
+pub const HALFWORD_DATA_TRANSFER_IMM_MASK: u32 = 0b0000_1110_0100_0000_0000_0000_1001_0000;
+pub const HALFWORD_DATA_TRANSFER_IMM_FORMAT: u32 = 0b0000_0000_0100_0000_0000_0000_1001_0000;
+
+/// r15 reads as the instruction's address + 8 when it executes, since two more instructions have
+/// been fetched behind it, but stores of r15 write the address + 12.
+const STORED_PC_OFFSET: u32 = 4;
+
 pub struct SingleDataTransferInstruction {
     source_register_index: u32,
     base_register_index: u32,
@@ -39,6 +46,7 @@ pub struct SingleDataTransferInstruction {
 }
 
 impl SingleDataTransferInstruction {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         source_register_index: u32,
         base_register_index: u32,
@@ -63,12 +71,9 @@ impl SingleDataTransferInstruction {
         }
     }
 
-    pub fn decode(registers: &mut RegisterBank, opcode: u32) -> Self {
+    pub fn decode(opcode: u32) -> Self {
         let offset = if opcode & (1 << 25) > 0 {
-            match Shift::from_opcode(opcode) {
-                Shift::Immediate(shift) => Operand::Immediate(shift.shift(registers)),
-                Shift::Register(shift) => Operand::RegisterShifted(Shift::Register(shift)),
-            }
+            Operand::RegisterShifted(Shift::from_opcode(opcode))
         } else {
             Operand::Immediate((opcode & 0xFFF, false))
         };
@@ -86,33 +91,35 @@ impl SingleDataTransferInstruction {
         }
     }
 
-    fn calculate_address(&self, registers: &RegisterBank) -> u32 {
-        let mut address = registers.reg(self.base_register_index as usize);
-
+    /// Returns the address accessed and the address written back to the base register. Both
+    /// come from the registers as they are before the transfer, so a load into the base or offset
+    /// register cannot change them.
+    fn addresses(&self, registers: &RegisterBank) -> (u32, u32) {
+        let mut base = registers.reg(self.base_register_index as usize);
         if self.force_word_alignment {
-            address &= !0b10;
+            base &= !0b10;
         }
 
-        if self.pre_index {
-            self.offset_address(address, registers)
+        let offset = self.offset.value(registers).0;
+        let indexed = if self.up {
+            base.wrapping_add(offset)
         } else {
-            address
-        }
-    }
+            base.wrapping_sub(offset)
+        };
 
-    fn offset_address(&self, address: u32, registers: &RegisterBank) -> u32 {
-        let offset = self.offset.value(registers);
-        if self.up {
-            address.wrapping_add(offset.0)
+        if self.pre_index {
+            (indexed, indexed)
         } else {
-            address.wrapping_sub(offset.0)
+            (base, indexed)
         }
     }
 }
 
 impl InstructionExecutor for SingleDataTransferInstruction {
     fn execute(&self, registers: &mut RegisterBank, bus: &mut Bus) -> Result<usize, CoreError> {
-        let address = self.calculate_address(registers);
+        let (address, write_back_address) = self.addresses(registers);
+        // Post-indexed transfers always write back.
+        let write_back = self.write_back || !self.pre_index;
 
         let mode = if !self.pre_index && self.write_back {
             CpuMode::User
@@ -125,17 +132,20 @@ impl InstructionExecutor for SingleDataTransferInstruction {
             } else {
                 bus.read_dword(address)?
             };
-            *registers.reg_with_mode_mut(self.source_register_index as usize, mode) =
-                if !self.byte_transfer && address % 4 == 2 {
-                    data.rotate_left(16)
-                } else {
-                    data
-                };
+            if write_back {
+                *registers.reg_mut(self.base_register_index as usize) = write_back_address;
+            }
+            // When the base is also the destination the loaded value wins.
+            if self.source_register_index == 15 {
+                registers.set_pc(data);
+            } else {
+                *registers.reg_with_mode_mut(self.source_register_index as usize, mode) = data;
+            }
         } else {
             let mut source_register =
                 registers.reg_with_mode(self.source_register_index as usize, mode);
             if self.source_register_index == 15 {
-                source_register -= 4;
+                source_register = source_register.wrapping_add(STORED_PC_OFFSET);
             }
 
             if self.byte_transfer {
@@ -143,13 +153,8 @@ impl InstructionExecutor for SingleDataTransferInstruction {
             } else {
                 bus.write_dword(address, source_register)?;
             }
-        }
-
-        if self.write_back || !self.pre_index {
-            *registers.reg_mut(self.base_register_index as usize) = if !self.pre_index {
-                self.offset_address(address, registers)
-            } else {
-                address
+            if write_back {
+                *registers.reg_mut(self.base_register_index as usize) = write_back_address;
             }
         }
 
@@ -167,7 +172,7 @@ impl InstructionExecutor for SingleDataTransferInstruction {
 
     fn description(&self, registers: &RegisterBank, bus: &mut Bus) -> String {
         let address_hint = if self.load {
-            let address = self.calculate_address(registers);
+            let (address, _) = self.addresses(registers);
             let data = if self.byte_transfer {
                 match bus.read_byte(address) {
                     Ok(data) => Ok(data as u32),
@@ -181,14 +186,7 @@ impl InstructionExecutor for SingleDataTransferInstruction {
             };
 
             let data = match data {
-                Ok(d) => format!(
-                    "${:X}",
-                    if !self.byte_transfer && address % 4 == 2 {
-                        d.rotate_left(16)
-                    } else {
-                        d
-                    }
-                ),
+                Ok(d) => format!("${:X}", d),
                 Err(_) => "???".to_string(),
             };
             format!("(={})", data)
@@ -214,6 +212,7 @@ pub struct BlockDataTransferInstruction {
 }
 
 impl BlockDataTransferInstruction {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         base_register_index: u32,
         registers: u16,
@@ -260,68 +259,83 @@ impl BlockDataTransferInstruction {
 
 impl InstructionExecutor for BlockDataTransferInstruction {
     fn execute(&self, registers: &mut RegisterBank, bus: &mut Bus) -> Result<usize, CoreError> {
+        // Registers are always transferred lowest first to the lowest address, so decrementing
+        // transfers start from the bottom of the block and count up like incrementing ones.
         let base_register = registers.reg(self.base_register_index as usize);
-        let mut base_address = if self.increment {
-            base_register
+        let transfer_size = 4 * self.number_of_registers;
+        let (mut base_address, new_address) = if self.increment {
+            (base_register, base_register.wrapping_add(transfer_size))
         } else {
-            base_register - 4 * (self.number_of_registers - 1)
+            let new_address = base_register.wrapping_sub(transfer_size);
+            (new_address, new_address)
         };
-
-        // TODO: This is a janky hack for the case of pre-index decrementing. THis definitely needs
-        // to have a better implementation.
-        if !self.increment && self.pre_index {
-            base_address = base_address - 4;
+        // The word at the base address is skipped when incrementing before, and it is the top of
+        // the block when decrementing after.
+        if self.pre_index == self.increment {
+            base_address = base_address.wrapping_add(4);
         }
 
+        let base_index = self.base_register_index;
+        let first_register = self.registers.trailing_zeros();
+
+        // Loading the PC with the S bit set restores the CPSR, but the base still belongs to the
+        // mode the instruction started in.
+        let original_mode = registers.cpsr.mode;
         let register_bank =
             if (((self.registers & (1 << 15)) == 0) || !self.load) && self.psr_and_force_user {
                 CpuMode::User
             } else {
-                registers.cpsr.mode
+                original_mode
             };
 
-        let new_address = if self.increment {
-            base_address + 4 * self.number_of_registers as u32
-        } else {
-            base_address
-        };
-
-        if !self.increment && self.pre_index {
-            base_address = base_address.wrapping_sub(4);
-        }
-
         for i in 0..16 {
             if (1 << i) & self.registers > 0 {
-                if self.pre_index {
-                    base_address = base_address.wrapping_add(4);
-                }
-
                 if self.load {
-                    *registers.reg_with_mode_mut(i as usize, register_bank) =
-                        bus.read_dword(base_address).unwrap();
-
-                    if i == 15 && self.psr_and_force_user {
-                        registers.cpsr = registers.spsr();
+                    // Block transfers ignore the low bits of the address instead of rotating.
+                    let data = bus.read_dword(base_address & !0b11)?;
+                    if i == 15 {
+                        if self.psr_and_force_user {
+                            registers.cpsr = registers.spsr();
+                        }
+                        registers.set_pc(data);
+                    } else {
+                        *registers.reg_with_mode_mut(i as usize, register_bank) = data;
                     }
                 } else {
-                    bus.write_dword(
-                        base_address,
-                        registers.reg_with_mode(i as usize, register_bank),
-                    )?;
-                }
-
-                if !self.pre_index {
-                    base_address += 4;
+                    // The base is written back after the first store, so storing it later in
+                    // the list stores the new value.
+                    let data = if self.write_back && i == base_index && i != first_register {
+                        new_address
+                    } else if i == 15 {
+                        registers.pc().wrapping_add(STORED_PC_OFFSET)
+                    } else {
+                        registers.reg_with_mode(i as usize, register_bank)
+                    };
+                    bus.write_dword(base_address, data)?;
                 }
 
-                // Write back's behavior is undefined when using the user mode banks.
-                if self.write_back {
-                    *registers.reg_mut(self.base_register_index as usize) = new_address;
-                }
+                base_address = base_address.wrapping_add(4);
             }
         }
 
-        Ok(1)
+        // Write back's behavior is undefined when using the user mode banks. A load that includes
+        // the base keeps the loaded value instead.
+        let base_loaded = self.load && self.registers & (1 << base_index) > 0;
+        if self.write_back && !base_loaded {
+            *registers.reg_with_mode_mut(base_index as usize, original_mode) = new_address;
+        }
+
+        // STM takes (n-1)S + 2N cycles and LDM takes nS + 1N + 1I. Loading the PC costs an
+        // additional 1S + 1N to refill the pipeline.
+        let cycles = if !self.load {
+            self.number_of_registers as usize + 1
+        } else if self.registers & (1 << 15) > 0 {
+            self.number_of_registers as usize + 4
+        } else {
+            self.number_of_registers as usize + 2
+        };
+
+        Ok(cycles)
     }
 
     fn mnemonic(&self) -> String {
@@ -336,7 +350,7 @@ impl InstructionExecutor for BlockDataTransferInstruction {
     fn description(&self, _registers: &RegisterBank, _bus: &mut Bus) -> String {
         let mut desc = format!("r{}", self.base_register_index);
         if self.write_back {
-            desc.push_str("!");
+            desc.push('!');
         }
 
         desc.push_str(", {");
@@ -351,10 +365,10 @@ impl InstructionExecutor for BlockDataTransferInstruction {
             }
         }
 
-        desc.push_str("}");
+        desc.push('}');
 
         if self.psr_and_force_user {
-            desc.push_str("^");
+            desc.push('^');
         }
 
         desc
@@ -404,26 +418,25 @@ impl InstructionExecutor for PsrTransferMrsInstruction {
 pub struct PsrTransferMsrInstruction {
     operand: Operand,
     use_spsr: bool,
-    write_flags: bool,
-    write_control: bool,
+    /// The bytes of the PSR to write, one for each of the control, extension, status and flags
+    /// field mask bits.
+    field_mask: u32,
 }
 
 impl PsrTransferMsrInstruction {
-    pub fn decode(registers: &mut RegisterBank, opcode: u32) -> Self {
+    pub fn decode(opcode: u32) -> Self {
         let operand = if opcode & (1 << 25) > 0 {
-            Operand::Immediate((opcode & 0xFFF, false))
+            Operand::Immediate((rotated_immediate(opcode), false))
         } else {
-            match Shift::from_opcode(opcode) {
-                Shift::Immediate(shift) => Operand::Immediate(shift.shift(registers)),
-                Shift::Register(shift) => Operand::RegisterShifted(Shift::Register(shift)),
-            }
+            Operand::RegisterShifted(Shift::from_opcode(opcode))
         };
 
         Self {
             operand,
             use_spsr: opcode & (1 << 22) > 0,
-            write_flags: opcode & (1 << 19) > 0,
-            write_control: opcode & (1 << 16) > 0,
+            field_mask: (0..4)
+                .filter(|field| opcode & (1 << (16 + field)) > 0)
+                .fold(0, |mask, field| mask | (0xFF << (field * 8))),
         }
     }
 }
@@ -432,26 +445,20 @@ impl InstructionExecutor for PsrTransferMsrInstruction {
     fn execute(&self, registers: &mut RegisterBank, _bus: &mut Bus) -> Result<usize, CoreError> {
         let operand = self.operand.value(registers);
 
+        let mut field_mask = self.field_mask;
+        // User mode may only change the condition flags.
+        if matches!(registers.cpsr.mode, CpuMode::User) {
+            field_mask &= 0xFF000000;
+        }
+
         let psr = if self.use_spsr {
             registers.spsr_mut()
         } else {
             &mut registers.cpsr
         };
-
-        let psr_operand = ProgramStatusRegister::from_u32(operand.0);
-        if self.write_flags {
-            psr.zero = psr_operand.zero;
-            psr.signed = psr_operand.signed;
-            psr.carry = psr_operand.carry;
-            psr.overflow = psr_operand.overflow;
-        }
-
-        if self.write_control {
-            psr.irq_disable = psr_operand.irq_disable;
-            psr.fiq_disable = psr_operand.fiq_disable;
-            psr.instruction_mode = psr_operand.instruction_mode;
-            psr.mode = psr_operand.mode;
-        }
+        *psr = ProgramStatusRegister::from_u32(
+            (psr.to_u32() & !field_mask) | (operand.0 & field_mask),
+        );
 
         Ok(1)
     }
@@ -461,11 +468,15 @@ impl InstructionExecutor for PsrTransferMsrInstruction {
     }
 
     fn description(&self, _registers: &RegisterBank, _bus: &mut Bus) -> String {
+        let fields: String = [(3, 'f'), (2, 's'), (1, 'x'), (0, 'c')]
+            .iter()
+            .filter(|(field, _)| self.field_mask & (0xFF << (field * 8)) > 0)
+            .map(|(_, name)| name)
+            .collect();
         format!(
-            "{}_{}{}, {}",
+            "{}_{}, {}",
             if self.use_spsr { "spsr" } else { "cpsr" },
-            if self.write_flags { "f" } else { "" },
-            if self.write_control { "c" } else { "" },
+            fields,
             self.operand
         )
     }
@@ -541,6 +552,7 @@ pub struct HalfwordDataTransferRegInstruction {
 }
 
 impl HalfwordDataTransferRegInstruction {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         pre_index: bool,
         up: bool,
@@ -574,7 +586,7 @@ impl HalfwordDataTransferRegInstruction {
             signed: opcode & (1 << 6) > 0,
             halfword: opcode & (1 << 5) > 0,
             base_register: (opcode >> 16) & 0xF,
-            offset: if (opcode >> 22) > 0 {
+            offset: if opcode & (1 << 22) > 0 {
                 HalfwordDataOffset::Offset(((opcode & 0xF) | ((opcode >> 4) & 0xF0)) as u8)
             } else {
                 HalfwordDataOffset::Register(opcode & 0xF)
@@ -586,49 +598,52 @@ impl HalfwordDataTransferRegInstruction {
 
 impl InstructionExecutor for HalfwordDataTransferRegInstruction {
     fn execute(&self, registers: &mut RegisterBank, bus: &mut Bus) -> Result<usize, CoreError> {
-        let mut address = registers.reg(self.base_register as usize);
+        let base_address = registers.reg(self.base_register as usize);
         let offset = match self.offset {
             HalfwordDataOffset::Register(reg) => registers.reg(reg as usize),
             HalfwordDataOffset::Offset(offset) => offset as u32,
         };
+        let offset_address = if self.up {
+            base_address.wrapping_add(offset)
+        } else {
+            base_address.wrapping_sub(offset)
+        };
+        let address = if self.pre_index {
+            offset_address
+        } else {
+            base_address
+        };
 
-        if self.pre_index {
-            if self.up {
-                address = address.wrapping_add(offset)
-            } else {
-                address = address.wrapping_sub(offset)
-            }
-        }
-
-        if self.load {
-            *registers.reg_mut(self.destination_register as usize) = if self.halfword {
-                if self.signed {
-                    bus.read_word(address)? as i16 as i32 as u32
-                } else {
-                    bus.read_word(address)? as u32
-                }
-            } else {
-                bus.read_byte(address)? as i8 as i32 as u32
-            }
+        let data = if self.load {
+            Some(match (self.halfword, self.signed) {
+                (true, false) => bus.read_word(address)? as u32,
+                // A misaligned signed halfword load only loads the addressed byte.
+                (true, true) if address & 1 != 0 => bus.read_byte(address)? as i8 as u32,
+                (true, true) => bus.read_word(address)? as i16 as u32,
+                (false, _) => bus.read_byte(address)? as i8 as u32,
+            })
         } else {
             bus.write_word(
                 address,
                 registers.reg(self.destination_register as usize) as u16,
-            )?
+            )?;
+            None
+        };
+
+        // Post-indexed transfers always write back.
+        if self.write_back || !self.pre_index {
+            *registers.reg_mut(self.base_register as usize) = offset_address;
         }
 
-        if self.pre_index {
-            if self.up {
-                address = address.wrapping_add(offset)
+        // A loaded value wins over the write back when the base is also the destination.
+        if let Some(data) = data {
+            if self.destination_register == 15 {
+                registers.set_pc(data);
             } else {
-                address = address.wrapping_sub(offset)
+                *registers.reg_mut(self.destination_register as usize) = data;
             }
         }
 
-        if self.write_back {
-            *registers.reg_mut(self.base_register as usize) = address;
-        }
-
         Ok(1)
     }
 
@@ -644,21 +659,23 @@ impl InstructionExecutor for HalfwordDataTransferRegInstruction {
     }
 
     fn description(&self, _registers: &RegisterBank, _bus: &mut Bus) -> String {
-        let mut desc = format!("[r{}", self.base_register).to_string();
-        desc += &match self.offset {
-            HalfwordDataOffset::Register(reg) => format!(", {reg}]"),
-            HalfwordDataOffset::Offset(offset) => {
-                if offset > 0 {
-                    format!(", #{:X}]", offset)
-                } else {
-                    "]".to_string()
-                }
-            }
+        let sign = if self.up { "" } else { "-" };
+        let offset = match self.offset {
+            HalfwordDataOffset::Register(reg) => Some(format!("{sign}r{reg}")),
+            HalfwordDataOffset::Offset(0) => None,
+            HalfwordDataOffset::Offset(offset) => Some(format!("#{sign}{:X}", offset)),
         };
-        if self.write_back {
-            desc += "!";
-        }
 
-        desc
+        let address = match (offset, self.pre_index) {
+            (None, _) => format!("[r{}]", self.base_register),
+            (Some(offset), true) => format!(
+                "[r{}, {offset}]{}",
+                self.base_register,
+                if self.write_back { "!" } else { "" }
+            ),
+            (Some(offset), false) => format!("[r{}], {offset}", self.base_register),
+        };
+
+        format!("r{}, {address}", self.destination_register)
     }
 }
@@ -0,0 +1,138 @@
+use std::fmt::Display;
+
+use crate::core::{Bus, CoreError};
+
+use crate::core::interpreter::{
+    instruction::InstructionExecutor, register::RegisterBank, status::CpuMode,
+};
+
+const UNDEFINED_VECTOR: u32 = 0x04;
+
+pub const CLZ_MASK: u32 = 0b0000_1111_1111_1111_0000_1111_1111_0000;
+pub const CLZ_FORMAT: u32 = 0b0000_0001_0110_1111_0000_1111_0001_0000;
+
+pub const BLX_REGISTER_MASK: u32 = 0b0000_1111_1111_1111_1111_1111_1111_0000;
+pub const BLX_REGISTER_FORMAT: u32 = 0b0000_0001_0010_1111_1111_1111_0011_0000;
+
+pub const SATURATING_ARITHMETIC_MASK: u32 = 0b0000_1111_1001_0000_0000_1111_1111_0000;
+pub const SATURATING_ARITHMETIC_FORMAT: u32 = 0b0000_0001_0000_0000_0000_0000_0101_0000;
+
+pub const SIGNED_HALFWORD_MULTIPLY_MASK: u32 = 0b0000_1111_1001_0000_0000_0000_1001_0000;
+pub const SIGNED_HALFWORD_MULTIPLY_FORMAT: u32 = 0b0000_0001_0000_0000_0000_0000_1000_0000;
+
+pub const BREAKPOINT_MASK: u32 = 0b0000_1111_1111_0000_0000_0000_1111_0000;
+pub const BREAKPOINT_FORMAT: u32 = 0b0000_0001_0010_0000_0000_0000_0111_0000;
+
+pub const DOUBLEWORD_TRANSFER_MASK: u32 = 0b0000_1110_0001_0000_0000_0000_1101_0000;
+pub const DOUBLEWORD_TRANSFER_FORMAT: u32 = 0b0000_0000_0000_0000_0000_0000_1101_0000;
+
+/// Instructions added in ARMv5 that the ARM7TDMI does not have. Encodings using the NV condition
+/// (BLX immediate, PLD) are left out since ARMv4T never executes those anyway.
+#[derive(Debug, Eq, PartialEq)]
+pub enum Armv5Instruction {
+    CountLeadingZeros,
+    BranchLinkExchange,
+    SaturatingArithmetic,
+    SignedHalfwordMultiply,
+    Breakpoint,
+    DoublewordTransfer,
+}
+
+impl Armv5Instruction {
+    pub fn decode(opcode: u32) -> Option<Self> {
+        if (opcode & CLZ_MASK) == CLZ_FORMAT {
+            Some(Self::CountLeadingZeros)
+        } else if (opcode & BLX_REGISTER_MASK) == BLX_REGISTER_FORMAT {
+            Some(Self::BranchLinkExchange)
+        } else if (opcode & SATURATING_ARITHMETIC_MASK) == SATURATING_ARITHMETIC_FORMAT {
+            Some(Self::SaturatingArithmetic)
+        } else if (opcode & SIGNED_HALFWORD_MULTIPLY_MASK) == SIGNED_HALFWORD_MULTIPLY_FORMAT {
+            Some(Self::SignedHalfwordMultiply)
+        } else if (opcode & BREAKPOINT_MASK) == BREAKPOINT_FORMAT {
+            Some(Self::Breakpoint)
+        } else if (opcode & DOUBLEWORD_TRANSFER_MASK) == DOUBLEWORD_TRANSFER_FORMAT {
+            Some(Self::DoublewordTransfer)
+        } else {
+            None
+        }
+    }
+}
+
+impl Display for Armv5Instruction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Armv5Instruction::CountLeadingZeros => write!(f, "clz"),
+            Armv5Instruction::BranchLinkExchange => write!(f, "blx"),
+            Armv5Instruction::SaturatingArithmetic => write!(f, "qadd/qsub"),
+            Armv5Instruction::SignedHalfwordMultiply => write!(f, "smla/smul"),
+            Armv5Instruction::Breakpoint => write!(f, "bkpt"),
+            Armv5Instruction::DoublewordTransfer => write!(f, "ldrd/strd"),
+        }
+    }
+}
+
+pub struct UndefinedInstruction {
+    past_address: u32,
+    armv5_instruction: Option<Armv5Instruction>,
+}
+
+impl UndefinedInstruction {
+    pub fn decode(registers: &mut RegisterBank, opcode: u32) -> Self {
+        Self {
+            past_address: registers.pc(),
+            armv5_instruction: Armv5Instruction::decode(opcode),
+        }
+    }
+}
+
+impl InstructionExecutor for UndefinedInstruction {
+    fn execute(&self, registers: &mut RegisterBank, _bus: &mut Bus) -> Result<usize, CoreError> {
+        if let Some(instruction) = &self.armv5_instruction {
+            println!("Warning: ARMv5 instruction not present on ARMv4T ({instruction}).");
+        }
+        registers.enter_exception(CpuMode::Undefined, UNDEFINED_VECTOR, self.past_address);
+
+        Ok(3)
+    }
+
+    fn mnemonic(&self) -> String {
+        "und".into()
+    }
+
+    fn description(&self, _registers: &RegisterBank, _bus: &mut Bus) -> String {
+        match &self.armv5_instruction {
+            Some(instruction) => format!("{instruction} ; ARMv5 instruction not present on ARMv4T"),
+            None => String::new(),
+        }
+    }
+}
+
+/// Stands in for an opcode the interpreter cannot decode yet when it is told to carry on past
+/// them. Executing it does nothing but log the opcode.
+pub struct SkippedInstruction {
+    opcode: u32,
+}
+
+impl SkippedInstruction {
+    pub fn new(opcode: u32) -> Self {
+        Self { opcode }
+    }
+}
+
+impl InstructionExecutor for SkippedInstruction {
+    fn execute(&self, _registers: &mut RegisterBank, _bus: &mut Bus) -> Result<usize, CoreError> {
+        println!(
+            "Warning: Skipping unimplemented opcode 0x{:08X}.",
+            self.opcode
+        );
+        Ok(1)
+    }
+
+    fn mnemonic(&self) -> String {
+        "???".into()
+    }
+
+    fn description(&self, _registers: &RegisterBank, _bus: &mut Bus) -> String {
+        format!("; unimplemented opcode 0x{:08X} skipped", self.opcode)
+    }
+}
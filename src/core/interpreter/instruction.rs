@@ -8,9 +8,10 @@ use super::thumb::LongBranchWithLinkInstruction;
 
 use super::arm::{
     BlockDataTransferInstruction, BranchAndExchangeInstruction, BranchInstruction,
-    DataProcessingInstruction, HalfwordDataTransferRegInstruction, PsrTransferMrsInstruction,
-    PsrTransferMsrInstruction, SingleDataSwapInstruction, SingleDataTransferInstruction,
-    SoftwareInterruptInstruction,
+    DataProcessingInstruction, HalfwordDataTransferRegInstruction, MultiplyInstruction,
+    MultiplyLongInstruction, PsrTransferMrsInstruction, PsrTransferMsrInstruction,
+    SingleDataSwapInstruction, SingleDataTransferInstruction, SkippedInstruction,
+    SoftwareInterruptInstruction, UndefinedInstruction,
 };
 
 pub trait InstructionExecutor {
@@ -31,6 +32,32 @@ pub enum Instruction {
     PsrTransferMsr(PsrTransferMsrInstruction),
     SingleDataSwap(SingleDataSwapInstruction),
     HalfwordDataTransfer(HalfwordDataTransferRegInstruction),
+    Multiply(MultiplyInstruction),
+    MultiplyLong(MultiplyLongInstruction),
+    Undefined(UndefinedInstruction),
+    Skipped(SkippedInstruction),
+}
+
+impl Instruction {
+    pub fn executor(&self) -> &dyn InstructionExecutor {
+        match self {
+            Instruction::Branch(b) => b,
+            Instruction::BranchAndExchange(b) => b,
+            Instruction::DataProcessing(d) => d,
+            Instruction::SingleDataTransfer(d) => d,
+            Instruction::SoftwareInterrupt(i) => i,
+            Instruction::BlockDataTransfer(d) => d,
+            Instruction::PsrTransferMrs(d) => d,
+            Instruction::PsrTransferMsr(d) => d,
+            Instruction::SingleDataSwap(d) => d,
+            Instruction::LongBranchWithLink(d) => d,
+            Instruction::HalfwordDataTransfer(d) => d,
+            Instruction::Multiply(m) => m,
+            Instruction::MultiplyLong(m) => m,
+            Instruction::Undefined(u) => u,
+            Instruction::Skipped(s) => s,
+        }
+    }
 }
 
 pub struct Operation {
@@ -41,6 +68,8 @@ pub struct Operation {
 }
 
 pub enum Operand {
+    /// An immediate and whether it was rotated. Rotated immediates carry out their top bit, while
+    /// the others, including every Thumb immediate, leave the carry flag as it is.
     Immediate((u32, bool)),
     Register(u32),
     RegisterShifted(Shift),
@@ -49,8 +78,9 @@ pub enum Operand {
 impl Operand {
     pub fn value(&self, registers: &RegisterBank) -> (u32, bool) {
         match self {
-            Operand::Immediate(value) => *value,
-            Operand::Register(index) => (registers.reg(*index as usize), false),
+            Operand::Immediate((value, true)) => (*value, value & (1 << 31) > 0),
+            Operand::Immediate((value, false)) => (*value, registers.cpsr.carry),
+            Operand::Register(index) => (registers.reg(*index as usize), registers.cpsr.carry),
             Operand::RegisterShifted(shift) => shift.shift(registers),
         }
     }
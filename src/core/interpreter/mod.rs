@@ -6,34 +6,311 @@ mod shift;
 mod status;
 mod thumb;
 
-use instruction::{Instruction, InstructionExecutor, Operation};
-use register::RegisterBank;
-use status::InstructionMode;
+use instruction::{Instruction, Operation};
+pub use register::RegisterBank;
+pub use status::{CpuMode, InstructionMode, StatusFlag};
 use thumb::{
-    decode_add_offset_stack_pointer, decode_add_subtract, decode_alu_operations, decode_conditional_branch, decode_hi_reg_branch_exchange, decode_load_store_halfword, decode_load_store_immediate_offset, decode_move_shifted_register, decode_push_pop_registers, decode_sp_relative_load_store, decode_unconditional_branch, LongBranchWithLinkInstruction
+    decode_add_offset_stack_pointer, decode_add_subtract, decode_alu_operations,
+    decode_conditional_branch, decode_hi_reg_branch_exchange, decode_load_store_halfword,
+    decode_load_store_immediate_offset, decode_move_shifted_register, decode_push_pop_registers,
+    decode_sp_relative_load_store, decode_unconditional_branch, LongBranchWithLinkInstruction,
+};
+
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashSet,
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
 };
 
 use super::{Bus, CoreError};
 
+const RESET_VECTOR: u32 = 0x0;
+const CARTRIDGE_ENTRY: u32 = 0x8000000;
+const IRQ_VECTOR: u32 = 0x18;
+const FIQ_VECTOR: u32 = 0x1C;
+
+/// A copy of the CPU's registers and pipeline that can be restored later.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct InterpreterState {
+    pub registers: RegisterBank,
+    pub fetched_instruction: Option<(u32, u32)>,
+    pub decoded_instruction: Option<(u32, u32)>,
+    pub instruction_count: u64,
+}
+
+/// What the interpreter does when it reaches an opcode it cannot decode.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum UnimplementedOpcodePolicy {
+    /// Stop emulation with `CoreError::OpcodeNotImplemented`.
+    #[default]
+    Abort,
+    /// Log the opcode and carry on as if it were a NOP.
+    Skip,
+    /// Take the undefined instruction exception, as hardware does for opcodes it does not know.
+    UndefinedException,
+}
+
+/// How each executed instruction is written to the log and trace.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TraceFormat {
+    /// `$address: opcode mnemonic.cond description`.
+    #[default]
+    Disassembly,
+    /// r0 to r15 followed by the CPSR, all in hex, as they are before the instruction executes.
+    /// r15 reads two instructions ahead, as it does to the instruction. This lines up with the
+    /// register logs of other emulators such as mGBA.
+    RegisterState,
+}
+
+/// A breakpoint that stopped execution, along with the address of the instruction it stopped
+/// before.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BreakpointHit {
+    Address(u32),
+    Opcode(u32),
+}
+
 #[derive(Default)]
 pub struct Interpreter {
     registers: RegisterBank,
     fetched_instruction: Option<(u32, u32)>,
     decoded_instruction: Option<Operation>,
     pub logging_enabled: bool,
+    pub trace_format: TraceFormat,
+    /// Whether disassembly trace lines end with the flags as they are after the instruction ran.
+    pub trace_flags: bool,
+    /// Where executed instructions are traced to, as well as stdout when logging is enabled.
+    trace_output: Option<BufWriter<File>>,
+    pub unimplemented_opcode_policy: UnimplementedOpcodePolicy,
+    /// Whether common BIOS calls are emulated instead of running the BIOS's code for them.
+    pub high_level_bios: bool,
+    opcode_breakpoints: Vec<(u32, u32)>,
+    address_breakpoints: HashSet<u32>,
+    breakpoint_hit: Option<BreakpointHit>,
+    resuming_from_breakpoint: bool,
+    instruction_count: u64,
+    irq_line: bool,
 }
 
 impl Interpreter {
+    pub fn registers(&self) -> &RegisterBank {
+        &self.registers
+    }
+
+    /// The number of instructions that have reached the execute stage, including those whose
+    /// condition failed.
+    pub fn instruction_count(&self) -> u64 {
+        self.instruction_count
+    }
+
+    /// Changes a CPSR flag, for poking at the CPU while it is paused.
+    pub fn set_flag(&mut self, flag: StatusFlag, value: bool) {
+        self.registers.cpsr.set_flag(flag, value);
+    }
+
+    /// Puts the CPU in the state it comes out of reset in: Supervisor mode in the ARM state with
+    /// IRQs and FIQs masked, every register cleared and the pipeline empty, about to fetch from
+    /// the reset vector. Breakpoints and the unimplemented opcode policy are kept.
+    pub fn reset(&mut self) {
+        self.registers = RegisterBank::default();
+        self.registers.cpsr.mode = CpuMode::Supervisor;
+        self.registers.cpsr.irq_disable = true;
+        self.registers.cpsr.fiq_disable = true;
+        self.breakpoint_hit = None;
+        self.resuming_from_breakpoint = false;
+        self.instruction_count = 0;
+        self.irq_line = false;
+        self.jump_to(RESET_VECTOR, InstructionMode::Arm);
+    }
+
+    /// Sets up the banked stack pointers and mode the BIOS leaves behind once the boot sequence
+    /// completes, then jumps to the cartridge entry point.
+    pub fn skip_bios(&mut self) {
+        *self.registers.reg_with_mode_mut(13, CpuMode::Supervisor) = 0x3007FE0;
+        *self.registers.reg_with_mode_mut(13, CpuMode::Irq) = 0x3007FA0;
+        *self.registers.reg_with_mode_mut(13, CpuMode::System) = 0x3007F00;
+        self.registers.cpsr.mode = CpuMode::System;
+        self.registers.cpsr.irq_disable = false;
+        self.registers.cpsr.fiq_disable = false;
+        self.jump_to(CARTRIDGE_ENTRY, InstructionMode::Arm);
+    }
+
+    /// Starts execution at `address` in the given state, discarding anything already in the
+    /// pipeline.
+    pub fn jump_to(&mut self, address: u32, instruction_mode: InstructionMode) {
+        self.registers.cpsr.instruction_mode = instruction_mode;
+        *self.registers.reg_mut(15) = address;
+
+        self.fetched_instruction = None;
+        self.decoded_instruction = None;
+        self.registers.pipeline_flush = false;
+    }
+
+    pub fn state(&self) -> InterpreterState {
+        InterpreterState {
+            registers: self.registers.clone(),
+            fetched_instruction: self.fetched_instruction,
+            decoded_instruction: self
+                .decoded_instruction
+                .as_ref()
+                .map(|decoded| (decoded.opcode, decoded.location)),
+            instruction_count: self.instruction_count,
+        }
+    }
+
+    pub fn restore(&mut self, state: &InterpreterState) -> Result<(), CoreError> {
+        self.registers = state.registers.clone();
+        self.decoded_instruction = None;
+        self.instruction_count = state.instruction_count;
+
+        // The decoded instruction is decoded again, with the PC it saw the first time around.
+        if let Some((opcode, location)) = state.decoded_instruction {
+            let pc = self.registers.pc();
+            *self.registers.reg_mut(15) = location;
+            self.registers.increment_pc();
+            self.fetched_instruction = Some((opcode, location));
+            self.decode()?;
+            *self.registers.reg_mut(15) = pc;
+        }
+        self.fetched_instruction = state.fetched_instruction;
+
+        Ok(())
+    }
+
+    /// Drives the IRQ input. While it is held high the CPU takes the IRQ exception at the next
+    /// instruction boundary where IRQs are not masked in the CPSR.
+    pub fn set_irq_line(&mut self, asserted: bool) {
+        self.irq_line = asserted;
+    }
+
+    fn enter_irq(&mut self) {
+        self.enter_interrupt(CpuMode::Irq, IRQ_VECTOR);
+    }
+
+    /// Takes the FIQ exception, switching to the FIQ copies of r8 to r14 and masking both IRQs and
+    /// FIQs. Nothing on the GBA raises an FIQ, so this is only entered on request.
+    pub fn enter_fiq(&mut self) {
+        self.enter_interrupt(CpuMode::Fiq, FIQ_VECTOR);
+    }
+
+    /// The return address is set up so that the handler's `subs pc, lr, #4` resumes at the
+    /// instruction that was about to execute.
+    fn enter_interrupt(&mut self, mode: CpuMode, vector: u32) {
+        let next_instruction = self.next_instruction();
+        self.registers
+            .enter_exception(mode, vector, next_instruction + 4);
+        self.fetched_instruction = None;
+        self.decoded_instruction = None;
+        self.registers.pipeline_flush = false;
+    }
+
+    /// The address of the instruction that executes next, wherever it is in the pipeline.
+    fn next_instruction(&self) -> u32 {
+        if let Some(decoded_instruction) = &self.decoded_instruction {
+            decoded_instruction.location
+        } else if let Some((_, location)) = self.fetched_instruction {
+            location
+        } else {
+            self.registers.pc()
+        }
+    }
+
+    /// Switches the CPU to `mode`, bringing that mode's banked registers into view.
+    pub fn set_cpu_mode(&mut self, mode: CpuMode) {
+        self.registers.cpsr.mode = mode;
+    }
+
+    /// Switches between ARM and Thumb state. The pipeline is refilled so the next instruction is
+    /// fetched again in the new state.
+    pub fn set_instruction_mode(&mut self, instruction_mode: InstructionMode) {
+        let next_instruction = self.next_instruction();
+        self.jump_to(next_instruction, instruction_mode);
+    }
+
+    /// Stops execution before any instruction whose opcode matches `pattern` in the bits set in
+    /// `mask`.
+    pub fn add_opcode_breakpoint(&mut self, mask: u32, pattern: u32) {
+        self.opcode_breakpoints.push((mask, pattern & mask));
+    }
+
+    /// Stops execution before the instruction at `address`.
+    pub fn add_breakpoint(&mut self, address: u32) {
+        self.address_breakpoints.insert(address);
+    }
+
+    /// Returns whether there was a breakpoint at `address` to remove.
+    pub fn remove_breakpoint(&mut self, address: u32) -> bool {
+        self.address_breakpoints.remove(&address)
+    }
+
+    /// Returns the breakpoint hit during the last tick, if any. The instruction it stopped before
+    /// runs on the next tick.
+    pub fn take_breakpoint(&mut self) -> Option<BreakpointHit> {
+        self.breakpoint_hit.take()
+    }
+
+    fn check_breakpoints(&mut self) -> bool {
+        if self.resuming_from_breakpoint {
+            self.resuming_from_breakpoint = false;
+            return false;
+        }
+
+        let Some(decoded_instruction) = &self.decoded_instruction else {
+            return false;
+        };
+        let location = decoded_instruction.location;
+        let opcode = decoded_instruction.opcode;
+        self.breakpoint_hit = if self.address_breakpoints.contains(&location) {
+            Some(BreakpointHit::Address(location))
+        } else if self
+            .opcode_breakpoints
+            .iter()
+            .any(|(mask, pattern)| opcode & mask == *pattern)
+        {
+            Some(BreakpointHit::Opcode(location))
+        } else {
+            return false;
+        };
+
+        self.resuming_from_breakpoint = true;
+        true
+    }
+
     pub fn tick(&mut self, bus: &mut Bus) -> Result<usize, CoreError> {
+        if self.irq_line && !self.registers.cpsr.irq_disable {
+            self.enter_irq();
+        }
+
+        if self.check_breakpoints() {
+            return Ok(0);
+        }
+
+        // The opcode two instructions ahead is fetched while this one executes, so it is what
+        // loads see on the open bus. It is thrown away if the instruction jumps. A failed fetch is
+        // only an error if the instruction carries on to it.
+        let prefetch_location = self.registers.pc();
+        let instruction_mode = self.registers.cpsr.instruction_mode;
+        let prefetch = bus.fetch(prefetch_location, instruction_mode).ok();
         let cycles = self.execute(bus)?;
         self.decode()?;
-        self.fetch(bus)?;
+        let jumped = self.registers.pc() != prefetch_location
+            || self.registers.cpsr.instruction_mode as u32 != instruction_mode as u32;
+        match prefetch {
+            Some(opcode) if !jumped => {
+                self.fetched_instruction = Some((opcode, prefetch_location));
+                self.registers.increment_pc();
+            }
+            _ => self.fetch(bus)?,
+        }
         Ok(cycles)
     }
 
     fn fetch(&mut self, bus: &mut Bus) -> Result<(), CoreError> {
         let fetch_location = self.registers.pc();
-        self.fetched_instruction = Some((bus.read_dword(fetch_location)?, fetch_location));
+        let opcode = bus.fetch(fetch_location, self.registers.cpsr.instruction_mode)?;
+        self.fetched_instruction = Some((opcode, fetch_location));
         self.registers.increment_pc();
         Ok(())
     }
@@ -51,7 +328,12 @@ impl Interpreter {
                 location: pc,
                 condition: fetched_instruction >> 28,
                 opcode: fetched_instruction,
-                instruction: if (fetched_instruction & arm::BRANCH_AND_EXCHANGE_MASK)
+                instruction: if arm::Armv5Instruction::decode(fetched_instruction).is_some() {
+                    Instruction::Undefined(arm::UndefinedInstruction::decode(
+                        &mut self.registers,
+                        fetched_instruction,
+                    ))
+                } else if (fetched_instruction & arm::BRANCH_AND_EXCHANGE_MASK)
                     == arm::BRANCH_AND_EXCHANGE_FORMAT
                 {
                     Instruction::BranchAndExchange(arm::BranchAndExchangeInstruction::decode(
@@ -73,15 +355,17 @@ impl Interpreter {
                 } else if (fetched_instruction & arm::SOFTWARE_INTERRUPT_MASK)
                     == arm::SOFTWARE_INTERRUPT_FORMAT
                 {
-                    Instruction::SoftwareInterrupt(arm::SoftwareInterruptInstruction::decode(
-                        &mut self.registers,
-                        fetched_instruction,
-                    ))
+                    Instruction::SoftwareInterrupt(
+                        arm::SoftwareInterruptInstruction::decode(
+                            &mut self.registers,
+                            fetched_instruction,
+                        )
+                        .with_high_level_bios(self.high_level_bios),
+                    )
                 } else if (fetched_instruction & arm::SINGLE_TRANSFER_MASK)
                     == arm::SINGLE_TRANSFER_FORMAT
                 {
                     Instruction::SingleDataTransfer(arm::SingleDataTransferInstruction::decode(
-                        &mut self.registers,
                         fetched_instruction,
                     ))
                 } else if (fetched_instruction & arm::SINGLE_DATA_SWAP_MASK)
@@ -91,11 +375,15 @@ impl Interpreter {
                         fetched_instruction,
                     ))
                 } else if (fetched_instruction & arm::MULTIPLY_MASK) == arm::MULTIPLY_FORMAT {
-                    unimplemented!()
+                    Instruction::Multiply(arm::MultiplyInstruction::decode(fetched_instruction))
                 } else if (fetched_instruction & arm::MULTIPLY_MASK) == arm::MULTIPLY_LONG_FORMAT {
-                    unimplemented!()
+                    Instruction::MultiplyLong(arm::MultiplyLongInstruction::decode(
+                        fetched_instruction,
+                    ))
                 } else if (fetched_instruction & arm::HALFWORD_DATA_TRANSFER_REG_MASK)
                     == arm::HALFWORD_DATA_TRANSFER_REG_FORMAT
+                    || (fetched_instruction & arm::HALFWORD_DATA_TRANSFER_IMM_MASK)
+                        == arm::HALFWORD_DATA_TRANSFER_IMM_FORMAT
                 {
                     Instruction::HalfwordDataTransfer(
                         arm::HalfwordDataTransferRegInstruction::decode(fetched_instruction),
@@ -110,41 +398,60 @@ impl Interpreter {
                     == arm::PSR_TRANSFER_MSR_FORMAT
                 {
                     Instruction::PsrTransferMsr(arm::PsrTransferMsrInstruction::decode(
-                        &mut self.registers,
                         fetched_instruction,
                     ))
                 } else if (fetched_instruction & arm::DATA_PROCESSING_MASK)
                     == arm::DATA_PROCESSING_FORMAT
                 {
                     Instruction::DataProcessing(arm::DataProcessingInstruction::decode(
-                        &mut self.registers,
                         fetched_instruction,
                     ))
                 } else {
-                    return Err(CoreError::OpcodeNotImplemented(fetched_instruction));
+                    self.unimplemented_opcode(fetched_instruction)?
                 },
             });
         }
         Ok(())
     }
 
+    /// Decides what an opcode the decoder does not know about turns into.
+    fn unimplemented_opcode(&mut self, opcode: u32) -> Result<Instruction, CoreError> {
+        match self.unimplemented_opcode_policy {
+            UnimplementedOpcodePolicy::Abort => Err(CoreError::OpcodeNotImplemented(opcode)),
+            UnimplementedOpcodePolicy::Skip => {
+                Ok(Instruction::Skipped(arm::SkippedInstruction::new(opcode)))
+            }
+            UnimplementedOpcodePolicy::UndefinedException => Ok(Instruction::Undefined(
+                arm::UndefinedInstruction::decode(&mut self.registers, opcode),
+            )),
+        }
+    }
+
     fn decode_thumb(&mut self) -> Result<(), CoreError> {
         if let Some((fetched_instruction, pc)) = self.fetched_instruction {
             let fetched_instruction = fetched_instruction & 0xFFFF;
+            let software_interrupt = (fetched_instruction & thumb::SOFTWARE_INTERRUPT_MASK)
+                == thumb::SOFTWARE_INTERRUPT_FORMAT;
             self.decoded_instruction = Some(Operation {
                 location: pc,
+                // A condition of 0xF in the conditional branch format is SWI, which always runs.
                 condition: if (fetched_instruction & thumb::CONDITIONAL_BRANCH_MASK)
                     == thumb::CONDITIONAL_BRANCH_FORMAT
+                    && !software_interrupt
                 {
                     (fetched_instruction >> 8) & 0b1111
                 } else {
                     0xE
                 },
                 opcode: fetched_instruction,
-                instruction: if (fetched_instruction & thumb::SOFTWARE_INTERRUPT_MASK)
-                    == thumb::SOFTWARE_INTERRUPT_FORMAT
-                {
-                    unimplemented!()
+                instruction: if software_interrupt {
+                    Instruction::SoftwareInterrupt(
+                        arm::SoftwareInterruptInstruction::decode(
+                            &mut self.registers,
+                            fetched_instruction,
+                        )
+                        .with_high_level_bios(self.high_level_bios),
+                    )
                 } else if (fetched_instruction & thumb::UNCONDITIONAL_BRANCH_MASK)
                     == thumb::UNCONDITIONAL_BRANCH_FORMAT
                 {
@@ -156,7 +463,7 @@ impl Interpreter {
                 } else if (fetched_instruction & thumb::MULTIPLE_LOAD_STORE_MASK)
                     == thumb::MULTIPLE_LOAD_STORE_FORMAT
                 {
-                    unimplemented!()
+                    thumb::decode_multiple_load_store(fetched_instruction)
                 } else if (fetched_instruction & thumb::LONG_BRANCH_WITH_LINK_MASK)
                     == thumb::LONG_BRANCH_WITH_LINK_FORMAT
                 {
@@ -182,7 +489,7 @@ impl Interpreter {
                 } else if (fetched_instruction & thumb::LOAD_ADDRESS_MASK)
                     == thumb::LOAD_ADDRESS_FORMAT
                 {
-                    unimplemented!()
+                    thumb::decode_load_address(fetched_instruction)
                 } else if (fetched_instruction & thumb::LOAD_STORE_WITH_IMMEDIATE_OFFSET_MASK)
                     == thumb::LOAD_STORE_WITH_IMMEDIATE_OFFSET_FORMAT
                 {
@@ -220,7 +527,7 @@ impl Interpreter {
                 {
                     decode_move_shifted_register(fetched_instruction)
                 } else {
-                    return Err(CoreError::OpcodeNotImplemented(fetched_instruction));
+                    self.unimplemented_opcode(fetched_instruction)?
                 },
             })
         }
@@ -230,57 +537,193 @@ impl Interpreter {
 
     fn execute(&mut self, bus: &mut Bus) -> Result<usize, CoreError> {
         if let Some(decoded_instruction) = &self.decoded_instruction {
-            let ins: &dyn InstructionExecutor = match &decoded_instruction.instruction {
-                Instruction::Branch(b) => b,
-                Instruction::BranchAndExchange(b) => b,
-                Instruction::DataProcessing(d) => d,
-                Instruction::SingleDataTransfer(d) => d,
-                Instruction::SoftwareInterrupt(i) => i,
-                Instruction::BlockDataTransfer(d) => d,
-                Instruction::PsrTransferMrs(d) => d,
-                Instruction::PsrTransferMsr(d) => d,
-                Instruction::SingleDataSwap(d) => d,
-                Instruction::LongBranchWithLink(d) => d,
-                Instruction::HalfwordDataTransfer(d) => d,
-            };
+            let ins = decoded_instruction.instruction.executor();
+            self.instruction_count += 1;
 
-            self.log_instruction(
-                decoded_instruction.location,
-                decoded_instruction.opcode,
-                decoded_instruction.condition,
-                &ins.mnemonic(),
-                &ins.description(&self.registers, bus),
-            );
+            let line = if self.logging_enabled || self.trace_output.is_some() {
+                Some(match self.trace_format {
+                    TraceFormat::Disassembly => format!(
+                        "${:08X}: {:08X} {}",
+                        decoded_instruction.location,
+                        decoded_instruction.opcode,
+                        Self::format_instruction(
+                            decoded_instruction.condition,
+                            &ins.mnemonic(),
+                            &ins.description(&self.registers, bus),
+                        )
+                    ),
+                    TraceFormat::RegisterState => Self::format_register_state(&self.registers),
+                })
+            } else {
+                None
+            };
 
-            if self.check_condition(decoded_instruction.condition) {
+            let cycles = if self.check_condition(decoded_instruction.condition) {
                 let cycles = ins.execute(&mut self.registers, bus);
                 if self.registers.pipeline_flush {
                     self.decoded_instruction = None;
                     self.fetched_instruction = None;
                     self.registers.pipeline_flush = false;
                 }
-                return cycles;
+                cycles
+            } else {
+                Ok(1)
+            };
+
+            // The line is only written now so that it can show the flags the instruction set.
+            if let Some(mut line) = line {
+                if self.trace_flags && self.trace_format == TraceFormat::Disassembly {
+                    line.push_str(&format!(" [{}]", Self::format_flags(&self.registers)));
+                }
+                Self::log_instruction(&mut self.trace_output, self.logging_enabled, &line);
             }
+            return cycles;
         }
 
         Ok(1)
     }
 
-    pub fn log_instruction(
+    /// N, Z, C and V for each flag that is set, or `-` in its place when it is clear.
+    fn format_flags(registers: &RegisterBank) -> String {
+        let cpsr = &registers.cpsr;
+        [
+            (cpsr.signed, 'N'),
+            (cpsr.zero, 'Z'),
+            (cpsr.carry, 'C'),
+            (cpsr.overflow, 'V'),
+        ]
+        .iter()
+        .map(|&(set, flag)| if set { flag } else { '-' })
+        .collect()
+    }
+
+    /// Takes the trace output as its own argument so it can be written while the instruction
+    /// being logged is still borrowed from the pipeline.
+    fn log_instruction(
+        trace_output: &mut Option<BufWriter<File>>,
+        logging_enabled: bool,
+        line: &str,
+    ) {
+        if logging_enabled {
+            println!("{line}");
+        }
+        if let Some(output) = trace_output {
+            if let Err(e) = writeln!(output, "{line}") {
+                println!("Warning: Stopping instruction trace after failing to write it: {e}");
+                *trace_output = None;
+            }
+        }
+    }
+
+    /// Starts writing a line for every executed instruction to the file at `path`, in the same
+    /// format as the log. Passing `None` stops the trace and flushes what is buffered.
+    pub fn set_trace_output(&mut self, path: Option<&Path>) -> io::Result<()> {
+        if let Some(mut output) = self.trace_output.take() {
+            output.flush()?;
+        }
+        if let Some(path) = path {
+            self.trace_output = Some(BufWriter::new(File::create(path)?));
+        }
+        Ok(())
+    }
+
+    fn format_register_state(registers: &RegisterBank) -> String {
+        let mut line = String::with_capacity(16 * 9 + 14);
+        for i in 0..16 {
+            line.push_str(&format!("{:08X} ", registers.reg(i)));
+        }
+        line.push_str(&format!("cpsr: {:08X}", registers.cpsr.to_u32()));
+        line
+    }
+
+    fn format_instruction(condition: u32, mnemonic: &str, description: &str) -> String {
+        let condition = Self::get_condition_label(condition);
+        format!(
+            "{mnemonic}{}{condition} {description}",
+            if !condition.is_empty() { "." } else { "" },
+        )
+    }
+
+    /// Decodes `count` instructions starting at `address` without running them, returning each
+    /// one's address and text. The CPU's registers and pipeline are not touched, though operand
+    /// hints such as the value a load would fetch are worked out from the current registers.
+    pub fn disassemble_at(
         &self,
+        bus: &mut Bus,
         address: u32,
-        opcode: u32,
-        condition: u32,
-        mneumonic: &str,
-        description: &str,
-    ) {
-        if self.logging_enabled {
-            let condition = Self::get_condition_label(condition);
-            println!(
-                "${address:08X}: {opcode:08X} {mneumonic}{}{condition} {description}",
-                if condition.len() > 0 { "." } else { "" },
-            );
+        instruction_mode: InstructionMode,
+        count: usize,
+    ) -> Result<Vec<(u32, String)>, CoreError> {
+        let mut scratch = Self::disassembler(self.registers.clone(), instruction_mode);
+        let size = match instruction_mode {
+            InstructionMode::Arm => 4,
+            InstructionMode::Thumb => 2,
+        };
+
+        let mut lines = Vec::with_capacity(count);
+        let mut location = address;
+        for _ in 0..count {
+            let opcode = bus.peek_opcode(location, instruction_mode)?;
+            lines.push((
+                location,
+                scratch.disassemble_fetched(bus, opcode, location)?,
+            ));
+            location = location.wrapping_add(size);
         }
+
+        Ok(lines)
+    }
+
+    /// Decodes a single opcode as if it were at `address`, with nothing in memory and every
+    /// register 0. Useful for checking the decoder against a known listing.
+    pub fn disassemble_opcode(
+        opcode: u32,
+        address: u32,
+        instruction_mode: InstructionMode,
+    ) -> Result<String, CoreError> {
+        Self::disassembler(RegisterBank::default(), instruction_mode).disassemble_fetched(
+            &mut Bus::default(),
+            opcode,
+            address,
+        )
+    }
+
+    /// An interpreter that is only used to decode instructions, so opcodes it does not know are
+    /// shown rather than treated as errors.
+    fn disassembler(registers: RegisterBank, instruction_mode: InstructionMode) -> Self {
+        let mut scratch = Interpreter {
+            registers,
+            unimplemented_opcode_policy: UnimplementedOpcodePolicy::Skip,
+            ..Default::default()
+        };
+        scratch.registers.cpsr.instruction_mode = instruction_mode;
+        scratch
+    }
+
+    fn disassemble_fetched(
+        &mut self,
+        bus: &mut Bus,
+        opcode: u32,
+        location: u32,
+    ) -> Result<String, CoreError> {
+        let size = match self.registers.cpsr.instruction_mode {
+            InstructionMode::Arm => 4,
+            InstructionMode::Thumb => 2,
+        };
+        self.fetched_instruction = Some((opcode, location));
+        // PC relative operands see the PC two instructions ahead, as when executing.
+        *self.registers.reg_mut(15) = location.wrapping_add(2 * size);
+        self.decode()?;
+        let decoded = self
+            .decoded_instruction
+            .as_ref()
+            .expect("decoding a fetched opcode always produces an instruction");
+        let ins = decoded.instruction.executor();
+        Ok(Self::format_instruction(
+            decoded.condition,
+            &ins.mnemonic(),
+            &ins.description(&self.registers, bus),
+        ))
     }
 
     fn get_condition_label(condition_code: u32) -> &'static str {
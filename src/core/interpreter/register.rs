@@ -1,5 +1,8 @@
+use serde::{Deserialize, Serialize};
+
 use super::status::{CpuMode, InstructionMode, ProgramStatusRegister};
 
+#[derive(Clone, Serialize, Deserialize)]
 pub struct RegisterBank {
     reg: [u32; 16],
     fiq_reg: [u32; 7],
@@ -44,8 +47,14 @@ impl RegisterBank {
         }
     }
 
+    /// Jumps to `value` and flushes the pipeline. On the ARMv4T only BX changes the instruction
+    /// set, so any other write to r15 stays in the current state and drops the low bits of the
+    /// address that state cannot use. BX switches state before calling this.
     pub fn set_pc(&mut self, value: u32) {
-        *self.reg_mut(15) = value;
+        *self.reg_mut(15) = match self.cpsr.instruction_mode {
+            InstructionMode::Arm => value & !0b11,
+            InstructionMode::Thumb => value & !0b1,
+        };
         self.pipeline_flush = true;
     }
 
@@ -53,6 +62,21 @@ impl RegisterBank {
         self.reg(15)
     }
 
+    /// Switches into `mode` the way the CPU does when taking an exception. The CPSR is saved to the
+    /// new mode's SPSR, the return address goes into its link register and execution continues in
+    /// ARM state at `vector` with IRQs disabled.
+    pub fn enter_exception(&mut self, mode: CpuMode, vector: u32, return_address: u32) {
+        *self.spsr_with_mode_mut(mode) = self.cpsr;
+        self.cpsr.mode = mode;
+        self.cpsr.instruction_mode = InstructionMode::Arm;
+        self.cpsr.irq_disable = true;
+        if let CpuMode::Fiq = mode {
+            self.cpsr.fiq_disable = true;
+        }
+        *self.reg_mut(14) = return_address;
+        self.set_pc(vector);
+    }
+
     fn spsr_with_mode_mut(&mut self, mode: CpuMode) -> &mut ProgramStatusRegister {
         match mode {
             CpuMode::Fiq => &mut self.spsr[0],
@@ -96,7 +120,7 @@ impl RegisterBank {
                 if index < 8 || index == 15 {
                     &mut self.reg[index]
                 } else {
-                    &mut self.fiq_reg[index - 7]
+                    &mut self.fiq_reg[index - 8]
                 }
             }
             CpuMode::Supervisor => {
@@ -130,6 +154,11 @@ impl RegisterBank {
         }
     }
 
+    /// r0 to r15 as seen from `mode`, which need not be the mode the CPU is in.
+    pub fn registers_with_mode(&self, mode: CpuMode) -> [u32; 16] {
+        std::array::from_fn(|index| self.reg_with_mode(index, mode))
+    }
+
     pub fn reg_with_mode(&self, index: usize, mode: CpuMode) -> u32 {
         match mode {
             CpuMode::User | CpuMode::System => self.reg[index],
@@ -137,7 +166,7 @@ impl RegisterBank {
                 if index < 8 || index == 15 {
                     self.reg[index]
                 } else {
-                    self.fiq_reg[index - 7]
+                    self.fiq_reg[index - 8]
                 }
             }
             CpuMode::Supervisor => {
@@ -171,6 +200,37 @@ impl RegisterBank {
         }
     }
 
+    /// Every general purpose and saved status register along with its name, banked copies
+    /// included. The CPSR is left out since its flags are more useful on their own.
+    pub fn named_registers(&self) -> Vec<(String, u32)> {
+        let mut registers: Vec<(String, u32)> = self
+            .reg
+            .iter()
+            .enumerate()
+            .map(|(i, value)| (format!("r{i}"), *value))
+            .collect();
+        registers.extend(
+            self.fiq_reg
+                .iter()
+                .enumerate()
+                .map(|(i, value)| (format!("r{}_fiq", i + 8), *value)),
+        );
+        for (bank, values) in [
+            ("svc", self.svc_reg),
+            ("abt", self.abt_reg),
+            ("irq", self.irq_reg),
+            ("und", self.und_reg),
+        ] {
+            registers.push((format!("r13_{bank}"), values[0]));
+            registers.push((format!("r14_{bank}"), values[1]));
+        }
+        for (bank, spsr) in ["fiq", "svc", "irq", "abt", "und"].iter().zip(self.spsr) {
+            registers.push((format!("spsr_{bank}"), spsr.to_u32()));
+        }
+
+        registers
+    }
+
     pub fn reg_mut(&mut self, index: usize) -> &mut u32 {
         self.reg_with_mode_mut(index, self.cpsr.mode)
     }
@@ -178,4 +238,14 @@ impl RegisterBank {
     pub fn reg(&self, index: usize) -> u32 {
         self.reg_with_mode(index, self.cpsr.mode)
     }
+
+    /// Reads a register for an instruction that shifts by a register. The shift amount is read in
+    /// an extra cycle, during which the PC has moved on by another instruction.
+    pub fn reg_with_shift_prefetch(&self, index: usize) -> u32 {
+        if index == 15 {
+            self.pc() + 4
+        } else {
+            self.reg(index)
+        }
+    }
 }
@@ -62,40 +62,62 @@ impl ShiftType {
         }
     }
 
+    /// Shifts by an amount encoded in the instruction. An amount of zero is used to encode LSR #32,
+    /// ASR #32 and RRX, while LSL #0 passes the operand and carry through untouched.
     pub fn shift(&self, operand: u32, shift_amount: u32, old_carry: bool) -> (u32, bool) {
-        match self {
-            ShiftType::LogicalLeft => {
-                let carry = if shift_amount > 0 {
-                    ((operand << shift_amount - 1) & (1 << 31)) > 0
-                } else {
-                    false
-                };
-                (operand << shift_amount, carry)
+        match (self, shift_amount) {
+            (ShiftType::LogicalLeft, 0) => (operand, old_carry),
+            (ShiftType::LogicalRight, 0) => (0, operand & (1 << 31) > 0),
+            (ShiftType::ArithmeticRight, 0) => {
+                (((operand as i32) >> 31) as u32, operand & (1 << 31) > 0)
             }
-            ShiftType::LogicalRight => {
-                let shift_amount = if shift_amount > 0 { shift_amount } else { 32 };
-                (
-                    operand >> shift_amount,
-                    (operand & (1 << shift_amount - 1)) > 0,
-                )
+            (ShiftType::RotateRight, 0) => {
+                ((operand >> 1) | ((old_carry as u32) << 31), operand & 1 > 0)
             }
-            ShiftType::ArithmeticRight => {
-                let shift_amount = if shift_amount > 0 { shift_amount } else { 32 };
-                (
+            _ => self.shift_by_register(operand, shift_amount, old_carry),
+        }
+    }
+
+    /// Shifts by an amount taken from the bottom byte of a register. An amount of zero passes the
+    /// operand and carry through untouched and amounts of 32 or more are shifted out entirely.
+    pub fn shift_by_register(
+        &self,
+        operand: u32,
+        shift_amount: u32,
+        old_carry: bool,
+    ) -> (u32, bool) {
+        let shift_amount = shift_amount & 0xFF;
+        if shift_amount == 0 {
+            return (operand, old_carry);
+        }
+
+        match self {
+            ShiftType::LogicalLeft => match shift_amount {
+                1..=31 => (
+                    operand << shift_amount,
+                    operand & (1 << (32 - shift_amount)) > 0,
+                ),
+                32 => (0, operand & 1 > 0),
+                _ => (0, false),
+            },
+            ShiftType::LogicalRight => match shift_amount {
+                1..=31 => (
+                    operand >> shift_amount,
+                    operand & (1 << (shift_amount - 1)) > 0,
+                ),
+                32 => (0, operand & (1 << 31) > 0),
+                _ => (0, false),
+            },
+            ShiftType::ArithmeticRight => match shift_amount {
+                1..=31 => (
                     ((operand as i32) >> shift_amount) as u32,
-                    (operand & (1 << shift_amount - 1)) > 0,
-                )
-            }
+                    operand & (1 << (shift_amount - 1)) > 0,
+                ),
+                _ => (((operand as i32) >> 31) as u32, operand & (1 << 31) > 0),
+            },
             ShiftType::RotateRight => {
-                if shift_amount > 0 {
-                    (
-                        operand.rotate_right(shift_amount),
-                        (operand & (1 << shift_amount - 1)) > 0,
-                    )
-                } else {
-                    let old_carry = if old_carry { 1 } else { 0 };
-                    (operand.rotate_right(1) | (old_carry << 31), operand & 1 > 0)
-                }
+                let result = operand.rotate_right(shift_amount);
+                (result, result & (1 << 31) > 0)
             }
         }
     }
@@ -149,8 +171,8 @@ impl ImmediateShift {
 
 impl RegisterShift {
     pub fn shift(&self, registers: &RegisterBank) -> (u32, bool) {
-        self.shift_type.shift(
-            registers.reg(self.base_register as usize),
+        self.shift_type.shift_by_register(
+            registers.reg_with_shift_prefetch(self.base_register as usize),
             registers.reg(self.shift_register as usize),
             registers.cpsr.carry,
         )
@@ -189,5 +211,5 @@ impl Display for ImmediateShift {
 pub fn rotated_immediate(opcode: u32) -> u32 {
     let shift_amount = 2 * ((opcode >> 8) & 0xF);
     let immediate = opcode & 0xFF;
-    (immediate as u32).rotate_right(shift_amount)
+    immediate.rotate_right(shift_amount)
 }
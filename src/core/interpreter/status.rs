@@ -1,4 +1,6 @@
-#[derive(Copy, Clone, Default)]
+use serde::{Deserialize, Serialize};
+
+#[derive(Copy, Clone, Default, Serialize, Deserialize)]
 #[repr(u32)]
 pub enum CpuMode {
     #[default]
@@ -23,9 +25,14 @@ impl CpuMode {
             _ => CpuMode::Undefined,
         }
     }
+
+    /// User and System mode share a register bank and have no SPSR.
+    pub fn has_spsr(&self) -> bool {
+        !matches!(self, CpuMode::User | CpuMode::System)
+    }
 }
 
-#[derive(Copy, Clone, Default)]
+#[derive(Copy, Clone, Default, Serialize, Deserialize)]
 #[repr(u32)]
 pub enum InstructionMode {
     #[default]
@@ -33,7 +40,20 @@ pub enum InstructionMode {
     Thumb = 1,
 }
 
-#[derive(Copy, Clone, Default)]
+/// A single bit flag of the CPSR that can be changed from outside the CPU. The state bit is left
+/// out since changing it means refilling the pipeline.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StatusFlag {
+    Negative,
+    Zero,
+    Carry,
+    Overflow,
+    StickyOverflow,
+    IrqDisable,
+    FiqDisable,
+}
+
+#[derive(Copy, Clone, Default, Serialize, Deserialize)]
 pub struct ProgramStatusRegister {
     pub signed: bool,
     pub zero: bool,
@@ -47,7 +67,7 @@ pub struct ProgramStatusRegister {
 }
 
 impl ProgramStatusRegister {
-    pub fn to_u32(&self) -> u32 {
+    pub fn to_u32(self) -> u32 {
         ((self.signed as u32) << 31)
             | ((self.zero as u32) << 30)
             | ((self.carry as u32) << 29)
@@ -59,6 +79,33 @@ impl ProgramStatusRegister {
             | self.mode as u32
     }
 
+    /// The single bit flags along with their names.
+    pub fn flags(&self) -> [(&'static str, bool); 8] {
+        [
+            ("N", self.signed),
+            ("Z", self.zero),
+            ("C", self.carry),
+            ("V", self.overflow),
+            ("Q", self.sticky_overflow),
+            ("I", self.irq_disable),
+            ("F", self.fiq_disable),
+            ("T", self.instruction_mode as u32 > 0),
+        ]
+    }
+
+    pub fn set_flag(&mut self, flag: StatusFlag, value: bool) {
+        let bit = match flag {
+            StatusFlag::Negative => &mut self.signed,
+            StatusFlag::Zero => &mut self.zero,
+            StatusFlag::Carry => &mut self.carry,
+            StatusFlag::Overflow => &mut self.overflow,
+            StatusFlag::StickyOverflow => &mut self.sticky_overflow,
+            StatusFlag::IrqDisable => &mut self.irq_disable,
+            StatusFlag::FiqDisable => &mut self.fiq_disable,
+        };
+        *bit = value;
+    }
+
     pub fn from_u32(psr: u32) -> Self {
         Self {
             signed: psr & (1 << 31) > 0,
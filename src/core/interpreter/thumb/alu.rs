@@ -1,7 +1,7 @@
 use num_enum::TryFromPrimitive;
 
 use crate::core::interpreter::{
-    arm::{DataProcessingInstruction, DataProcessingOperation},
+    arm::{DataProcessingInstruction, DataProcessingOperation, MultiplyInstruction},
     instruction::{Instruction, Operand},
     shift::{ImmediateShift, RegisterShift, Shift, ShiftType},
 };
@@ -34,31 +34,31 @@ pub fn decode_mcas_immediate(opcode: u32) -> Instruction {
 
     match operation {
         McasOperation::Move => Instruction::DataProcessing(DataProcessingInstruction::new(
-            false,
+            true,
             rd,
             imm8,
-            Some(rd as u32),
+            Some(rd),
             DataProcessingOperation::Move,
         )),
         McasOperation::Compare => Instruction::DataProcessing(DataProcessingInstruction::new(
-            false,
+            true,
             rd,
             imm8,
             None,
             DataProcessingOperation::Compare,
         )),
         McasOperation::Add => Instruction::DataProcessing(DataProcessingInstruction::new(
-            false,
+            true,
             rd,
             imm8,
-            Some(rd as u32),
+            Some(rd),
             DataProcessingOperation::Add,
         )),
         McasOperation::Subtract => Instruction::DataProcessing(DataProcessingInstruction::new(
-            false,
+            true,
             rd,
             imm8,
-            Some(rd as u32),
+            Some(rd),
             DataProcessingOperation::Subtract,
         )),
     }
@@ -67,11 +67,11 @@ pub fn decode_mcas_immediate(opcode: u32) -> Instruction {
 pub fn decode_add_subtract(opcode: u32) -> Instruction {
     let operation = (opcode >> 9) & 1 > 0;
     let rd = opcode & 0b111;
-    let rs = (opcode >> 3) & 0b11;
+    let rs = (opcode >> 3) & 0b111;
     let rn = (opcode >> 6) & 0b111;
 
     let operand = if (opcode >> 10) & 1 > 0 {
-        Operand::Immediate((rn as u32, false))
+        Operand::Immediate((rn, false))
     } else {
         Operand::Register(rn)
     };
@@ -80,7 +80,7 @@ pub fn decode_add_subtract(opcode: u32) -> Instruction {
         true,
         rs,
         operand,
-        Some(rd as u32),
+        Some(rd),
         if operation {
             DataProcessingOperation::Subtract
         } else {
@@ -156,8 +156,9 @@ pub fn decode_alu_operations(opcode: u32) -> Instruction {
             ))),
         ),
         AluOperation::Tst => (DataProcessingOperation::Test, Operand::Register(rs)),
+        // Rd := 0 - Rs
         AluOperation::Neg => (
-            DataProcessingOperation::Subtract,
+            DataProcessingOperation::ReverseSubtract,
             Operand::Immediate((0, false)),
         ),
         AluOperation::Cmp => (DataProcessingOperation::Compare, Operand::Register(rs)),
@@ -166,7 +167,9 @@ pub fn decode_alu_operations(opcode: u32) -> Instruction {
             Operand::Register(rs),
         ),
         AluOperation::Orr => (DataProcessingOperation::Or, Operand::Register(rs)),
-        AluOperation::Mul => unimplemented!(),
+        AluOperation::Mul => {
+            return Instruction::Multiply(MultiplyInstruction::new(true, rd, rd, rs));
+        }
         AluOperation::Bic => (DataProcessingOperation::AndNot, Operand::Register(rs)),
         AluOperation::Mvn => (DataProcessingOperation::MoveNegate, Operand::Register(rs)),
     };
@@ -178,7 +181,7 @@ pub fn decode_alu_operations(opcode: u32) -> Instruction {
 
     let destination = match operation {
         AluOperation::Tst | AluOperation::Cmp | AluOperation::Cmn => None,
-        _ => Some(rd as u32),
+        _ => Some(rd),
     };
 
     Instruction::DataProcessing(DataProcessingInstruction::new(
@@ -191,25 +194,38 @@ pub fn decode_alu_operations(opcode: u32) -> Instruction {
 }
 
 pub fn decode_move_shifted_register(opcode: u32) -> Instruction {
-    let op = ShiftType::from_u32((opcode >> 11) & 0b11);
+    let shift_type = ShiftType::from_u32((opcode >> 11) & 0b11);
     let offset = (opcode >> 6) & 0b11111;
     let rs = (opcode >> 3) & 0b111;
     let rd = opcode & 0b111;
 
-    let shift_type = match op {
-        ShiftType::LogicalLeft => ShiftType::LogicalLeft,
-        ShiftType::LogicalRight => ShiftType::LogicalRight,
-        ShiftType::ArithmeticRight => ShiftType::ArithmeticRight,
-        ShiftType::RotateRight => ShiftType::RotateRight,
-    };
-
     Instruction::DataProcessing(DataProcessingInstruction::new(
         true,
         rs,
         Operand::RegisterShifted(Shift::Immediate(ImmediateShift::new(
             rs, offset, shift_type,
         ))),
-        Some(rd as u32),
+        Some(rd),
         DataProcessingOperation::Move,
     ))
 }
+
+pub fn decode_load_address(opcode: u32) -> Instruction {
+    let stack_pointer = (opcode >> 11) & 1 > 0;
+    let rd = (opcode >> 8) & 0b111;
+    let offset = (opcode & 0xFF) << 2;
+
+    let instruction = DataProcessingInstruction::new(
+        false,
+        if stack_pointer { 13 } else { 15 },
+        Operand::Immediate((offset, false)),
+        Some(rd),
+        DataProcessingOperation::Add,
+    );
+
+    Instruction::DataProcessing(if stack_pointer {
+        instruction
+    } else {
+        instruction.with_word_aligned_source()
+    })
+}
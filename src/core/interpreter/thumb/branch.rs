@@ -24,17 +24,16 @@ pub const HI_REGISTER_OPERATIONS_BRANCH_EXCHANGE_MASK: u32 = 0b1111_1100_0000_00
 pub const LONG_BRANCH_WITH_LINK_FORMAT: u32 = 0b1111_0000_0000_0000;
 pub const LONG_BRANCH_WITH_LINK_MASK: u32 = 0b1111_0000_0000_0000;
 
+/// Both branches are relative to the PC as seen during execute, which is the instruction address
+/// plus 4, the same as ARM branches are relative to their address plus 8.
 pub fn decode_conditional_branch(opcode: u32) -> Instruction {
-    let offset = (((opcode & 0xFF) as i8) as i32) << 1;
-    Instruction::Branch(BranchInstruction::new(None, offset as i32))
+    let offset = ((opcode & 0xFF) << 24) as i32 >> 23;
+    Instruction::Branch(BranchInstruction::new(None, offset))
 }
 
 pub fn decode_unconditional_branch(opcode: u32) -> Instruction {
-    let mut offset = (opcode & 0x7FF) << 1;
-    if (offset >> 10) & 1 > 0 {
-        offset |= 0xF8000000;
-    }
-    Instruction::Branch(BranchInstruction::new(None, offset as i32))
+    let offset = ((opcode & 0x7FF) << 21) as i32 >> 20;
+    Instruction::Branch(BranchInstruction::new(None, offset))
 }
 
 #[derive(TryFromPrimitive)]
@@ -49,20 +48,20 @@ enum HiRegBxOperation {
 pub fn decode_hi_reg_branch_exchange(opcode: u32) -> Instruction {
     let op = HiRegBxOperation::try_from((opcode >> 8) & 0b11).unwrap();
     let rs = (opcode >> 3) & 0b1111;
-    let rd = (opcode & 0b111) | ((opcode >> 7) & 1);
+    let rd = (opcode & 0b111) | ((opcode >> 4) & 0b1000);
 
     match op {
         HiRegBxOperation::Add => Instruction::DataProcessing(DataProcessingInstruction::new(
             false,
             rs,
             Operand::Register(rd),
-            Some(rd as u32),
+            Some(rd),
             DataProcessingOperation::Add,
         )),
         HiRegBxOperation::Compare => Instruction::DataProcessing(DataProcessingInstruction::new(
             true,
-            rs,
-            Operand::Register(rd),
+            rd,
+            Operand::Register(rs),
             None,
             DataProcessingOperation::Compare,
         )),
@@ -70,7 +69,7 @@ pub fn decode_hi_reg_branch_exchange(opcode: u32) -> Instruction {
             false,
             rs,
             Operand::Register(rs),
-            Some(rd as u32),
+            Some(rd),
             DataProcessingOperation::Move,
         )),
         HiRegBxOperation::BranchExchange => {
@@ -93,6 +92,17 @@ impl LongBranchWithLinkInstruction {
     }
 }
 
+impl LongBranchWithLinkInstruction {
+    /// The upper half of the offset, sign extended and shifted into place.
+    fn high_offset(&self) -> u32 {
+        (((self.offset << 21) as i32) >> 9) as u32
+    }
+
+    fn low_offset(&self) -> u32 {
+        self.offset << 1
+    }
+}
+
 impl InstructionExecutor for LongBranchWithLinkInstruction {
     fn execute(
         &self,
@@ -100,13 +110,12 @@ impl InstructionExecutor for LongBranchWithLinkInstruction {
         _bus: &mut crate::core::Bus,
     ) -> Result<usize, CoreError> {
         if self.h {
-            let address = (registers.reg(14) + (self.offset << 1)) & !1;
-            let pc = registers.pc() - 2;
+            let address = registers.reg(14).wrapping_add(self.low_offset());
+            let return_address = registers.pc() - 2;
             registers.set_pc(address);
-            *registers.reg_mut(14) = pc | 1;
+            *registers.reg_mut(14) = return_address | 1;
         } else {
-            let address = registers.pc().wrapping_add(self.offset << 12) & !0xFF800000;
-            *registers.reg_mut(14) = address;
+            *registers.reg_mut(14) = registers.pc().wrapping_add(self.high_offset());
         }
 
         Ok(1)
@@ -122,9 +131,9 @@ impl InstructionExecutor for LongBranchWithLinkInstruction {
 
     fn description(&self, registers: &RegisterBank, _bus: &mut crate::core::Bus) -> String {
         let address_hint = if self.h {
-            registers.reg(14) + (self.offset << 1)
+            registers.reg(14).wrapping_add(self.low_offset())
         } else {
-            registers.pc() + (self.offset << 12)
+            registers.pc().wrapping_add(self.high_offset())
         };
 
         format!("#{:X} (=${:08X})", self.offset, address_hint)
@@ -1,5 +1,8 @@
 use crate::core::interpreter::{
-    arm::{HalfwordDataOffset, HalfwordDataTransferRegInstruction, SingleDataTransferInstruction},
+    arm::{
+        BlockDataTransferInstruction, HalfwordDataOffset, HalfwordDataTransferRegInstruction,
+        SingleDataTransferInstruction,
+    },
     instruction::{Instruction, Operand},
 };
 
@@ -68,7 +71,7 @@ pub fn decode_pc_relative_load(opcode: u32) -> Instruction {
     Instruction::SingleDataTransfer(SingleDataTransferInstruction::new(
         rd,
         15,
-        Operand::Immediate((word8 as u32, false)),
+        Operand::Immediate((word8, false)),
         true,
         false,
         false,
@@ -85,7 +88,7 @@ pub fn decode_sp_relative_load_store(opcode: u32) -> Instruction {
     Instruction::SingleDataTransfer(SingleDataTransferInstruction::new(
         rd,
         13,
-        Operand::Immediate((word8 as u32, false)),
+        Operand::Immediate((word8, false)),
         load,
         false,
         false,
@@ -133,3 +136,26 @@ pub fn decode_load_store_sign_extended(opcode: u32) -> Instruction {
         rd,
     ))
 }
+
+pub fn decode_multiple_load_store(opcode: u32) -> Instruction {
+    let load = (opcode >> 11) & 1 > 0;
+    let rb = (opcode >> 8) & 0b111;
+    let register_list = opcode & 0x00FF;
+    let mut number_of_registers = 0;
+    for i in 0..8 {
+        if (register_list >> i) & 1 > 0 {
+            number_of_registers += 1;
+        }
+    }
+
+    Instruction::BlockDataTransfer(BlockDataTransferInstruction::new(
+        rb,
+        register_list as u16,
+        load,
+        true,
+        true,
+        false,
+        false,
+        number_of_registers,
+    ))
+}
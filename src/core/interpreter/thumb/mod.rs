@@ -21,3 +21,6 @@ pub const LOAD_ADDRESS_MASK: u32 = 0b1111_0000_0000_0000;
 
 pub const LOAD_STORE_WITH_IMMEDIATE_OFFSET_FORMAT: u32 = 0b0110_0000_0000_0000;
 pub const LOAD_STORE_WITH_IMMEDIATE_OFFSET_MASK: u32 = 0b1110_0000_0000_0000;
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,236 @@
+use std::{cell::RefCell, rc::Rc};
+
+use crate::core::{
+    interpreter::{
+        arm::DataProcessingInstruction,
+        instruction::{Instruction, InstructionExecutor, Operand},
+        register::RegisterBank,
+        shift::Shift,
+        status::InstructionMode,
+        thumb::{
+            decode_add_subtract, decode_alu_operations, decode_hi_reg_branch_exchange,
+            decode_load_address, decode_mcas_immediate,
+        },
+    },
+    memory::wram::Wram,
+    Bus, CoreError,
+};
+
+fn setup() -> (Bus, RegisterBank) {
+    let wram = Wram::new(0, 1024);
+
+    let mut bus = Bus::default();
+    bus.register_region(0..=1023, Rc::new(RefCell::new(wram)));
+
+    let mut registers = RegisterBank::default();
+    registers.cpsr.instruction_mode = InstructionMode::Thumb;
+
+    (bus, registers)
+}
+
+fn execute(
+    registers: &mut RegisterBank,
+    bus: &mut Bus,
+    instruction: Instruction,
+) -> Result<usize, CoreError> {
+    instruction.executor().execute(registers, bus)
+}
+
+#[test]
+fn mov_immediate_sets_flags() -> Result<(), CoreError> {
+    let (mut bus, mut registers) = setup();
+    *registers.reg_mut(0) = 0x55;
+
+    // mov r0, #0
+    execute(&mut registers, &mut bus, decode_mcas_immediate(0x2000))?;
+
+    assert_eq!(registers.reg(0), 0);
+    assert!(registers.cpsr.zero);
+    assert!(!registers.cpsr.signed);
+
+    Ok(())
+}
+
+#[test]
+fn immediate_arithmetic_sets_flags() -> Result<(), CoreError> {
+    let (mut bus, mut registers) = setup();
+    *registers.reg_mut(1) = 5;
+
+    // cmp r1, #5
+    execute(&mut registers, &mut bus, decode_mcas_immediate(0x2905))?;
+    assert!(registers.cpsr.zero);
+    assert!(registers.cpsr.carry);
+
+    // sub r1, #6
+    execute(&mut registers, &mut bus, decode_mcas_immediate(0x3906))?;
+    assert_eq!(registers.reg(1), 0xFFFF_FFFF);
+    assert!(registers.cpsr.signed);
+    assert!(!registers.cpsr.carry);
+
+    // add r1, #1
+    execute(&mut registers, &mut bus, decode_mcas_immediate(0x3101))?;
+    assert_eq!(registers.reg(1), 0);
+    assert!(registers.cpsr.zero);
+    assert!(registers.cpsr.carry);
+
+    Ok(())
+}
+
+#[test]
+fn neg_subtracts_from_zero() -> Result<(), CoreError> {
+    let (mut bus, mut registers) = setup();
+    *registers.reg_mut(1) = 5;
+
+    // neg r0, r1
+    execute(&mut registers, &mut bus, decode_alu_operations(0x4248))?;
+    assert_eq!(registers.reg(0), 0xFFFFFFFB);
+    assert!(registers.cpsr.signed);
+    assert!(!registers.cpsr.zero);
+    // 0 - 5 borrows.
+    assert!(!registers.cpsr.carry);
+
+    Ok(())
+}
+
+#[test]
+fn mul_sets_flags() -> Result<(), CoreError> {
+    let (mut bus, mut registers) = setup();
+    *registers.reg_mut(0) = 6;
+    *registers.reg_mut(1) = 7;
+
+    // mul r0, r1
+    execute(&mut registers, &mut bus, decode_alu_operations(0x4348))?;
+    assert_eq!(registers.reg(0), 42);
+    assert_eq!(registers.reg(1), 7);
+    assert!(!registers.cpsr.zero);
+    assert!(!registers.cpsr.signed);
+
+    *registers.reg_mut(1) = -1i32 as u32;
+    execute(&mut registers, &mut bus, decode_alu_operations(0x4348))?;
+    assert_eq!(registers.reg(0) as i32, -42);
+    assert!(registers.cpsr.signed);
+
+    *registers.reg_mut(1) = 0;
+    execute(&mut registers, &mut bus, decode_alu_operations(0x4348))?;
+    assert_eq!(registers.reg(0), 0);
+    assert!(registers.cpsr.zero);
+
+    Ok(())
+}
+
+#[test]
+fn add_subtract_uses_high_source_register() -> Result<(), CoreError> {
+    let (mut bus, mut registers) = setup();
+    *registers.reg_mut(4) = 10;
+    *registers.reg_mut(2) = 3;
+
+    // sub r0, r4, r2
+    execute(&mut registers, &mut bus, decode_add_subtract(0x1AA0))?;
+    assert_eq!(registers.reg(0), 7);
+
+    Ok(())
+}
+
+#[test]
+fn hi_register_compare_order() -> Result<(), CoreError> {
+    let (mut bus, mut registers) = setup();
+    *registers.reg_mut(8) = 1;
+    *registers.reg_mut(1) = 2;
+
+    // cmp r8, r1
+    execute(
+        &mut registers,
+        &mut bus,
+        decode_hi_reg_branch_exchange(0x4588),
+    )?;
+    assert!(registers.cpsr.signed);
+    assert!(!registers.cpsr.carry);
+
+    // mov r9, r1
+    execute(
+        &mut registers,
+        &mut bus,
+        decode_hi_reg_branch_exchange(0x4689),
+    )?;
+    assert_eq!(registers.reg(9), 2);
+
+    Ok(())
+}
+
+#[test]
+fn load_address_from_pc_is_word_aligned() -> Result<(), CoreError> {
+    let (mut bus, mut registers) = setup();
+    registers.set_pc(0x106);
+
+    // add r0, pc, #8
+    execute(&mut registers, &mut bus, decode_load_address(0xA002))?;
+    assert_eq!(registers.reg(0), 0x10C);
+
+    Ok(())
+}
+
+#[test]
+fn load_address_from_sp() -> Result<(), CoreError> {
+    let (mut bus, mut registers) = setup();
+    *registers.reg_mut(13) = 0x3007F02;
+    registers.cpsr.zero = false;
+
+    // add r1, sp, #0x3FC
+    execute(&mut registers, &mut bus, decode_load_address(0xA9FF))?;
+    assert_eq!(registers.reg(1), 0x30082FE);
+    assert!(!registers.cpsr.zero);
+
+    Ok(())
+}
+
+#[test]
+fn operands_carry_and_display() {
+    let mut registers = RegisterBank::default();
+    *registers.reg_mut(2) = 0x8000_0001;
+    registers.cpsr.carry = true;
+
+    // Plain immediates, as the Thumb decoders build them, leave the carry alone.
+    let immediate = Operand::Immediate((0x3FC, false));
+    assert_eq!(immediate.value(&registers), (0x3FC, true));
+    assert_eq!(immediate.to_string(), "#0x3FC");
+
+    let rotated = Operand::Immediate((0xF000_0000, true));
+    registers.cpsr.carry = false;
+    assert_eq!(rotated.value(&registers), (0xF000_0000, true));
+    assert_eq!(rotated.to_string(), "#0xF0000000");
+
+    // r2, LSL #1
+    let shifted = Operand::RegisterShifted(Shift::from_opcode(0x082));
+    assert_eq!(shifted.value(&registers), (0x2, true));
+    assert_eq!(shifted.to_string(), "r2, LSL, #1");
+}
+
+#[test]
+fn arm_and_thumb_immediates_share_carry_rules() -> Result<(), CoreError> {
+    let (mut bus, mut registers) = setup();
+    registers.cpsr.carry = true;
+
+    // mov r0, #1
+    execute(&mut registers, &mut bus, decode_mcas_immediate(0x2001))?;
+    assert_eq!(registers.reg(0), 1);
+    assert!(registers.cpsr.carry);
+
+    registers.cpsr.instruction_mode = InstructionMode::Arm;
+
+    // movs r1, #1
+    DataProcessingInstruction::decode(0xE3B01001).execute(&mut registers, &mut bus)?;
+    assert_eq!(registers.reg(1), 1);
+    assert!(registers.cpsr.carry);
+
+    // movs r2, #0x40000000
+    DataProcessingInstruction::decode(0xE3B02101).execute(&mut registers, &mut bus)?;
+    assert_eq!(registers.reg(2), 0x4000_0000);
+    assert!(!registers.cpsr.carry);
+
+    // movs r3, #0x80000000
+    DataProcessingInstruction::decode(0xE3B03102).execute(&mut registers, &mut bus)?;
+    assert_eq!(registers.reg(3), 0x8000_0000);
+    assert!(registers.cpsr.carry);
+
+    Ok(())
+}
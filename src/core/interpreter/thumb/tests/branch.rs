@@ -0,0 +1,101 @@
+use std::{cell::RefCell, rc::Rc};
+
+use crate::core::{
+    interpreter::{
+        instruction::InstructionExecutor, register::RegisterBank, status::InstructionMode,
+        thumb::LongBranchWithLinkInstruction, Interpreter,
+    },
+    memory::wram::Wram,
+    Bus, CoreError,
+};
+
+fn setup() -> (Bus, RegisterBank) {
+    let wram = Wram::new(0, 1024);
+
+    let mut bus = Bus::default();
+    bus.register_region(0..=1023, Rc::new(RefCell::new(wram)));
+
+    let mut registers = RegisterBank::default();
+    registers.cpsr.instruction_mode = InstructionMode::Thumb;
+
+    (bus, registers)
+}
+
+/// Executes a BL pair located at `address` and returns the registers afterwards.
+fn long_branch(address: u32, high: u32, low: u32) -> Result<RegisterBank, CoreError> {
+    let (mut bus, mut registers) = setup();
+
+    *registers.reg_mut(15) = address + 4;
+    LongBranchWithLinkInstruction::decode(high).execute(&mut registers, &mut bus)?;
+    *registers.reg_mut(15) = address + 6;
+    LongBranchWithLinkInstruction::decode(low).execute(&mut registers, &mut bus)?;
+
+    Ok(registers)
+}
+
+#[test]
+fn bl_forward() -> Result<(), CoreError> {
+    // bl #0x08001000
+    let registers = long_branch(0x0800_0100, 0xF000, 0xFF7E)?;
+    assert_eq!(registers.pc(), 0x0800_1000);
+    assert_eq!(registers.reg(14), 0x0800_0105);
+
+    // The furthest forward target, 4MB - 2 past the PC.
+    let registers = long_branch(0x0800_0100, 0xF3FF, 0xFFFF)?;
+    assert_eq!(registers.pc(), 0x0800_0104 + 0x3F_FFFE);
+    assert_eq!(registers.reg(14), 0x0800_0105);
+
+    Ok(())
+}
+
+#[test]
+fn bl_backward() -> Result<(), CoreError> {
+    // bl #0x08000000
+    let registers = long_branch(0x0800_0100, 0xF7FF, 0xFF7E)?;
+    assert_eq!(registers.pc(), 0x0800_0000);
+    assert_eq!(registers.reg(14), 0x0800_0105);
+
+    // The furthest backward target, 4MB before the PC.
+    let registers = long_branch(0x0840_0000, 0xF400, 0xF800)?;
+    assert_eq!(registers.pc(), 0x0800_0004);
+
+    Ok(())
+}
+
+/// Runs the branch at 0x100 through the pipeline and returns where the next fetch came from.
+fn run_branch(opcode: u16, zero: bool) -> Result<u32, CoreError> {
+    let (mut bus, _) = setup();
+    bus.write_word(0x100, opcode)?;
+
+    let mut cpu = Interpreter::default();
+    cpu.jump_to(0x100, InstructionMode::Thumb);
+    cpu.registers.cpsr.zero = zero;
+    // Fetch, decode and then execute the branch.
+    for _ in 0..3 {
+        cpu.tick(&mut bus)?;
+    }
+
+    Ok(cpu.fetched_instruction.unwrap().1)
+}
+
+#[test]
+fn beq_forward_and_backward() -> Result<(), CoreError> {
+    // beq #0x114
+    assert_eq!(run_branch(0xD008, true)?, 0x114);
+    // beq #0xF0
+    assert_eq!(run_branch(0xD0F6, true)?, 0xF0);
+    // Not taken, so execution carries on after the branch.
+    assert_eq!(run_branch(0xD0F6, false)?, 0x104);
+
+    Ok(())
+}
+
+#[test]
+fn b_forward_and_backward() -> Result<(), CoreError> {
+    // b #0x114
+    assert_eq!(run_branch(0xE008, false)?, 0x114);
+    // b #0xF0
+    assert_eq!(run_branch(0xE7F6, false)?, 0xF0);
+
+    Ok(())
+}
@@ -0,0 +1,70 @@
+use std::{cell::RefCell, rc::Rc};
+
+use crate::core::{
+    interpreter::{
+        instruction::Instruction,
+        status::{CpuMode, InstructionMode},
+        Interpreter,
+    },
+    memory::wram::Wram,
+    Bus, CoreError,
+};
+
+#[test]
+fn swi_enters_supervisor() -> Result<(), CoreError> {
+    let mut bus = Bus::default();
+    bus.register_region(0..=1023, Rc::new(RefCell::new(Wram::new(0, 1024))));
+
+    let mut cpu = Interpreter::default();
+    cpu.registers.cpsr.instruction_mode = InstructionMode::Thumb;
+    cpu.registers.cpsr.carry = true;
+    *cpu.registers.reg_mut(15) = 0x102;
+
+    // swi 0x06, located at 0x100
+    cpu.fetched_instruction = Some((0xDF06, 0x100));
+    cpu.decode()?;
+    let operation = cpu.decoded_instruction.take().unwrap();
+    assert!(matches!(
+        operation.instruction,
+        Instruction::SoftwareInterrupt(_)
+    ));
+    operation
+        .instruction
+        .executor()
+        .execute(&mut cpu.registers, &mut bus)?;
+
+    let registers = &mut cpu.registers;
+    assert!(matches!(registers.cpsr.mode, CpuMode::Supervisor));
+    assert!(matches!(
+        registers.cpsr.instruction_mode,
+        InstructionMode::Arm
+    ));
+    assert!(registers.cpsr.irq_disable);
+    assert_eq!(registers.pc(), 0x08);
+    assert_eq!(registers.reg(14), 0x102);
+
+    let spsr = registers.spsr();
+    assert!(matches!(spsr.mode, CpuMode::User));
+    assert!(matches!(spsr.instruction_mode, InstructionMode::Thumb));
+    assert!(spsr.carry);
+
+    Ok(())
+}
+
+#[test]
+fn swi_is_not_a_never_taken_branch() -> Result<(), CoreError> {
+    let mut cpu = Interpreter::default();
+    cpu.registers.cpsr.instruction_mode = InstructionMode::Thumb;
+
+    // swi 0x12 shares its encoding with a branch whose condition is 0xF.
+    cpu.fetched_instruction = Some((0xDF12, 0x100));
+    cpu.decode()?;
+    let operation = cpu.decoded_instruction.take().unwrap();
+    assert!(matches!(
+        operation.instruction,
+        Instruction::SoftwareInterrupt(_)
+    ));
+    assert!(cpu.check_condition(operation.condition));
+
+    Ok(())
+}
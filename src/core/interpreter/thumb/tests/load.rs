@@ -0,0 +1,118 @@
+use std::{cell::RefCell, rc::Rc};
+
+use crate::core::{
+    interpreter::{
+        instruction::Instruction, register::RegisterBank, status::InstructionMode,
+        thumb::decode_multiple_load_store, Interpreter,
+    },
+    memory::wram::Wram,
+    Bus, CoreError,
+};
+
+fn setup() -> (Bus, RegisterBank) {
+    let wram = Wram::new(0, 1024);
+
+    let mut bus = Bus::default();
+    bus.register_region(0..=1023, Rc::new(RefCell::new(wram)));
+
+    let mut registers = RegisterBank::default();
+    registers.cpsr.instruction_mode = InstructionMode::Thumb;
+
+    (bus, registers)
+}
+
+#[test]
+fn stmia() -> Result<(), CoreError> {
+    const EXPECTED_RESULT: [u32; 3] = [10, 20, 30];
+
+    let (mut bus, mut registers) = setup();
+
+    for (i, value) in EXPECTED_RESULT.iter().enumerate() {
+        *registers.reg_mut(i + 1) = *value;
+    }
+    *registers.reg_mut(0) = 0x10;
+
+    // stmia r0!, {r1, r2, r3}
+    decode_multiple_load_store(0xC00E)
+        .executor()
+        .execute(&mut registers, &mut bus)?;
+
+    let result = [
+        bus.read_dword(0x10)?,
+        bus.read_dword(0x14)?,
+        bus.read_dword(0x18)?,
+    ];
+    assert_eq!(result, EXPECTED_RESULT);
+    assert_eq!(registers.reg(0), 0x1C);
+
+    Ok(())
+}
+
+#[test]
+fn ldmia() -> Result<(), CoreError> {
+    let (mut bus, mut registers) = setup();
+
+    bus.write_dword(0x20, 0xAAAA)?;
+    bus.write_dword(0x24, 0xBBBB)?;
+    *registers.reg_mut(4) = 0x20;
+
+    // ldmia r4!, {r0, r1}
+    decode_multiple_load_store(0xCC03)
+        .executor()
+        .execute(&mut registers, &mut bus)?;
+
+    assert_eq!(registers.reg(0), 0xAAAA);
+    assert_eq!(registers.reg(1), 0xBBBB);
+    assert_eq!(registers.reg(4), 0x28);
+
+    Ok(())
+}
+
+/// Runs a single opcode through the Thumb decoder, checking it comes out as a single data
+/// transfer.
+fn decode(opcode: u32) -> Result<Instruction, CoreError> {
+    let mut cpu = Interpreter::default();
+    cpu.registers.cpsr.instruction_mode = InstructionMode::Thumb;
+    cpu.fetched_instruction = Some((opcode, 0x100));
+    cpu.decode()?;
+
+    let instruction = cpu.decoded_instruction.take().unwrap().instruction;
+    assert!(matches!(instruction, Instruction::SingleDataTransfer(_)));
+    Ok(instruction)
+}
+
+#[test]
+fn ldr_immediate_offset_is_word_scaled() -> Result<(), CoreError> {
+    let (mut bus, mut registers) = setup();
+
+    bus.write_dword(0x48, 0x12345678)?;
+    *registers.reg_mut(1) = 0x40;
+
+    // ldr r0, [r1, #8]
+    decode(0x6888)?
+        .executor()
+        .execute(&mut registers, &mut bus)?;
+
+    assert_eq!(registers.reg(0), 0x12345678);
+    assert_eq!(registers.reg(1), 0x40);
+
+    Ok(())
+}
+
+#[test]
+fn strb_immediate_offset_is_unscaled() -> Result<(), CoreError> {
+    let (mut bus, mut registers) = setup();
+
+    *registers.reg_mut(0) = 0xABCD;
+    *registers.reg_mut(1) = 0x40;
+
+    // strb r0, [r1, #3]
+    decode(0x70C8)?
+        .executor()
+        .execute(&mut registers, &mut bus)?;
+
+    assert_eq!(bus.read_dword(0x40)?, 0xCD000000);
+    assert_eq!(bus.read_byte(0x44)?, 0);
+
+    Ok(())
+}
@@ -0,0 +1,5 @@
+pub mod alu;
+pub mod branch;
+pub mod interrupt;
+pub mod load;
+pub mod stack;
@@ -0,0 +1,57 @@
+use std::{cell::RefCell, rc::Rc};
+
+use crate::core::{
+    interpreter::{
+        register::RegisterBank, status::InstructionMode, thumb::decode_push_pop_registers,
+    },
+    memory::wram::Wram,
+    Bus, CoreError,
+};
+
+fn setup() -> (Bus, RegisterBank) {
+    let wram = Wram::new(0, 1024);
+
+    let mut bus = Bus::default();
+    bus.register_region(0..=1023, Rc::new(RefCell::new(wram)));
+
+    let mut registers = RegisterBank::default();
+    registers.cpsr.instruction_mode = InstructionMode::Thumb;
+
+    (bus, registers)
+}
+
+#[test]
+fn push_pop_ordering() -> Result<(), CoreError> {
+    let (mut bus, mut registers) = setup();
+
+    *registers.reg_mut(0) = 0xA;
+    *registers.reg_mut(1) = 0xB;
+    *registers.reg_mut(13) = 0x100;
+    *registers.reg_mut(14) = 0x201;
+
+    // push {r0, r1, lr}
+    let push = decode_push_pop_registers(0xB503);
+    let cycles = push.executor().execute(&mut registers, &mut bus)?;
+
+    assert_eq!(cycles, 4);
+    assert_eq!(registers.reg(13), 0xF4);
+    assert_eq!(bus.read_dword(0xF4)?, 0xA);
+    assert_eq!(bus.read_dword(0xF8)?, 0xB);
+    assert_eq!(bus.read_dword(0xFC)?, 0x201);
+
+    *registers.reg_mut(0) = 0;
+    *registers.reg_mut(1) = 0;
+
+    // pop {r0, r1, pc}
+    let pop = decode_push_pop_registers(0xBD03);
+    let cycles = pop.executor().execute(&mut registers, &mut bus)?;
+
+    assert_eq!(cycles, 7);
+    assert_eq!(registers.reg(0), 0xA);
+    assert_eq!(registers.reg(1), 0xB);
+    assert_eq!(registers.pc(), 0x200);
+    assert!(registers.pipeline_flush);
+    assert_eq!(registers.reg(13), 0x100);
+
+    Ok(())
+}
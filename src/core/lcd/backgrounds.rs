@@ -0,0 +1,203 @@
+use serde::{Deserialize, Serialize};
+
+use super::{Lcd, OBJ_VRAM_START, SCREEN_WIDTH};
+
+const CHARACTER_BLOCK_SIZE: usize = 0x4000;
+const SCREEN_BLOCK_SIZE: usize = 0x800;
+/// A text screen block holds 32x32 tiles, so 256x256 pixels.
+const SCREEN_BLOCK_PIXELS: usize = 256;
+
+/// The rotation and scaling registers of BG2 or BG3.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub(super) struct AffineParameters {
+    /// PA, PB, PC and PD as 8.8 fixed point.
+    pub parameters: [i16; 4],
+    /// The X and Y reference point as 20.8 fixed point.
+    pub reference: [i32; 2],
+    /// The reference point for the current scanline. It is reloaded from `reference` when that is
+    /// written and at the start of every frame, and moves by PB and PD each scanline.
+    pub current: [i32; 2],
+}
+
+impl AffineParameters {
+    pub fn next_scanline(&mut self) {
+        self.current[0] += self.parameters[1] as i32;
+        self.current[1] += self.parameters[3] as i32;
+    }
+
+    pub fn reload(&mut self) {
+        self.current = self.reference;
+    }
+
+    /// Writes a byte of PA to PD or of the reference point, `offset` bytes into the registers.
+    pub fn write_byte(&mut self, offset: usize, data: u8) {
+        if offset < 8 {
+            let shift = (offset & 1) * 8;
+            let parameter = &mut self.parameters[offset / 2];
+            *parameter = ((*parameter as u16 & !(0xFF << shift)) | ((data as u16) << shift)) as i16;
+        } else {
+            let index = (offset - 8) / 4;
+            let shift = (offset & 0b11) * 8;
+            let raw = (self.reference[index] as u32 & !(0xFF << shift)) | ((data as u32) << shift);
+            // The reference point is a 28 bit signed value.
+            self.reference[index] = ((raw << 4) as i32) >> 4;
+            self.current[index] = self.reference[index];
+        }
+    }
+}
+
+impl Lcd {
+    fn bg_enabled(&self, bg: usize) -> bool {
+        self.display_control & (1 << (8 + bg)) > 0
+    }
+
+    fn character_base(&self, bg: usize) -> usize {
+        ((self.bg_control[bg] >> 2) & 0b11) as usize * CHARACTER_BLOCK_SIZE
+    }
+
+    fn screen_base(&self, bg: usize) -> usize {
+        ((self.bg_control[bg] >> 8) & 0x1F) as usize * SCREEN_BLOCK_SIZE
+    }
+
+    fn bg_size(&self, bg: usize) -> usize {
+        (self.bg_control[bg] >> 14) as usize
+    }
+
+    /// Draws the enabled layers of the tiled modes. Each pixel holds the color and priority of the
+    /// topmost layer that is not transparent there. Between layers of the same priority the lower
+    /// numbered one is in front.
+    pub(super) fn render_backgrounds(&self, line: usize) -> [Option<(u16, u8)>; SCREEN_WIDTH] {
+        let affine_layers = match self.bg_mode() {
+            0 => 0..0,
+            1 => 2..3,
+            _ => 2..4,
+        };
+        let text_layers = match self.bg_mode() {
+            0 => 0..4,
+            1 => 0..2,
+            _ => 0..0,
+        };
+        let mut layers: Vec<usize> = text_layers
+            .chain(affine_layers.clone())
+            .filter(|bg| self.bg_enabled(*bg))
+            .collect();
+        layers.sort_by_key(|bg| (self.bg_priority(*bg), *bg));
+
+        let mut pixels = [None; SCREEN_WIDTH];
+        for bg in layers {
+            let priority = self.bg_priority(bg);
+            for (x, pixel) in pixels.iter_mut().enumerate() {
+                if pixel.is_some() {
+                    continue;
+                }
+                let color = if affine_layers.contains(&bg) {
+                    self.affine_pixel(bg, x)
+                } else {
+                    self.text_pixel(bg, x, line)
+                };
+                *pixel = color.map(|color| (color, priority));
+            }
+        }
+
+        pixels
+    }
+
+    /// The color of a text background at a screen position, or None where it is transparent.
+    fn text_pixel(&self, bg: usize, x: usize, line: usize) -> Option<u16> {
+        let control = self.bg_control[bg];
+        let size = self.bg_size(bg);
+        let width = SCREEN_BLOCK_PIXELS << (size & 1);
+        let height = SCREEN_BLOCK_PIXELS << (size >> 1);
+        let x = (x + self.bg_offsets[bg * 2] as usize) & (width - 1);
+        let y = (line + self.bg_offsets[bg * 2 + 1] as usize) & (height - 1);
+
+        // Larger backgrounds are made of screen blocks laid out left to right, then top to bottom.
+        let block =
+            x / SCREEN_BLOCK_PIXELS + (y / SCREEN_BLOCK_PIXELS) * (width / SCREEN_BLOCK_PIXELS);
+        let (x, y) = (x % SCREEN_BLOCK_PIXELS, y % SCREEN_BLOCK_PIXELS);
+        let entry_address =
+            self.screen_base(bg) + block * SCREEN_BLOCK_SIZE + ((y / 8) * 32 + x / 8) * 2;
+        let entry =
+            u16::from_le_bytes([self.bg_vram(entry_address), self.bg_vram(entry_address + 1)]);
+
+        let tile = (entry & 0x3FF) as usize;
+        let column = if entry & (1 << 10) > 0 {
+            7 - x % 8
+        } else {
+            x % 8
+        };
+        let row = if entry & (1 << 11) > 0 {
+            7 - y % 8
+        } else {
+            y % 8
+        };
+        let full_palette = control & (1 << 7) > 0;
+        match self.tile_pixel(self.character_base(bg), tile, full_palette, column, row) {
+            0 => None,
+            index if full_palette => Some(self.palette_color(index)),
+            index => Some(self.palette_color((entry >> 12) as usize * 16 + index)),
+        }
+    }
+
+    /// The color of a rotated and scaled background at a screen position, or None where it is
+    /// transparent.
+    fn affine_pixel(&self, bg: usize, x: usize) -> Option<u16> {
+        let affine = &self.affine[bg - 2];
+        let size: i32 = 128 << self.bg_size(bg);
+        let [pa, _, pc, _] = affine.parameters;
+        let mut texture_x = (affine.current[0] + pa as i32 * x as i32) >> 8;
+        let mut texture_y = (affine.current[1] + pc as i32 * x as i32) >> 8;
+        if self.bg_control[bg] & (1 << 13) > 0 {
+            texture_x &= size - 1;
+            texture_y &= size - 1;
+        } else if !(0..size).contains(&texture_x) || !(0..size).contains(&texture_y) {
+            return None;
+        }
+
+        let (texture_x, texture_y, size) = (texture_x as usize, texture_y as usize, size as usize);
+        let tile =
+            self.bg_vram(self.screen_base(bg) + (texture_y / 8) * (size / 8) + texture_x / 8);
+        match self.tile_pixel(
+            self.character_base(bg),
+            tile as usize,
+            true,
+            texture_x % 8,
+            texture_y % 8,
+        ) {
+            0 => None,
+            index => Some(self.palette_color(index)),
+        }
+    }
+
+    /// The palette index of a pixel in a background tile. For 16 color tiles it is within the
+    /// tile's palette bank.
+    fn tile_pixel(
+        &self,
+        character_base: usize,
+        tile: usize,
+        full_palette: bool,
+        column: usize,
+        row: usize,
+    ) -> usize {
+        if full_palette {
+            self.bg_vram(character_base + tile * 64 + row * 8 + column) as usize
+        } else {
+            let data = self.bg_vram(character_base + tile * 32 + row * 4 + column / 2);
+            if column & 1 > 0 {
+                data >> 4
+            } else {
+                data & 0xF
+            }
+            .into()
+        }
+    }
+
+    /// Backgrounds can only use the first 64KiB of VRAM. Past it they read 0.
+    fn bg_vram(&self, address: usize) -> u8 {
+        if address < OBJ_VRAM_START {
+            self.vram[address]
+        } else {
+            0
+        }
+    }
+}
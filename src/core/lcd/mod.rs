@@ -1,12 +1,400 @@
-use super::Addressable;
+use serde::{Deserialize, Serialize};
 
-#[derive(Default)]
-pub struct Lcd {}
+use super::{Addressable, CoreError, InterruptKind};
+
+mod backgrounds;
+use backgrounds::AffineParameters;
+
+mod objects;
+
+mod reference;
+pub use reference::*;
+
+pub const SCREEN_WIDTH: usize = 240;
+pub const SCREEN_HEIGHT: usize = VISIBLE_SCANLINES as usize;
+pub const SCANLINE_CYCLES: usize = 1232;
+/// Cycles spent drawing a scanline before HBlank starts.
+pub const HDRAW_CYCLES: usize = 960;
+pub const VISIBLE_SCANLINES: u16 = 160;
+pub const TOTAL_SCANLINES: u16 = 228;
+pub const FRAME_CYCLES: usize = SCANLINE_CYCLES * TOTAL_SCANLINES as usize;
+
+pub const PALETTE_START: u32 = 0x5000000;
+pub const PALETTE_END: u32 = 0x5FFFFFF;
+pub const VRAM_START: u32 = 0x6000000;
+pub const VRAM_END: u32 = 0x6FFFFFF;
+pub const OAM_START: u32 = 0x7000000;
+pub const OAM_END: u32 = 0x7FFFFFF;
+
+const PALETTE_SIZE: usize = 0x400;
+const VRAM_SIZE: usize = 0x18000;
+const OAM_SIZE: usize = 0x400;
+/// Mode 4 draws from the second page when DISPCNT selects frame 1.
+const BITMAP_PAGE_SIZE: usize = 0xA000;
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Lcd {
+    scanline_cycles: usize,
+    vcount: u16,
+    display_control: u16,
+    /// The writable bits of DISPSTAT. The status flags are worked out from the timing on read.
+    display_status: u16,
+    bg_control: [u16; 4],
+    /// BGxHOFS and BGxVOFS for each background in turn.
+    bg_offsets: [u16; 8],
+    /// The rotation and scaling registers of BG2 and BG3.
+    affine: [AffineParameters; 2],
+    palette: Vec<u8>,
+    vram: Vec<u8>,
+    oam: Vec<u8>,
+    /// Rendered output as 0x00RRGGBB. It is rebuilt every frame so there is no point saving it.
+    #[serde(skip, default = "blank_framebuffer")]
+    framebuffer: Vec<u32>,
+    /// Interrupts raised since they were last collected, as IF bits.
+    interrupt_requests: u16,
+    /// Whether a visible scanline entered HBlank since this was last collected.
+    hblank_started: bool,
+}
+
+fn blank_framebuffer() -> Vec<u32> {
+    vec![0; SCREEN_WIDTH * SCREEN_HEIGHT]
+}
+
+impl Default for Lcd {
+    fn default() -> Self {
+        Self {
+            scanline_cycles: 0,
+            vcount: 0,
+            display_control: 0,
+            display_status: 0,
+            bg_control: [0; 4],
+            bg_offsets: [0; 8],
+            affine: Default::default(),
+            palette: vec![0; PALETTE_SIZE],
+            vram: vec![0; VRAM_SIZE],
+            oam: vec![0; OAM_SIZE],
+            framebuffer: blank_framebuffer(),
+            interrupt_requests: 0,
+            hblank_started: false,
+        }
+    }
+}
+
+impl Lcd {
+    /// Advances the LCD by the given number of cycles. Visible scanlines are rendered as they
+    /// finish. Returns true if VBlank started.
+    pub fn step(&mut self, cycles: usize) -> bool {
+        let mut vblank_started = false;
+
+        let mut previous_cycles = self.scanline_cycles;
+        self.scanline_cycles += cycles;
+        loop {
+            if previous_cycles < HDRAW_CYCLES
+                && self.scanline_cycles >= HDRAW_CYCLES
+                && self.vcount < VISIBLE_SCANLINES
+            {
+                self.hblank_started = true;
+            }
+            if self.scanline_cycles < SCANLINE_CYCLES {
+                break;
+            }
+            self.scanline_cycles -= SCANLINE_CYCLES;
+            previous_cycles = 0;
+            if self.vcount < VISIBLE_SCANLINES {
+                self.render_scanline(self.vcount as usize);
+                self.affine
+                    .iter_mut()
+                    .for_each(AffineParameters::next_scanline);
+            }
+            self.vcount = (self.vcount + 1) % TOTAL_SCANLINES;
+            if self.vcount == VISIBLE_SCANLINES {
+                vblank_started = true;
+                self.affine.iter_mut().for_each(AffineParameters::reload);
+                self.request_interrupt(DISPSTAT_VBLANK_IRQ, InterruptKind::VBlank);
+            }
+            if self.vcount == self.display_status >> 8 {
+                self.request_interrupt(DISPSTAT_VCOUNT_IRQ, InterruptKind::VCount);
+            }
+        }
+
+        vblank_started
+    }
+
+    /// Returns the interrupts the LCD has raised since the last call.
+    pub fn take_interrupt_requests(&mut self) -> u16 {
+        std::mem::take(&mut self.interrupt_requests)
+    }
+
+    /// Returns whether a visible scanline entered HBlank since the last call. HBlank DMA
+    /// transfers are started by this.
+    pub fn take_hblank_started(&mut self) -> bool {
+        std::mem::take(&mut self.hblank_started)
+    }
+
+    fn request_interrupt(&mut self, enable_bit: u16, kind: InterruptKind) {
+        if self.display_status & enable_bit > 0 {
+            self.interrupt_requests |= kind.mask();
+        }
+    }
+
+    pub fn vcount(&self) -> u16 {
+        self.vcount
+    }
+
+    pub fn scanline_cycles(&self) -> usize {
+        self.scanline_cycles
+    }
+
+    pub fn is_vblank(&self) -> bool {
+        self.vcount >= VISIBLE_SCANLINES
+    }
+
+    pub fn is_hblank(&self) -> bool {
+        self.scanline_cycles >= HDRAW_CYCLES
+    }
+
+    /// DISPSTAT with the VBlank, HBlank and VCOUNT match flags filled in.
+    pub fn display_status(&self) -> u16 {
+        let vcount_match = self.vcount == self.display_status >> 8;
+        (self.display_status & DISPSTAT_WRITABLE)
+            | self.is_vblank() as u16
+            | (self.is_hblank() as u16) << 1
+            | (vcount_match as u16) << 2
+    }
+
+    pub fn framebuffer(&self) -> &[u32] {
+        &self.framebuffer
+    }
+
+    /// The framebuffer as RGBA bytes, four per pixel, which is the layout image widgets expect.
+    pub fn framebuffer_rgba(&self) -> Vec<u8> {
+        self.framebuffer
+            .iter()
+            .flat_map(|pixel| {
+                let [blue, green, red, _] = pixel.to_le_bytes();
+                [red, green, blue, 0xFF]
+            })
+            .collect()
+    }
+
+    fn bg_mode(&self) -> u16 {
+        self.display_control & 0b111
+    }
+
+    fn forced_blank(&self) -> bool {
+        self.display_control & (1 << 7) > 0
+    }
+
+    fn bg2_enabled(&self) -> bool {
+        self.display_control & (1 << 10) > 0
+    }
+
+    fn objects_enabled(&self) -> bool {
+        self.display_control & (1 << 12) > 0
+    }
+
+    fn bg_priority(&self, bg: usize) -> u8 {
+        (self.bg_control[bg] & 0b11) as u8
+    }
+
+    fn obj_vram_start(&self) -> usize {
+        if self.bg_mode() >= 3 {
+            BITMAP_OBJ_VRAM_START
+        } else {
+            OBJ_VRAM_START
+        }
+    }
+
+    fn bitmap_page(&self) -> usize {
+        if self.display_control & (1 << 4) > 0 {
+            BITMAP_PAGE_SIZE
+        } else {
+            0
+        }
+    }
+
+    /// Composes the layers of a scanline. Palette index 0 is transparent in every layer, letting
+    /// lower layers show through down to the backdrop, which is the only place palette entry 0 is
+    /// drawn as a color.
+    fn render_scanline(&mut self, line: usize) {
+        let start = line * SCREEN_WIDTH;
+        if self.forced_blank() {
+            self.framebuffer[start..start + SCREEN_WIDTH].fill(bgr555_to_rgb(0x7FFF));
+            return;
+        }
+
+        let backdrop = self.palette_color(0);
+        let objects = if self.objects_enabled() {
+            self.render_objects(line)
+        } else {
+            [None; SCREEN_WIDTH]
+        };
+        let backgrounds = if self.bg_mode() < 3 {
+            self.render_backgrounds(line)
+        } else {
+            let bg2_priority = self.bg_priority(2);
+            std::array::from_fn(|x| {
+                self.bitmap_pixel(start + x)
+                    .map(|color| (color, bg2_priority))
+            })
+        };
+        for (x, (object, background)) in objects.iter().zip(backgrounds).enumerate() {
+            let color = match (*object, background) {
+                (Some((color, priority)), Some((_, bg_priority))) if priority <= bg_priority => {
+                    color
+                }
+                (_, Some((color, _))) => color,
+                (Some((color, _)), None) => color,
+                (None, None) => backdrop,
+            };
+            self.framebuffer[start + x] = bgr555_to_rgb(color);
+        }
+    }
+
+    /// The color of BG2 at a pixel in the bitmap modes, or None where it is transparent.
+    fn bitmap_pixel(&self, pixel: usize) -> Option<u16> {
+        if !self.bg2_enabled() {
+            return None;
+        }
+        match self.bg_mode() {
+            3 => Some(u16::from_le_bytes([
+                self.vram[pixel * 2],
+                self.vram[pixel * 2 + 1],
+            ])),
+            4 => match self.vram[self.bitmap_page() + pixel] {
+                0 => None,
+                index => Some(self.palette_color(index as usize)),
+            },
+            _ => None,
+        }
+    }
+
+    fn palette_color(&self, index: usize) -> u16 {
+        u16::from_le_bytes([self.palette[index * 2], self.palette[index * 2 + 1]])
+    }
+}
+
+/// Expands a 15-bit BGR color to 0x00RRGGBB, repeating the top bits of each channel so that full
+/// intensity maps to 0xFF.
+fn bgr555_to_rgb(color: u16) -> u32 {
+    let expand = |channel: u16| {
+        let channel = (channel & 0x1F) as u32;
+        (channel << 3) | (channel >> 2)
+    };
+    (expand(color) << 16) | (expand(color >> 5) << 8) | expand(color >> 10)
+}
+
+const DISPCNT_ADDRESS: u32 = 0x4000000;
+const DISPSTAT_ADDRESS: u32 = 0x4000004;
+const DISPSTAT_WRITABLE: u16 = 0xFF38;
+const DISPSTAT_VBLANK_IRQ: u16 = 1 << 3;
+const DISPSTAT_VCOUNT_IRQ: u16 = 1 << 5;
+const VCOUNT_ADDRESS: u32 = 0x4000006;
+const BGCNT_START: u32 = 0x4000008;
+const BGCNT_END: u32 = 0x400000F;
+const BG_OFFSET_START: u32 = 0x4000010;
+const BG_OFFSET_END: u32 = 0x400001F;
+const BG_AFFINE_START: u32 = 0x4000020;
+const BG_AFFINE_END: u32 = 0x400003F;
+
+/// Where object tiles start in VRAM in the tiled and the bitmap modes.
+const OBJ_VRAM_START: usize = 0x10000;
+const BITMAP_OBJ_VRAM_START: usize = 0x14000;
+
+/// VRAM is 96KiB mirrored every 128KiB, with the last 32KiB of each mirror repeating the 32KiB
+/// before it.
+fn vram_offset(address: u32) -> usize {
+    let offset = (address & 0x1FFFF) as usize;
+    if offset >= VRAM_SIZE {
+        offset - 0x8000
+    } else {
+        offset
+    }
+}
 
 impl Addressable for Lcd {
-    fn read_byte(&mut self, _address: u32) -> u8 {
-        0
+    fn read_byte(&mut self, address: u32) -> Result<u8, CoreError> {
+        Ok(match address {
+            DISPCNT_ADDRESS => self.display_control as u8,
+            0x4000001 => (self.display_control >> 8) as u8,
+            DISPSTAT_ADDRESS => self.display_status() as u8,
+            0x4000005 => (self.display_status() >> 8) as u8,
+            VCOUNT_ADDRESS => self.vcount as u8,
+            BGCNT_START..=BGCNT_END => {
+                let offset = address - BGCNT_START;
+                (self.bg_control[offset as usize / 2] >> ((offset & 1) * 8)) as u8
+            }
+            PALETTE_START..=PALETTE_END => self.palette[address as usize & (PALETTE_SIZE - 1)],
+            VRAM_START..=VRAM_END => self.vram[vram_offset(address)],
+            OAM_START..=OAM_END => self.oam[address as usize & (OAM_SIZE - 1)],
+            _ => 0,
+        })
+    }
+
+    fn write_byte(&mut self, address: u32, data: u8) {
+        match address {
+            DISPCNT_ADDRESS => {
+                self.display_control = (self.display_control & 0xFF00) | data as u16;
+            }
+            0x4000001 => {
+                self.display_control = (self.display_control & 0x00FF) | ((data as u16) << 8);
+            }
+            DISPSTAT_ADDRESS => {
+                self.display_status =
+                    (self.display_status & 0xFF00) | (data as u16 & DISPSTAT_WRITABLE);
+            }
+            0x4000005 => {
+                self.display_status = (self.display_status & 0x00FF) | ((data as u16) << 8);
+            }
+            BGCNT_START..=BGCNT_END => {
+                let offset = address - BGCNT_START;
+                let shift = (offset & 1) * 8;
+                let control = &mut self.bg_control[offset as usize / 2];
+                *control = (*control & !(0xFF << shift)) | ((data as u16) << shift);
+            }
+            // The offsets are 9 bits wide.
+            BG_OFFSET_START..=BG_OFFSET_END => {
+                let offset = address - BG_OFFSET_START;
+                let shift = (offset & 1) * 8;
+                let scroll = &mut self.bg_offsets[offset as usize / 2];
+                *scroll = ((*scroll & !(0xFF << shift)) | ((data as u16) << shift)) & 0x1FF;
+            }
+            BG_AFFINE_START..=BG_AFFINE_END => {
+                let offset = (address - BG_AFFINE_START) as usize;
+                self.affine[offset / 0x10].write_byte(offset % 0x10, data);
+            }
+            // Video memory is only 16 bits wide. Byte writes to palette RAM and background VRAM
+            // store the byte to both halves of the halfword, while object VRAM and OAM ignore
+            // them.
+            PALETTE_START..=PALETTE_END => {
+                let offset = address as usize & (PALETTE_SIZE - 2);
+                self.palette[offset..offset + 2].fill(data);
+            }
+            VRAM_START..=VRAM_END => {
+                let offset = vram_offset(address) & !1;
+                if offset < self.obj_vram_start() {
+                    self.vram[offset..offset + 2].fill(data);
+                }
+            }
+            _ => {}
+        }
     }
 
-    fn write_byte(&mut self, _address: u32, _data: u8) {}
+    fn write_word(&mut self, address: u32, data: u16) {
+        let [low, high] = data.to_le_bytes();
+        let (memory, offset) = match address {
+            PALETTE_START..=PALETTE_END => {
+                (&mut self.palette, address as usize & (PALETTE_SIZE - 1))
+            }
+            VRAM_START..=VRAM_END => (&mut self.vram, vram_offset(address)),
+            OAM_START..=OAM_END => (&mut self.oam, address as usize & (OAM_SIZE - 1)),
+            _ => {
+                self.write_byte(address, low);
+                self.write_byte(address + 1, high);
+                return;
+            }
+        };
+        memory[offset] = low;
+        memory[offset + 1] = high;
+    }
 }
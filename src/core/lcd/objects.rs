@@ -0,0 +1,150 @@
+use super::{Lcd, SCREEN_WIDTH};
+
+/// Object tiles live in the last 32KiB of VRAM.
+const OBJECT_TILE_BASE: usize = 0x10000;
+/// In the bitmap modes the first half of the object tiles is taken by the frame buffer.
+const BITMAP_MODE_FIRST_TILE: usize = 512;
+const OBJECT_PALETTE_BASE: usize = 256;
+const OBJECT_COUNT: usize = 128;
+
+/// Width and height in pixels for each shape (square, horizontal, vertical) and size.
+const OBJECT_DIMENSIONS: [[(usize, usize); 4]; 3] = [
+    [(8, 8), (16, 16), (32, 32), (64, 64)],
+    [(16, 8), (32, 8), (32, 16), (64, 32)],
+    [(8, 16), (8, 32), (16, 32), (32, 64)],
+];
+
+/// The attributes of a regular, non-affine object.
+struct Object {
+    x: usize,
+    y: usize,
+    width: usize,
+    height: usize,
+    horizontal_flip: bool,
+    vertical_flip: bool,
+    full_palette: bool,
+    tile: usize,
+    priority: u8,
+    palette_bank: usize,
+}
+
+impl Object {
+    /// Returns None for hidden objects, affine objects, and the prohibited shape.
+    fn from_attributes(attributes: [u16; 3]) -> Option<Self> {
+        let [attribute0, attribute1, attribute2] = attributes;
+        // Affine objects are not supported yet; bit 9 hides regular ones.
+        if attribute0 & (1 << 8) > 0 || attribute0 & (1 << 9) > 0 {
+            return None;
+        }
+        let shape = (attribute0 >> 14) as usize;
+        let size = (attribute1 >> 14) as usize;
+        let (width, height) = *OBJECT_DIMENSIONS.get(shape)?.get(size)?;
+
+        Some(Self {
+            x: (attribute1 & 0x1FF) as usize,
+            y: (attribute0 & 0xFF) as usize,
+            width,
+            height,
+            horizontal_flip: attribute1 & (1 << 12) > 0,
+            vertical_flip: attribute1 & (1 << 13) > 0,
+            full_palette: attribute0 & (1 << 13) > 0,
+            tile: (attribute2 & 0x3FF) as usize,
+            priority: ((attribute2 >> 10) & 0b11) as u8,
+            palette_bank: (attribute2 >> 12) as usize,
+        })
+    }
+}
+
+impl Lcd {
+    fn one_dimensional_mapping(&self) -> bool {
+        self.display_control & (1 << 6) > 0
+    }
+
+    fn object_attributes(&self, index: usize) -> [u16; 3] {
+        let entry = &self.oam[index * 8..index * 8 + 6];
+        [
+            u16::from_le_bytes([entry[0], entry[1]]),
+            u16::from_le_bytes([entry[2], entry[3]]),
+            u16::from_le_bytes([entry[4], entry[5]]),
+        ]
+    }
+
+    /// Draws the objects covering a scanline. Each pixel holds the color and priority of the
+    /// first object in OAM order that is not transparent there.
+    pub(super) fn render_objects(&self, line: usize) -> [Option<(u16, u8)>; SCREEN_WIDTH] {
+        let mut pixels = [None; SCREEN_WIDTH];
+
+        for index in 0..OBJECT_COUNT {
+            let Some(object) = Object::from_attributes(self.object_attributes(index)) else {
+                continue;
+            };
+            if self.bg_mode() >= 3 && object.tile < BITMAP_MODE_FIRST_TILE {
+                continue;
+            }
+
+            // Objects wrap around the bottom of the 256 line space.
+            let row = line.wrapping_sub(object.y) & 0xFF;
+            if row >= object.height {
+                continue;
+            }
+            let row = if object.vertical_flip {
+                object.height - 1 - row
+            } else {
+                row
+            };
+
+            for column in 0..object.width {
+                let x = (object.x + column) & 0x1FF;
+                if x >= SCREEN_WIDTH || pixels[x].is_some() {
+                    continue;
+                }
+                let column = if object.horizontal_flip {
+                    object.width - 1 - column
+                } else {
+                    column
+                };
+
+                let index = self.object_pixel(&object, column, row);
+                if index != 0 {
+                    pixels[x] = Some((
+                        self.palette_color(OBJECT_PALETTE_BASE + index),
+                        object.priority,
+                    ));
+                }
+            }
+        }
+
+        pixels
+    }
+
+    /// Looks up the palette index of a pixel within an object, including the palette bank for
+    /// 16 color objects. Index 0 is transparent either way.
+    fn object_pixel(&self, object: &Object, column: usize, row: usize) -> usize {
+        let tile_size = if object.full_palette { 2 } else { 1 };
+        let row_stride = if self.one_dimensional_mapping() {
+            object.width / 8 * tile_size
+        } else {
+            32
+        };
+        let tile = object.tile + (row / 8) * row_stride + (column / 8) * tile_size;
+        let tile_data =
+            |offset: usize| self.vram[OBJECT_TILE_BASE + ((tile * 32 + offset) & 0x7FFF)];
+
+        let (column, row) = (column % 8, row % 8);
+        if object.full_palette {
+            tile_data(row * 8 + column) as usize
+        } else {
+            let data = tile_data(row * 4 + column / 2);
+            let index = if column & 1 > 0 {
+                data >> 4
+            } else {
+                data & 0xF
+            } as usize;
+            if index == 0 {
+                0
+            } else {
+                object.palette_bank * 16 + index
+            }
+        }
+    }
+}
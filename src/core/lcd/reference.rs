@@ -0,0 +1,100 @@
+use anyhow::{anyhow, Result};
+use std::{fs, path::Path};
+
+use super::{SCREEN_HEIGHT, SCREEN_WIDTH};
+
+const FRAME_PIXELS: usize = SCREEN_WIDTH * SCREEN_HEIGHT;
+
+/// A frame captured from another emulator to validate rendering against. Pixels are stored as
+/// BGR555 in row-major order.
+pub struct ReferenceFrame {
+    pixels: Vec<u16>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct PixelDifference {
+    pub x: usize,
+    pub y: usize,
+    pub actual: u16,
+    pub expected: u16,
+}
+
+impl ReferenceFrame {
+    /// Loads a capture from disk. Files ending in `.png` are decoded as images, anything else is
+    /// taken to be a raw little-endian BGR555 dump.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let data = fs::read(path)
+            .map_err(|_| anyhow!("Unable to read reference frame {}", path.display()))?;
+        match path.extension() {
+            Some(extension) if extension.eq_ignore_ascii_case("png") => Self::from_png(&data),
+            _ => Self::from_bgr555(&data),
+        }
+    }
+
+    pub fn from_bgr555(data: &[u8]) -> Result<Self> {
+        if data.len() != FRAME_PIXELS * 2 {
+            return Err(anyhow!(
+                "Raw reference frames must be 0x{:X} bytes",
+                FRAME_PIXELS * 2
+            ));
+        }
+
+        Ok(Self {
+            pixels: data
+                .chunks_exact(2)
+                .map(|pixel| u16::from_le_bytes([pixel[0], pixel[1]]))
+                .collect(),
+        })
+    }
+
+    /// Decodes an 8-bit RGB or RGBA PNG, dropping the low three bits of each channel the same way
+    /// the capturing emulator expanded them.
+    pub fn from_png(data: &[u8]) -> Result<Self> {
+        let mut reader = png::Decoder::new(data).read_info()?;
+        let mut buffer = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut buffer)?;
+        if info.width as usize != SCREEN_WIDTH || info.height as usize != SCREEN_HEIGHT {
+            return Err(anyhow!(
+                "Reference frames must be {SCREEN_WIDTH}x{SCREEN_HEIGHT}"
+            ));
+        }
+        let channels = match (info.color_type, info.bit_depth) {
+            (png::ColorType::Rgb, png::BitDepth::Eight) => 3,
+            (png::ColorType::Rgba, png::BitDepth::Eight) => 4,
+            _ => return Err(anyhow!("Reference frames must be 8-bit RGB or RGBA")),
+        };
+
+        Ok(Self {
+            pixels: buffer[..info.buffer_size()]
+                .chunks_exact(channels)
+                .map(|pixel| {
+                    (pixel[0] as u16 >> 3)
+                        | ((pixel[1] as u16 >> 3) << 5)
+                        | ((pixel[2] as u16 >> 3) << 10)
+                })
+                .collect(),
+        })
+    }
+
+    pub fn pixels(&self) -> &[u16] {
+        &self.pixels
+    }
+
+    /// Returns every pixel of `frame` that does not match the reference. The unused top bit of
+    /// each pixel is ignored.
+    pub fn compare(&self, frame: &[u16]) -> Vec<PixelDifference> {
+        self.pixels
+            .iter()
+            .zip(frame)
+            .enumerate()
+            .filter(|(_, (expected, actual))| (*expected ^ *actual) & 0x7FFF != 0)
+            .map(|(index, (expected, actual))| PixelDifference {
+                x: index % SCREEN_WIDTH,
+                y: index / SCREEN_WIDTH,
+                actual: *actual & 0x7FFF,
+                expected: *expected & 0x7FFF,
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,148 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::{cell::RefCell, fs, io::ErrorKind, path::Path, rc::Rc};
+
+use crate::core::{Addressable, CoreError};
+
+use super::{
+    eeprom::{Eeprom, EEPROM_END, EEPROM_START},
+    flash::{Flash, FlashSize},
+    rom::Rom,
+    sram::Sram,
+};
+
+/// The kind of save memory a cartridge has.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BackupType {
+    #[default]
+    Sram,
+    Flash64K,
+    Flash128K,
+    Eeprom,
+}
+
+/// The cartridge save memory. SRAM and Flash are mapped at 0xE000000, while EEPROM takes the
+/// place of the ROM mirror at 0xD000000. The region a chip is not mapped to reads as 0xFF.
+#[derive(Clone, Serialize, Deserialize)]
+pub enum Backup {
+    Sram(Sram),
+    Flash(Flash),
+    Eeprom(Eeprom),
+}
+
+/// Reads a save file. A missing file reads as empty since a game that has never saved will not
+/// have one yet.
+pub fn read_save_file(path: &Path) -> Result<Vec<u8>> {
+    match fs::read(path) {
+        Ok(data) => Ok(data),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+impl Default for Backup {
+    fn default() -> Self {
+        Self::new(BackupType::default())
+    }
+}
+
+impl Backup {
+    pub fn new(backup_type: BackupType) -> Self {
+        match backup_type {
+            BackupType::Sram => Backup::Sram(Sram::default()),
+            BackupType::Flash64K => Backup::Flash(Flash::new(FlashSize::Kilobytes64)),
+            BackupType::Flash128K => Backup::Flash(Flash::new(FlashSize::Kilobytes128)),
+            BackupType::Eeprom => Backup::Eeprom(Eeprom::default()),
+        }
+    }
+
+    pub fn is_eeprom(&self) -> bool {
+        matches!(self, Backup::Eeprom(_))
+    }
+
+    /// Tells an EEPROM that a DMA transfer into it has finished, which ends the request being
+    /// sent.
+    pub fn end_transfer(&mut self) {
+        if let Backup::Eeprom(eeprom) = self {
+            eeprom.end_request();
+        }
+    }
+
+    /// Puts back the chip from a save state, commands in progress included. Its contents are
+    /// written to the save file at the next flush so the file matches the restored game.
+    pub fn restore(&mut self, state: Backup) {
+        *self = state;
+        match self {
+            Backup::Sram(sram) => sram.mark_dirty(),
+            Backup::Flash(flash) => flash.mark_dirty(),
+            Backup::Eeprom(eeprom) => eeprom.mark_dirty(),
+        }
+    }
+
+    pub fn load(&mut self, path: &Path) -> Result<()> {
+        match self {
+            Backup::Sram(sram) => sram.load(path),
+            Backup::Flash(flash) => flash.load(path),
+            Backup::Eeprom(eeprom) => eeprom.load(path),
+        }
+    }
+
+    pub fn flush(&mut self, path: &Path) -> Result<bool> {
+        match self {
+            Backup::Sram(sram) => sram.flush(path),
+            Backup::Flash(flash) => flash.flush(path),
+            Backup::Eeprom(eeprom) => eeprom.flush(path),
+        }
+    }
+}
+
+impl Addressable for Backup {
+    fn read_byte(&mut self, address: u32) -> Result<u8, CoreError> {
+        let is_eeprom_region = (EEPROM_START..=EEPROM_END).contains(&address);
+        match self {
+            Backup::Eeprom(eeprom) if is_eeprom_region => eeprom.read_byte(address),
+            Backup::Sram(sram) if !is_eeprom_region => sram.read_byte(address),
+            Backup::Flash(flash) if !is_eeprom_region => flash.read_byte(address),
+            _ => Ok(0xFF),
+        }
+    }
+
+    fn write_byte(&mut self, address: u32, data: u8) {
+        let is_eeprom_region = (EEPROM_START..=EEPROM_END).contains(&address);
+        match self {
+            Backup::Eeprom(eeprom) if is_eeprom_region => eeprom.write_byte(address, data),
+            Backup::Sram(sram) if !is_eeprom_region => sram.write_byte(address, data),
+            Backup::Flash(flash) if !is_eeprom_region => flash.write_byte(address, data),
+            _ => {}
+        }
+    }
+}
+
+/// The last 16MiB of the ROM mirrors. Carts with an EEPROM answer there instead of the ROM, so
+/// which one is seen depends on the kind of save memory.
+pub struct EepromRegion {
+    rom: Rc<RefCell<Rom>>,
+    backup: Rc<RefCell<Backup>>,
+}
+
+impl EepromRegion {
+    pub fn new(rom: Rc<RefCell<Rom>>, backup: Rc<RefCell<Backup>>) -> Self {
+        Self { rom, backup }
+    }
+}
+
+impl Addressable for EepromRegion {
+    fn read_byte(&mut self, address: u32) -> Result<u8, CoreError> {
+        if self.backup.borrow().is_eeprom() {
+            self.backup.borrow_mut().read_byte(address)
+        } else {
+            self.rom.borrow_mut().read_byte(address)
+        }
+    }
+
+    fn write_byte(&mut self, address: u32, data: u8) {
+        if self.backup.borrow().is_eeprom() {
+            self.backup.borrow_mut().write_byte(address, data);
+        }
+    }
+}
@@ -0,0 +1,266 @@
+use serde::{Deserialize, Serialize};
+
+use crate::core::{Addressable, Bus, CoreError, InterruptKind};
+
+pub const DMA_REGISTERS_START: u32 = 0x40000B0;
+pub const DMA_REGISTERS_END: u32 = 0x40000DF;
+const CHANNEL_COUNT: usize = 4;
+/// SAD, DAD, CNT_L and CNT_H for each channel.
+const CHANNEL_REGISTERS_SIZE: u32 = 12;
+
+const DMA_INTERRUPTS: [InterruptKind; CHANNEL_COUNT] = [
+    InterruptKind::Dma0,
+    InterruptKind::Dma1,
+    InterruptKind::Dma2,
+    InterruptKind::Dma3,
+];
+
+const CONTROL_REPEAT: u16 = 1 << 9;
+const CONTROL_WORD: u16 = 1 << 10;
+const CONTROL_IRQ: u16 = 1 << 14;
+const CONTROL_ENABLE: u16 = 1 << 15;
+
+/// When an enabled channel starts its transfer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DmaTiming {
+    Immediate,
+    VBlank,
+    HBlank,
+    /// Sound FIFO for channels 1 and 2, video capture for channel 3. Neither is emulated yet.
+    Special,
+}
+
+/// How an address moves after each unit is copied.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AddressControl {
+    Increment,
+    Decrement,
+    Fixed,
+    /// Increments during the transfer and goes back to DAD when a repeat starts. Only valid for
+    /// the destination.
+    IncrementReload,
+}
+
+impl AddressControl {
+    fn from_bits(bits: u16) -> Self {
+        match bits & 0b11 {
+            0 => AddressControl::Increment,
+            1 => AddressControl::Decrement,
+            2 => AddressControl::Fixed,
+            _ => AddressControl::IncrementReload,
+        }
+    }
+
+    fn step(self, unit_size: u32) -> u32 {
+        match self {
+            AddressControl::Increment | AddressControl::IncrementReload => unit_size,
+            AddressControl::Decrement => unit_size.wrapping_neg(),
+            AddressControl::Fixed => 0,
+        }
+    }
+}
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+struct DmaChannel {
+    source: u32,
+    destination: u32,
+    count: u16,
+    control: u16,
+    /// The addresses the next transfer continues from. They are latched from SAD and DAD when
+    /// the channel is enabled and carry on from where the last transfer ended on repeats.
+    internal_source: u32,
+    internal_destination: u32,
+    pending: bool,
+}
+
+impl DmaChannel {
+    fn enabled(&self) -> bool {
+        self.control & CONTROL_ENABLE > 0
+    }
+
+    fn timing(&self) -> DmaTiming {
+        match (self.control >> 12) & 0b11 {
+            0 => DmaTiming::Immediate,
+            1 => DmaTiming::VBlank,
+            2 => DmaTiming::HBlank,
+            _ => DmaTiming::Special,
+        }
+    }
+
+    fn destination_control(&self) -> AddressControl {
+        AddressControl::from_bits(self.control >> 5)
+    }
+
+    fn source_control(&self) -> AddressControl {
+        AddressControl::from_bits(self.control >> 7)
+    }
+
+    fn write_control(&mut self, control: u16) {
+        let was_enabled = self.enabled();
+        self.control = control;
+        if !was_enabled && self.enabled() {
+            self.internal_source = self.source;
+            self.internal_destination = self.destination;
+            self.pending = self.timing() == DmaTiming::Immediate;
+        } else if !self.enabled() {
+            self.pending = false;
+        }
+    }
+}
+
+/// The four DMA channels. Channels only record that a transfer is due; the transfers themselves
+/// are carried out over the bus by the system, which takes them from `next_transfer`.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct DmaController {
+    channels: [DmaChannel; CHANNEL_COUNT],
+}
+
+/// A block copy taken from a channel, ready to run against the bus.
+#[derive(Debug, PartialEq)]
+pub struct DmaTransfer {
+    pub channel: usize,
+    pub source: u32,
+    pub destination: u32,
+    /// Number of units to copy.
+    pub count: u32,
+    pub source_control: AddressControl,
+    pub destination_control: AddressControl,
+    /// Whether units are 32-bit words rather than halfwords.
+    pub word: bool,
+}
+
+impl DmaTransfer {
+    /// Copies every unit and returns the source and destination addresses following the last
+    /// one.
+    pub fn run(&self, bus: &mut Bus) -> Result<(u32, u32), CoreError> {
+        let unit_size = if self.word { 4 } else { 2 };
+        let source_step = self.source_control.step(unit_size);
+        let destination_step = self.destination_control.step(unit_size);
+
+        let (mut source, mut destination) = (self.source, self.destination);
+        for _ in 0..self.count {
+            if self.word {
+                let data = bus.read_dword(source & !0b11)?;
+                bus.write_dword(destination & !0b11, data)?;
+            } else {
+                let data = bus.read_word(source & !0b1)?;
+                bus.write_word(destination & !0b1, data)?;
+            }
+            source = source.wrapping_add(source_step);
+            destination = destination.wrapping_add(destination_step);
+        }
+
+        Ok((source, destination))
+    }
+}
+
+impl DmaController {
+    /// Marks every enabled channel waiting on `timing` as due.
+    pub fn trigger(&mut self, timing: DmaTiming) {
+        for channel in &mut self.channels {
+            if channel.enabled() && channel.timing() == timing {
+                channel.pending = true;
+            }
+        }
+    }
+
+    /// Takes the highest priority transfer that is due, which is the lowest numbered channel.
+    pub fn next_transfer(&mut self) -> Option<DmaTransfer> {
+        let (index, channel) = self
+            .channels
+            .iter_mut()
+            .enumerate()
+            .find(|(_, channel)| channel.pending)?;
+        channel.pending = false;
+
+        // Channels 0-2 only have a 14-bit count. A count of zero means the largest transfer the
+        // channel can do.
+        let (mask, largest) = if index == 3 {
+            (0xFFFF, 0x10000)
+        } else {
+            (0x3FFF, 0x4000)
+        };
+        let count = match channel.count & mask {
+            0 => largest,
+            count => count as u32,
+        };
+
+        Some(DmaTransfer {
+            channel: index,
+            source: channel.internal_source,
+            destination: channel.internal_destination,
+            count,
+            source_control: channel.source_control(),
+            destination_control: channel.destination_control(),
+            word: channel.control & CONTROL_WORD > 0,
+        })
+    }
+
+    /// Records where a transfer taken from `next_transfer` finished. Channels that do not repeat
+    /// are disabled. Returns the interrupt to request, if the channel has them enabled.
+    pub fn complete(&mut self, index: usize, source: u32, destination: u32) -> u16 {
+        let channel = &mut self.channels[index];
+        channel.internal_source = source;
+        channel.internal_destination =
+            if channel.destination_control() == AddressControl::IncrementReload {
+                channel.destination
+            } else {
+                destination
+            };
+
+        if channel.control & CONTROL_REPEAT == 0 || channel.timing() == DmaTiming::Immediate {
+            channel.control &= !CONTROL_ENABLE;
+        }
+
+        if channel.control & CONTROL_IRQ > 0 {
+            DMA_INTERRUPTS[index].mask()
+        } else {
+            0
+        }
+    }
+}
+
+/// Replaces the byte at `offset` in a little-endian value.
+fn set_byte(value: u32, offset: u32, data: u8) -> u32 {
+    let shift = offset * 8;
+    (value & !(0xFF << shift)) | ((data as u32) << shift)
+}
+
+impl Addressable for DmaController {
+    /// Only CNT_H can be read back; the addresses and count are write only.
+    fn read_byte(&mut self, address: u32) -> Result<u8, CoreError> {
+        let offset = address - DMA_REGISTERS_START;
+        let channel = &self.channels[(offset / CHANNEL_REGISTERS_SIZE) as usize];
+        Ok(match offset % CHANNEL_REGISTERS_SIZE {
+            10 => channel.control as u8,
+            11 => (channel.control >> 8) as u8,
+            _ => 0,
+        })
+    }
+
+    fn write_byte(&mut self, address: u32, data: u8) {
+        let offset = address - DMA_REGISTERS_START;
+        let index = (offset / CHANNEL_REGISTERS_SIZE) as usize;
+        let channel = &mut self.channels[index];
+        // Channel 0 can only read internal memory and only channel 3 can write to the cartridge.
+        let source_mask = if index == 0 { 0x7FFFFFF } else { 0xFFFFFFF };
+        let destination_mask = if index == 3 { 0xFFFFFFF } else { 0x7FFFFFF };
+
+        match offset % CHANNEL_REGISTERS_SIZE {
+            register @ 0..=3 => {
+                channel.source = set_byte(channel.source, register, data) & source_mask;
+            }
+            register @ 4..=7 => {
+                channel.destination =
+                    set_byte(channel.destination, register - 4, data) & destination_mask;
+            }
+            register @ 8..=9 => {
+                channel.count = set_byte(channel.count as u32, register - 8, data) as u16;
+            }
+            register => {
+                let control = set_byte(channel.control as u32, register - 10, data) as u16;
+                channel.write_control(control);
+            }
+        }
+    }
+}
@@ -0,0 +1,138 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::{collections::VecDeque, fs, path::Path};
+
+use crate::core::{Addressable, CoreError};
+
+use super::backup::read_save_file;
+
+pub const EEPROM_START: u32 = 0xD000000;
+pub const EEPROM_END: u32 = 0xDFFFFFF;
+const LARGE_SIZE: usize = 0x2000;
+const SMALL_SIZE: usize = 0x200;
+const BLOCK_SIZE: usize = 8;
+const BLOCK_BITS: usize = BLOCK_SIZE * 8;
+/// A read answers with four bits of padding before the data.
+const READ_PADDING_BITS: usize = 4;
+
+/// Serial EEPROM save memory. Requests are sent one bit at a time in bit 0 of halfwords written
+/// to the EEPROM region, normally by DMA3, and read answers come back the same way. A request is
+/// two command bits (0b11 to read, 0b10 to write), the block address, then for writes 64 bits of
+/// data, and a final 0 bit. The 512 byte chip takes 6 address bits and the 8KiB chip 14, so the
+/// size is worked out from the length of the first request.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Eeprom {
+    container: Vec<u8>,
+    address_bits: Option<usize>,
+    /// Bits received since the last request ended.
+    request: Vec<bool>,
+    /// Bits still to be read back for the last read request.
+    response: VecDeque<bool>,
+    #[serde(skip)]
+    dirty: bool,
+}
+
+impl Default for Eeprom {
+    fn default() -> Self {
+        Self {
+            container: vec![0xFF; LARGE_SIZE],
+            address_bits: None,
+            request: Vec::new(),
+            response: VecDeque::new(),
+            dirty: false,
+        }
+    }
+}
+
+impl Eeprom {
+    pub fn mark_dirty(&mut self) {
+        self.dirty = true;
+    }
+
+    /// Fills the memory from a save file. A 512 byte file fixes the chip size as the small one.
+    pub fn load(&mut self, path: &Path) -> Result<()> {
+        let data = read_save_file(path)?;
+        if data.len() == SMALL_SIZE {
+            self.address_bits = Some(6);
+        }
+        let length = data.len().min(self.container.len());
+        self.container[..length].copy_from_slice(&data[..length]);
+        self.dirty = false;
+        Ok(())
+    }
+
+    /// Writes the memory out to a save file if it was modified since the last flush. Returns
+    /// whether anything was written.
+    pub fn flush(&mut self, path: &Path) -> Result<bool> {
+        if !self.dirty {
+            return Ok(false);
+        }
+        fs::write(path, &self.container[..self.size()])?;
+        self.dirty = false;
+        Ok(true)
+    }
+
+    fn size(&self) -> usize {
+        match self.address_bits {
+            Some(6) => SMALL_SIZE,
+            _ => LARGE_SIZE,
+        }
+    }
+
+    /// Carries out the request made of the bits written so far. Nothing marks the end of a
+    /// request on the bus, so this is called when the DMA transfer sending it finishes.
+    pub fn end_request(&mut self) {
+        let bits = std::mem::take(&mut self.request);
+        let (read, address_bits) = match bits.len() {
+            9 => (true, 6),
+            17 => (true, 14),
+            73 => (false, 6),
+            81 => (false, 14),
+            length => {
+                println!("Warning: Ignoring EEPROM request of {length} bits");
+                return;
+            }
+        };
+        if !bits[0] || bits[1] != read {
+            println!("Warning: Ignoring malformed EEPROM request");
+            return;
+        }
+        self.address_bits = Some(address_bits);
+
+        let block = bits[2..2 + address_bits]
+            .iter()
+            .fold(0, |address, bit| (address << 1) | *bit as usize);
+        let start = (block * BLOCK_SIZE) & (self.size() - 1);
+        let data = &mut self.container[start..start + BLOCK_SIZE];
+
+        if read {
+            self.response = (0..READ_PADDING_BITS)
+                .map(|_| false)
+                .chain((0..BLOCK_BITS).map(|bit| data[bit / 8] & (0x80 >> (bit % 8)) > 0))
+                .collect();
+        } else {
+            let data_bits = &bits[2 + address_bits..2 + address_bits + BLOCK_BITS];
+            for (byte, bits) in data.iter_mut().zip(data_bits.chunks_exact(8)) {
+                *byte = bits.iter().fold(0, |byte, bit| (byte << 1) | *bit as u8);
+            }
+            self.dirty = true;
+        }
+    }
+}
+
+impl Addressable for Eeprom {
+    /// Returns the next bit of a read answer. Once there is nothing left to send the chip
+    /// reports that it is ready with a 1.
+    fn read_byte(&mut self, address: u32) -> Result<u8, CoreError> {
+        if address & 1 > 0 {
+            return Ok(0);
+        }
+        Ok(self.response.pop_front().unwrap_or(true) as u8)
+    }
+
+    fn write_byte(&mut self, address: u32, data: u8) {
+        if address & 1 == 0 {
+            self.request.push(data & 1 > 0);
+        }
+    }
+}
@@ -0,0 +1,175 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::{fs, path::Path};
+
+use crate::core::{Addressable, CoreError};
+
+use super::backup::read_save_file;
+
+const BANK_SIZE: usize = 0x10000;
+const SECTOR_SIZE: usize = 0x1000;
+const COMMAND_ADDRESS: usize = 0x5555;
+const UNLOCK_ADDRESS: usize = 0x2AAA;
+
+/// (manufacturer, device) as reported in ID mode. Games use these to pick the size and command
+/// set, so they are those of the Panasonic 64K and Sanyo 128K chips, which both use the plain
+/// byte program command.
+const ID_64K: (u8, u8) = (0x32, 0x1B);
+const ID_128K: (u8, u8) = (0x62, 0x13);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FlashSize {
+    Kilobytes64,
+    Kilobytes128,
+}
+
+/// A command that is waiting on a further write to complete.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+enum FlashCommand {
+    /// Waiting for a second unlock sequence then chip or sector erase.
+    Erase,
+    /// The next write stores a byte.
+    Program,
+    /// The next write to 0x0000 selects the 64KiB bank.
+    BankSwitch,
+}
+
+/// Flash backup memory. Commands are written as bytes to 0x5555 after the unlock sequence of
+/// 0xAA to 0x5555 and 0x55 to 0x2AAA. The 128K chip is accessed as two 64KiB banks.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Flash {
+    size: FlashSize,
+    container: Vec<u8>,
+    bank: usize,
+    /// How far through the unlock sequence the writes so far have got.
+    unlock_step: u8,
+    command: Option<FlashCommand>,
+    id_mode: bool,
+    /// Set by programs and erases that have not reached the save file yet.
+    #[serde(skip)]
+    dirty: bool,
+}
+
+impl Flash {
+    pub fn mark_dirty(&mut self) {
+        self.dirty = true;
+    }
+
+    pub fn new(size: FlashSize) -> Self {
+        let banks = match size {
+            FlashSize::Kilobytes64 => 1,
+            FlashSize::Kilobytes128 => 2,
+        };
+        Self {
+            size,
+            container: vec![0xFF; BANK_SIZE * banks],
+            bank: 0,
+            unlock_step: 0,
+            command: None,
+            id_mode: false,
+            dirty: false,
+        }
+    }
+
+    /// Fills the memory from a save file.
+    pub fn load(&mut self, path: &Path) -> Result<()> {
+        let data = read_save_file(path)?;
+        let length = data.len().min(self.container.len());
+        self.container[..length].copy_from_slice(&data[..length]);
+        self.dirty = false;
+        Ok(())
+    }
+
+    /// Writes the memory out to a save file if it was modified since the last flush. Returns
+    /// whether anything was written.
+    pub fn flush(&mut self, path: &Path) -> Result<bool> {
+        if !self.dirty {
+            return Ok(false);
+        }
+        fs::write(path, &self.container)?;
+        self.dirty = false;
+        Ok(true)
+    }
+
+    fn id(&self) -> (u8, u8) {
+        match self.size {
+            FlashSize::Kilobytes64 => ID_64K,
+            FlashSize::Kilobytes128 => ID_128K,
+        }
+    }
+
+    fn erase(&mut self, start: usize, length: usize) {
+        self.container[start..start + length].fill(0xFF);
+        self.dirty = true;
+    }
+
+    fn run_command(&mut self, offset: usize, command: u8) {
+        self.command = match (self.command, offset, command) {
+            (Some(FlashCommand::Erase), COMMAND_ADDRESS, 0x10) => {
+                let length = self.container.len();
+                self.erase(0, length);
+                None
+            }
+            (Some(FlashCommand::Erase), _, 0x30) => {
+                self.erase(
+                    self.bank * BANK_SIZE + (offset & !(SECTOR_SIZE - 1)),
+                    SECTOR_SIZE,
+                );
+                None
+            }
+            (_, COMMAND_ADDRESS, 0x90) => {
+                self.id_mode = true;
+                None
+            }
+            (_, COMMAND_ADDRESS, 0xF0) => {
+                self.id_mode = false;
+                None
+            }
+            (_, COMMAND_ADDRESS, 0x80) => Some(FlashCommand::Erase),
+            (_, COMMAND_ADDRESS, 0xA0) => Some(FlashCommand::Program),
+            (_, COMMAND_ADDRESS, 0xB0) if self.size == FlashSize::Kilobytes128 => {
+                Some(FlashCommand::BankSwitch)
+            }
+            _ => None,
+        };
+    }
+}
+
+impl Addressable for Flash {
+    fn read_byte(&mut self, address: u32) -> Result<u8, CoreError> {
+        let offset = address as usize & (BANK_SIZE - 1);
+        Ok(match (self.id_mode, offset) {
+            (true, 0) => self.id().0,
+            (true, 1) => self.id().1,
+            _ => self.container[self.bank * BANK_SIZE + offset],
+        })
+    }
+
+    fn write_byte(&mut self, address: u32, data: u8) {
+        let offset = address as usize & (BANK_SIZE - 1);
+        match self.command {
+            Some(FlashCommand::Program) => {
+                self.container[self.bank * BANK_SIZE + offset] = data;
+                self.dirty = true;
+                self.command = None;
+                return;
+            }
+            Some(FlashCommand::BankSwitch) if offset == 0 => {
+                self.bank = (data & 1) as usize;
+                self.command = None;
+                return;
+            }
+            _ => {}
+        }
+
+        self.unlock_step = match (self.unlock_step, offset, data) {
+            (0, COMMAND_ADDRESS, 0xAA) => 1,
+            (1, UNLOCK_ADDRESS, 0x55) => 2,
+            (2, _, command) => {
+                self.run_command(offset, command);
+                0
+            }
+            _ => 0,
+        };
+    }
+}
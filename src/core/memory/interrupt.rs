@@ -0,0 +1,52 @@
+use serde::{Deserialize, Serialize};
+
+use crate::core::{Addressable, CoreError};
+
+pub const INTERRUPT_REGISTERS_START: u32 = 0x4000200;
+pub const INTERRUPT_REGISTERS_END: u32 = 0x4000209;
+
+/// IE, IF and IME. Interrupt sources set bits in IF, and an IRQ is raised while IME is set and an
+/// IF bit is also set in IE.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct InterruptController {
+    pub enable: u16,
+    pub flags: u16,
+    pub master_enable: bool,
+}
+
+impl InterruptController {
+    /// Sets bits in IF. Each bit is one of the `InterruptKind` sources.
+    pub fn request(&mut self, mask: u16) {
+        self.flags |= mask;
+    }
+
+    /// Whether an enabled interrupt has been requested and IME lets it through.
+    pub fn irq_pending(&self) -> bool {
+        self.master_enable && self.enable & self.flags != 0
+    }
+}
+
+impl Addressable for InterruptController {
+    fn read_byte(&mut self, address: u32) -> Result<u8, CoreError> {
+        Ok(match address {
+            0x4000200 => self.enable as u8,
+            0x4000201 => (self.enable >> 8) as u8,
+            0x4000202 => self.flags as u8,
+            0x4000203 => (self.flags >> 8) as u8,
+            0x4000208 => self.master_enable as u8,
+            _ => 0,
+        })
+    }
+
+    fn write_byte(&mut self, address: u32, data: u8) {
+        match address {
+            0x4000200 => self.enable = (self.enable & 0xFF00) | data as u16,
+            0x4000201 => self.enable = (self.enable & 0x00FF) | ((data as u16 & 0x3F) << 8),
+            // Writing a 1 to an IF bit acknowledges the interrupt.
+            0x4000202 => self.flags &= !(data as u16),
+            0x4000203 => self.flags &= !((data as u16) << 8),
+            0x4000208 => self.master_enable = data & 1 > 0,
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,159 @@
+use serde::{Deserialize, Serialize};
+use std::{cell::RefCell, rc::Rc};
+
+use crate::core::{Addressable, Apu, CoreError, Lcd, SOUND_REGISTERS_END, SOUND_REGISTERS_START};
+
+use super::{
+    dma::{DmaController, DMA_REGISTERS_END, DMA_REGISTERS_START},
+    interrupt::{InterruptController, INTERRUPT_REGISTERS_END, INTERRUPT_REGISTERS_START},
+    io_names::describe_io_access,
+    keypad::{Keypad, KEYPAD_REGISTERS_END, KEYPAD_REGISTERS_START},
+    waitstate::WAITCNT_ADDRESS,
+};
+
+pub const IO_REGISTERS_START: u32 = 0x4000000;
+pub const IO_REGISTERS_END: u32 = 0x40003FE;
+
+/// The memory mapped I/O registers. Accesses are forwarded to whichever component owns the
+/// register being addressed.
+pub struct IoRegisters {
+    lcd: Rc<RefCell<Lcd>>,
+    apu: Rc<RefCell<Apu>>,
+    dma: Rc<RefCell<DmaController>>,
+    interrupts: Rc<RefCell<InterruptController>>,
+    keypad: Rc<RefCell<Keypad>>,
+    pub system_control: SystemControl,
+    pub trace_enabled: bool,
+}
+
+/// Registers that are not owned by any other component.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct SystemControl {
+    pub post_boot: bool,
+    /// WAITCNT. The bus is told about changes to it after each instruction.
+    pub wait_control: u16,
+}
+
+impl IoRegisters {
+    pub fn new(
+        lcd: Rc<RefCell<Lcd>>,
+        apu: Rc<RefCell<Apu>>,
+        dma: Rc<RefCell<DmaController>>,
+        interrupts: Rc<RefCell<InterruptController>>,
+        keypad: Rc<RefCell<Keypad>>,
+    ) -> Self {
+        Self {
+            lcd,
+            apu,
+            dma,
+            interrupts,
+            keypad,
+            system_control: SystemControl::default(),
+            trace_enabled: false,
+        }
+    }
+
+    /// The component that owns the register at `address`, if it is not handled here.
+    fn owner(&self, address: u32) -> Option<&RefCell<dyn Addressable>> {
+        match address {
+            0x4000000..=0x4000056 => Some(&*self.lcd),
+            SOUND_REGISTERS_START..=SOUND_REGISTERS_END => Some(&*self.apu),
+            DMA_REGISTERS_START..=DMA_REGISTERS_END => Some(&*self.dma),
+            KEYPAD_REGISTERS_START..=KEYPAD_REGISTERS_END => Some(&*self.keypad),
+            // WAITCNT sits between IF and IME.
+            WAITCNT_ADDRESS..=0x4000207 => None,
+            INTERRUPT_REGISTERS_START..=INTERRUPT_REGISTERS_END => Some(&*self.interrupts),
+            _ => None,
+        }
+    }
+
+    /// Registers that are not emulated read as 0 so that games touching them can carry on.
+    fn dispatch_read(&mut self, address: u32) -> Result<u8, CoreError> {
+        if let Some(owner) = self.owner(address) {
+            return owner.borrow_mut().read_byte(address);
+        }
+        Ok(match address {
+            0x4000300 => self.system_control.post_boot as u8,
+            WAITCNT_ADDRESS => self.system_control.wait_control as u8,
+            0x4000205 => (self.system_control.wait_control >> 8) as u8,
+            0x4000206 | 0x4000207 => 0,
+            _ => {
+                println!("Warning: Unhandled read from 0x{:08X}", address);
+                0
+            }
+        })
+    }
+
+    fn dispatch_write(&mut self, address: u32, data: u8) {
+        if let Some(owner) = self.owner(address) {
+            owner.borrow_mut().write_byte(address, data);
+            return;
+        }
+        match address {
+            0x4000300 => self.system_control.post_boot = data > 0,
+            WAITCNT_ADDRESS => {
+                self.system_control.wait_control =
+                    (self.system_control.wait_control & 0xFF00) | data as u16
+            }
+            // The top bit is the read only gamepak type flag.
+            0x4000205 => {
+                self.system_control.wait_control =
+                    (self.system_control.wait_control & 0x00FF) | ((data as u16 & 0x5F) << 8)
+            }
+            0x4000206 | 0x4000207 => {}
+            _ => {
+                println!("Warning: Unhandled write from 0x{:08X}", address);
+            }
+        }
+    }
+
+    fn trace(&self, address: u32, data: &[u8], write: bool) {
+        if self.trace_enabled {
+            for (offset, byte) in data.iter().enumerate() {
+                println!(
+                    "{}",
+                    describe_io_access(address + offset as u32, *byte, write)
+                );
+            }
+        }
+    }
+}
+
+impl Addressable for IoRegisters {
+    fn read_byte(&mut self, address: u32) -> Result<u8, CoreError> {
+        let data = self.dispatch_read(address)?;
+        self.trace(address, &[data], false);
+        Ok(data)
+    }
+
+    fn write_byte(&mut self, address: u32, data: u8) {
+        self.trace(address, &[data], true);
+        self.dispatch_write(address, data);
+    }
+
+    /// Halfword accesses are passed on whole so that the owner of the register sees how wide
+    /// they were.
+    fn read_word(&mut self, address: u32) -> Result<u16, CoreError> {
+        let data = match self.owner(address) {
+            Some(owner) => owner.borrow_mut().read_word(address)?,
+            None => u16::from_le_bytes([
+                self.dispatch_read(address)?,
+                self.dispatch_read(address + 1)?,
+            ]),
+        };
+        self.trace(address, &data.to_le_bytes(), false);
+        Ok(data)
+    }
+
+    fn write_word(&mut self, address: u32, data: u16) {
+        self.trace(address, &data.to_le_bytes(), true);
+        match self.owner(address) {
+            Some(owner) => owner.borrow_mut().write_word(address, data),
+            None => {
+                let [low, high] = data.to_le_bytes();
+                self.dispatch_write(address, low);
+                self.dispatch_write(address + 1, high);
+            }
+        }
+    }
+}
@@ -0,0 +1,120 @@
+/// The documented I/O registers as (address, size in bytes, name).
+const IO_REGISTER_NAMES: [(u32, u32, &str); 95] = [
+    (0x4000000, 2, "DISPCNT"),
+    (0x4000002, 2, "GREENSWAP"),
+    (0x4000004, 2, "DISPSTAT"),
+    (0x4000006, 2, "VCOUNT"),
+    (0x4000008, 2, "BG0CNT"),
+    (0x400000A, 2, "BG1CNT"),
+    (0x400000C, 2, "BG2CNT"),
+    (0x400000E, 2, "BG3CNT"),
+    (0x4000010, 2, "BG0HOFS"),
+    (0x4000012, 2, "BG0VOFS"),
+    (0x4000014, 2, "BG1HOFS"),
+    (0x4000016, 2, "BG1VOFS"),
+    (0x4000018, 2, "BG2HOFS"),
+    (0x400001A, 2, "BG2VOFS"),
+    (0x400001C, 2, "BG3HOFS"),
+    (0x400001E, 2, "BG3VOFS"),
+    (0x4000020, 2, "BG2PA"),
+    (0x4000022, 2, "BG2PB"),
+    (0x4000024, 2, "BG2PC"),
+    (0x4000026, 2, "BG2PD"),
+    (0x4000028, 4, "BG2X"),
+    (0x400002C, 4, "BG2Y"),
+    (0x4000030, 2, "BG3PA"),
+    (0x4000032, 2, "BG3PB"),
+    (0x4000034, 2, "BG3PC"),
+    (0x4000036, 2, "BG3PD"),
+    (0x4000038, 4, "BG3X"),
+    (0x400003C, 4, "BG3Y"),
+    (0x4000040, 2, "WIN0H"),
+    (0x4000042, 2, "WIN1H"),
+    (0x4000044, 2, "WIN0V"),
+    (0x4000046, 2, "WIN1V"),
+    (0x4000048, 2, "WININ"),
+    (0x400004A, 2, "WINOUT"),
+    (0x400004C, 2, "MOSAIC"),
+    (0x4000050, 2, "BLDCNT"),
+    (0x4000052, 2, "BLDALPHA"),
+    (0x4000054, 2, "BLDY"),
+    (0x4000060, 2, "SOUND1CNT_L"),
+    (0x4000062, 2, "SOUND1CNT_H"),
+    (0x4000064, 2, "SOUND1CNT_X"),
+    (0x4000068, 2, "SOUND2CNT_L"),
+    (0x400006C, 2, "SOUND2CNT_H"),
+    (0x4000070, 2, "SOUND3CNT_L"),
+    (0x4000072, 2, "SOUND3CNT_H"),
+    (0x4000074, 2, "SOUND3CNT_X"),
+    (0x4000078, 2, "SOUND4CNT_L"),
+    (0x400007C, 2, "SOUND4CNT_H"),
+    (0x4000080, 2, "SOUNDCNT_L"),
+    (0x4000082, 2, "SOUNDCNT_H"),
+    (0x4000084, 2, "SOUNDCNT_X"),
+    (0x4000088, 2, "SOUNDBIAS"),
+    (0x4000090, 16, "WAVE_RAM"),
+    (0x40000A0, 4, "FIFO_A"),
+    (0x40000A4, 4, "FIFO_B"),
+    (0x40000B0, 4, "DMA0SAD"),
+    (0x40000B4, 4, "DMA0DAD"),
+    (0x40000B8, 2, "DMA0CNT_L"),
+    (0x40000BA, 2, "DMA0CNT_H"),
+    (0x40000BC, 4, "DMA1SAD"),
+    (0x40000C0, 4, "DMA1DAD"),
+    (0x40000C4, 2, "DMA1CNT_L"),
+    (0x40000C6, 2, "DMA1CNT_H"),
+    (0x40000C8, 4, "DMA2SAD"),
+    (0x40000CC, 4, "DMA2DAD"),
+    (0x40000D0, 2, "DMA2CNT_L"),
+    (0x40000D2, 2, "DMA2CNT_H"),
+    (0x40000D4, 4, "DMA3SAD"),
+    (0x40000D8, 4, "DMA3DAD"),
+    (0x40000DC, 2, "DMA3CNT_L"),
+    (0x40000DE, 2, "DMA3CNT_H"),
+    (0x4000100, 2, "TM0CNT_L"),
+    (0x4000102, 2, "TM0CNT_H"),
+    (0x4000104, 2, "TM1CNT_L"),
+    (0x4000106, 2, "TM1CNT_H"),
+    (0x4000108, 2, "TM2CNT_L"),
+    (0x400010A, 2, "TM2CNT_H"),
+    (0x400010C, 2, "TM3CNT_L"),
+    (0x400010E, 2, "TM3CNT_H"),
+    (0x4000120, 4, "SIODATA32"),
+    (0x4000128, 2, "SIOCNT"),
+    (0x400012A, 2, "SIODATA8"),
+    (0x4000130, 2, "KEYINPUT"),
+    (0x4000132, 2, "KEYCNT"),
+    (0x4000134, 2, "RCNT"),
+    (0x4000140, 2, "JOYCNT"),
+    (0x4000150, 4, "JOY_RECV"),
+    (0x4000154, 4, "JOY_TRANS"),
+    (0x4000158, 2, "JOYSTAT"),
+    (0x4000200, 2, "IE"),
+    (0x4000202, 2, "IF"),
+    (0x4000204, 2, "WAITCNT"),
+    (0x4000208, 2, "IME"),
+    (0x4000300, 1, "POSTFLG"),
+    (0x4000301, 1, "HALTCNT"),
+];
+
+/// Names the I/O register at `address`. Addresses past the first byte of a register get the
+/// byte offset appended, like "DISPCNT+1".
+pub fn io_register_name(address: u32) -> Option<String> {
+    IO_REGISTER_NAMES
+        .iter()
+        .find(|(start, size, _)| (*start..*start + size).contains(&address))
+        .map(|(start, _, name)| match address - start {
+            0 => name.to_string(),
+            offset => format!("{name}+{offset}"),
+        })
+}
+
+/// Describes an I/O access for the trace, using the register name when there is one.
+pub fn describe_io_access(address: u32, data: u8, write: bool) -> String {
+    format!(
+        "{} {} = 0x{:02X}",
+        if write { "write" } else { "read" },
+        io_register_name(address).unwrap_or_else(|| format!("0x{address:08X}")),
+        data
+    )
+}
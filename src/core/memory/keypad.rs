@@ -0,0 +1,87 @@
+use serde::{Deserialize, Serialize};
+
+use crate::core::{Addressable, CoreError};
+
+pub const KEYPAD_REGISTERS_START: u32 = 0x4000130;
+pub const KEYPAD_REGISTERS_END: u32 = 0x4000133;
+
+const BUTTONS_MASK: u16 = 0x3FF;
+const CONTROL_IRQ: u16 = 1 << 14;
+/// Set when every selected button must be held for the interrupt, rather than any of them.
+const CONTROL_ALL: u16 = 1 << 15;
+
+/// The buttons in the order of their bits in KEYINPUT and KEYCNT.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Button {
+    A = 0,
+    B = 1,
+    Select = 2,
+    Start = 3,
+    Right = 4,
+    Left = 5,
+    Up = 6,
+    Down = 7,
+    R = 8,
+    L = 9,
+}
+
+impl Button {
+    fn mask(self) -> u16 {
+        1 << self as u16
+    }
+}
+
+/// KEYINPUT and KEYCNT. KEYINPUT reads a 0 for each button that is held.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct Keypad {
+    /// A 1 for each held button.
+    held: u16,
+    control: u16,
+}
+
+impl Keypad {
+    pub fn set_button(&mut self, button: Button, pressed: bool) {
+        if pressed {
+            self.held |= button.mask();
+        } else {
+            self.held &= !button.mask();
+        }
+    }
+
+    fn key_input(&self) -> u16 {
+        !self.held & BUTTONS_MASK
+    }
+
+    /// Whether the held buttons meet the condition set in KEYCNT for a keypad interrupt.
+    pub fn interrupt_requested(&self) -> bool {
+        if self.control & CONTROL_IRQ == 0 {
+            return false;
+        }
+        let selected = self.control & BUTTONS_MASK;
+        if self.control & CONTROL_ALL > 0 {
+            selected != 0 && self.held & selected == selected
+        } else {
+            self.held & selected != 0
+        }
+    }
+}
+
+impl Addressable for Keypad {
+    fn read_byte(&mut self, address: u32) -> Result<u8, CoreError> {
+        Ok(match address {
+            0x4000130 => self.key_input() as u8,
+            0x4000131 => (self.key_input() >> 8) as u8,
+            0x4000132 => self.control as u8,
+            _ => (self.control >> 8) as u8,
+        })
+    }
+
+    /// KEYINPUT is read only.
+    fn write_byte(&mut self, address: u32, data: u8) {
+        match address {
+            0x4000132 => self.control = (self.control & 0xFF00) | data as u16,
+            0x4000133 => self.control = (self.control & 0x00FF) | ((data as u16 & 0xC3) << 8),
+            _ => {}
+        }
+    }
+}
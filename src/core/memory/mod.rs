@@ -1,2 +1,12 @@
-pub mod system_io;
+pub mod backup;
+pub mod dma;
+pub mod eeprom;
+pub mod flash;
+pub mod interrupt;
+pub mod io;
+pub mod io_names;
+pub mod keypad;
+pub mod rom;
+pub mod sram;
+pub mod waitstate;
 pub mod wram;
@@ -0,0 +1,80 @@
+use anyhow::{anyhow, Result};
+
+use crate::core::{Addressable, CoreError};
+
+pub const ROM_START: u32 = 0x8000000;
+/// The ROM is mirrored once for each of the three wait states, and the last mirror ends here.
+pub const ROM_END: u32 = 0xDFFFFFF;
+const MAX_SIZE: usize = 0x2000000;
+/// Where the game title sits in the cartridge header. It is up to 12 ASCII characters padded
+/// with zeros.
+const TITLE_OFFSET: usize = 0xA0;
+const TITLE_LENGTH: usize = 12;
+
+/// The cartridge ROM. Writes from the bus are ignored. Reads past the end of the ROM see the
+/// cartridge's address lines, which hold the halfword address that was last requested.
+#[derive(Default)]
+pub struct Rom {
+    container: Vec<u8>,
+}
+
+impl Rom {
+    /// Replaces the contents of the ROM with a whole cartridge image.
+    pub fn load_data(&mut self, data: &[u8]) -> Result<()> {
+        if data.len() > MAX_SIZE {
+            return Err(anyhow!(
+                "ROMs can be at most 0x{MAX_SIZE:X} bytes but this one is 0x{:X}",
+                data.len()
+            ));
+        }
+        self.container = data.to_vec();
+        Ok(())
+    }
+
+    /// Overwrites part of the ROM, growing it if needed. Only meant for loading test code.
+    pub fn patch(&mut self, address: u32, data: &[u8]) {
+        let start = Self::offset(address);
+        let end = (start + data.len()).min(MAX_SIZE);
+        if self.container.len() < end {
+            self.container.resize(end, 0);
+        }
+        self.container[start..end].copy_from_slice(&data[..end - start]);
+    }
+
+    /// The number of bytes backing the ROM. Nothing is allocated until an image is loaded.
+    pub fn size(&self) -> usize {
+        self.container.len()
+    }
+
+    /// The game title from the cartridge header, if the ROM is long enough to have one.
+    pub fn title(&self) -> Option<String> {
+        let title = self
+            .container
+            .get(TITLE_OFFSET..TITLE_OFFSET + TITLE_LENGTH)?;
+        let length = title.iter().position(|c| *c == 0).unwrap_or(TITLE_LENGTH);
+        Some(
+            String::from_utf8_lossy(&title[..length])
+                .trim_end()
+                .to_string(),
+        )
+    }
+
+    fn offset(address: u32) -> usize {
+        (address as usize) & (MAX_SIZE - 1)
+    }
+}
+
+impl Addressable for Rom {
+    fn read_byte(&mut self, address: u32) -> Result<u8, CoreError> {
+        let offset = Self::offset(address);
+        Ok(match self.container.get(offset) {
+            Some(data) => *data,
+            None => {
+                let halfword = (offset >> 1) as u16;
+                (halfword >> ((offset & 1) * 8)) as u8
+            }
+        })
+    }
+
+    fn write_byte(&mut self, _address: u32, _data: u8) {}
+}
@@ -0,0 +1,72 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::{fs, path::Path};
+
+use crate::core::{Addressable, CoreError};
+
+use super::backup::read_save_file;
+
+pub const SRAM_START: u32 = 0xE000000;
+pub const SRAM_END: u32 = 0xFFFFFFF;
+pub const SRAM_SIZE: usize = 0x10000;
+
+/// Battery backed cartridge RAM. The 64KiB of storage is mirrored across the whole region and is
+/// only ever accessed a byte at a time by real hardware.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Sram {
+    container: Vec<u8>,
+    /// Whether the save file is out of date. This is about the host file, so states leave it
+    /// out.
+    #[serde(skip)]
+    dirty: bool,
+}
+
+impl Default for Sram {
+    fn default() -> Self {
+        Self {
+            container: vec![0xFF; SRAM_SIZE],
+            dirty: false,
+        }
+    }
+}
+
+impl Sram {
+    /// Makes the next flush write the memory out even if nothing has been written to it since.
+    pub fn mark_dirty(&mut self) {
+        self.dirty = true;
+    }
+
+    /// Fills the memory from a save file.
+    pub fn load(&mut self, path: &Path) -> Result<()> {
+        let data = read_save_file(path)?;
+        let length = data.len().min(self.container.len());
+        self.container[..length].copy_from_slice(&data[..length]);
+        self.dirty = false;
+        Ok(())
+    }
+
+    /// Writes the memory out to a save file if it was modified since the last flush. Returns
+    /// whether anything was written.
+    pub fn flush(&mut self, path: &Path) -> Result<bool> {
+        if !self.dirty {
+            return Ok(false);
+        }
+        fs::write(path, &self.container)?;
+        self.dirty = false;
+        Ok(true)
+    }
+}
+
+impl Addressable for Sram {
+    fn read_byte(&mut self, address: u32) -> Result<u8, CoreError> {
+        Ok(self.container[(address as usize) & (SRAM_SIZE - 1)])
+    }
+
+    fn write_byte(&mut self, address: u32, data: u8) {
+        let address = (address as usize) & (SRAM_SIZE - 1);
+        if self.container[address] != data {
+            self.container[address] = data;
+            self.dirty = true;
+        }
+    }
+}
@@ -0,0 +1,70 @@
+pub const WAITCNT_ADDRESS: u32 = 0x4000204;
+
+/// Wait states selected by each two bit field of WAITCNT for SRAM and non-sequential gamepak
+/// accesses.
+const NON_SEQUENTIAL_WAITS: [usize; 4] = [4, 3, 2, 8];
+
+/// Extra cycles each memory region adds to an access, as set up by WAITCNT. The rest of the
+/// memory map either has no wait states or a fixed number of them.
+pub struct WaitStates {
+    control: u16,
+    sram: usize,
+    /// Non-sequential and sequential waits for the three gamepak mirrors.
+    gamepak_non_sequential: [usize; 3],
+    gamepak_sequential: [usize; 3],
+}
+
+impl Default for WaitStates {
+    fn default() -> Self {
+        Self::from_control(0)
+    }
+}
+
+impl WaitStates {
+    pub fn from_control(control: u16) -> Self {
+        let field = |shift: u16| NON_SEQUENTIAL_WAITS[((control >> shift) & 0b11) as usize];
+        let fast = |bit: u16, slow: usize| if control & (1 << bit) > 0 { 1 } else { slow };
+        Self {
+            control,
+            sram: field(0),
+            gamepak_non_sequential: [field(2), field(5), field(8)],
+            gamepak_sequential: [fast(4, 2), fast(7, 4), fast(10, 8)],
+        }
+    }
+
+    pub fn control(&self) -> u16 {
+        self.control
+    }
+
+    /// The cycles an access of `width` bytes at `address` takes on top of the one every access
+    /// needs. Accesses wider than the bus of a region are split into sequential halves.
+    pub fn wait_cycles(&self, address: u32, width: u32, sequential: bool) -> usize {
+        match address >> 24 {
+            // On-board WRAM has 2 wait states and a 16 bit bus.
+            0x02 => {
+                if width == 4 {
+                    5
+                } else {
+                    2
+                }
+            }
+            // Palette RAM and VRAM have no wait states but only a 16 bit bus.
+            0x05 | 0x06 => (width == 4) as usize,
+            0x08..=0x0D => {
+                let wait_state = ((address >> 25) - 4) as usize;
+                let first = if sequential {
+                    self.gamepak_sequential[wait_state]
+                } else {
+                    self.gamepak_non_sequential[wait_state]
+                };
+                if width == 4 {
+                    first + 1 + self.gamepak_sequential[wait_state]
+                } else {
+                    first
+                }
+            }
+            0x0E | 0x0F => self.sram,
+            _ => 0,
+        }
+    }
+}
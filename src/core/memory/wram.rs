@@ -1,4 +1,4 @@
-use crate::core::Addressable;
+use crate::core::{Addressable, CoreError};
 
 pub struct Wram {
     start_address: u32,
@@ -6,21 +6,37 @@ pub struct Wram {
 }
 
 impl Wram {
+    /// RAM of `size` bytes, mirrored every `size` bytes from `start_address` on. The size has to
+    /// be a power of two, as all RAM on the GBA is.
     pub fn new(start_address: u32, size: usize) -> Self {
+        assert!(
+            size.is_power_of_two(),
+            "RAM size {size:#X} is not a power of two"
+        );
         Self {
             start_address,
             container: vec![0; size],
         }
     }
 
+    pub fn data(&self) -> &[u8] {
+        &self.container
+    }
+
+    /// Replaces the contents of the memory. The size of the memory is not changed.
+    pub fn load_data(&mut self, data: &[u8]) {
+        let length = data.len().min(self.container.len());
+        self.container[..length].copy_from_slice(&data[..length]);
+    }
+
     fn virtual_address(&self, address: u32) -> usize {
-        ((address - self.start_address) as usize) % self.container.len()
+        (address - self.start_address) as usize & (self.container.len() - 1)
     }
 }
 
 impl Addressable for Wram {
-    fn read_byte(&mut self, address: u32) -> u8 {
-        self.container[self.virtual_address(address)]
+    fn read_byte(&mut self, address: u32) -> Result<u8, CoreError> {
+        Ok(self.container[self.virtual_address(address)])
     }
 
     fn write_byte(&mut self, address: u32, data: u8) {
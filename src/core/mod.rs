@@ -10,17 +10,52 @@ pub use bios::*;
 mod memory;
 
 mod lcd;
+pub use lcd::*;
+
+mod state;
+pub use state::*;
+
+mod interrupt;
+pub use interrupt::*;
+
+mod apu;
+pub use apu::*;
 
 use anyhow::{anyhow, Result};
-use lcd::Lcd;
-use std::{cell::RefCell, fmt, rc::Rc, time::Instant};
+use std::{
+    cell::{Ref, RefCell},
+    fmt, fs,
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+    rc::Rc,
+    time::{Duration, Instant},
+};
 
-use memory::{system_io::SystemIoFlags, wram::Wram};
+pub use memory::backup::BackupType;
+pub use memory::keypad::Button;
+use memory::{
+    backup::{Backup, EepromRegion},
+    dma::{DmaController, DmaTiming},
+    eeprom::{EEPROM_END, EEPROM_START},
+    interrupt::InterruptController,
+    io::{IoRegisters, IO_REGISTERS_END, IO_REGISTERS_START},
+    keypad::Keypad,
+    rom::{Rom, ROM_END, ROM_START},
+    sram::{SRAM_END, SRAM_START},
+    wram::Wram,
+};
+
+const POST_BOOT_FLAG_ADDRESS: u32 = 0x4000300;
+const EWRAM_START: u32 = 0x2000000;
+const EWRAM_SIZE: usize = 0x40000;
+const IWRAM_START: u32 = 0x3000000;
+const IWRAM_SIZE: usize = 0x8000;
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum CoreError {
     OpcodeNotImplemented(u32),
     InvalidRegion(u32),
+    UnalignedAccess(u32),
 }
 
 impl fmt::Display for CoreError {
@@ -32,57 +67,474 @@ impl fmt::Display for CoreError {
             CoreError::OpcodeNotImplemented(opcode) => {
                 write!(f, "Opcode not implemented: 0x{0:08X}", opcode)
             }
+            CoreError::UnalignedAccess(address) => {
+                write!(f, "Unaligned access at 0x{:08X}", address)
+            }
         }
     }
 }
 
+impl std::error::Error for CoreError {}
+
+#[derive(Debug, PartialEq)]
+pub enum StopReason {
+    CyclesElapsed,
+    FrameCompleted,
+    InstructionsCompleted,
+    /// Execution reached a breakpoint at this address. The instruction has not executed yet.
+    Breakpoint(u32),
+    /// The instruction at this address matched an opcode breakpoint. It has not executed yet.
+    OpcodeBreakpoint(u32),
+    /// An access hit a watchpoint. The instruction that made it has finished.
+    Watchpoint {
+        address: u32,
+        kind: WatchKind,
+    },
+}
+
 pub struct Gba {
     cpu: Interpreter,
     bus: Bus,
+    lcd: Rc<RefCell<Lcd>>,
+    apu: Rc<RefCell<Apu>>,
+    io: Rc<RefCell<IoRegisters>>,
+    interrupts: Rc<RefCell<InterruptController>>,
+    dma: Rc<RefCell<DmaController>>,
+    keypad: Rc<RefCell<Keypad>>,
+    ewram: Rc<RefCell<Wram>>,
+    iwram: Rc<RefCell<Wram>>,
+    rom: Rc<RefCell<Rom>>,
+    backup: Rc<RefCell<Backup>>,
+    save_file: Option<PathBuf>,
+    autosave_interval: Option<Duration>,
+    last_autosave: Instant,
+    snapshot_interval: Option<u64>,
+    /// Save states taken every `snapshot_interval` instructions, oldest first, along with the
+    /// instruction count they were taken at.
+    snapshots: Vec<(u64, SaveState)>,
 }
 
 impl Gba {
     pub fn new(bios_filename: &str) -> Result<Self> {
+        let mut gba = Self::with_bios(Bios::new(bios_filename)?);
+        gba.reset();
+        // TODO: Implement async logging.
+        gba.cpu.logging_enabled = true;
+
+        Ok(gba)
+    }
+
+    pub fn with_bios(bios: Bios) -> Self {
         let mut bus = Bus::default();
+        let lcd = Rc::new(RefCell::new(Lcd::default()));
+        let interrupts = Rc::new(RefCell::new(InterruptController::default()));
+        let dma = Rc::new(RefCell::new(DmaController::default()));
+        let keypad = Rc::new(RefCell::new(Keypad::default()));
+        let apu = Rc::new(RefCell::new(Apu::default()));
+        let io = Rc::new(RefCell::new(IoRegisters::new(
+            lcd.clone(),
+            apu.clone(),
+            dma.clone(),
+            interrupts.clone(),
+            keypad.clone(),
+        )));
+        let ewram = Rc::new(RefCell::new(Wram::new(EWRAM_START, EWRAM_SIZE)));
+        let iwram = Rc::new(RefCell::new(Wram::new(IWRAM_START, IWRAM_SIZE)));
+        let rom = Rc::new(RefCell::new(Rom::default()));
+        let backup = Rc::new(RefCell::new(Backup::default()));
 
-        let bios = Bios::new(bios_filename)?;
         bus.register_region(0..=0x3FFF, Rc::new(RefCell::new(bios)));
-        bus.register_region(0x4000000..=0x4000056, Rc::new(RefCell::new(Lcd::default())));
-        bus.register_region(
-            0x4000200..=0x4700000,
-            Rc::new(RefCell::new(SystemIoFlags::default())),
-        );
-        bus.register_region(
-            0x3000000..=0x3FFFFFF,
-            Rc::new(RefCell::new(Wram::new(0x3000000, 0x8000))),
-        );
+        bus.register_region(IO_REGISTERS_START..=IO_REGISTERS_END, io.clone());
+        bus.register_region(EWRAM_START..=0x2FFFFFF, ewram.clone());
+        bus.register_region(IWRAM_START..=0x3FFFFFF, iwram.clone());
+        bus.register_region(PALETTE_START..=PALETTE_END, lcd.clone());
+        bus.register_region(VRAM_START..=VRAM_END, lcd.clone());
+        bus.register_region(OAM_START..=OAM_END, lcd.clone());
+        // The EEPROM region goes first so that it wins over the ROM mirror it overlaps.
         bus.register_region(
-            0x8000000..=0xFFFFFFF,
-            Rc::new(RefCell::new(Wram::new(0x8000000, 0x8000000))),
+            EEPROM_START..=EEPROM_END,
+            Rc::new(RefCell::new(EepromRegion::new(rom.clone(), backup.clone()))),
         );
+        bus.register_region(ROM_START..=ROM_END, rom.clone());
+        bus.register_region(SRAM_START..=SRAM_END, backup.clone());
 
-        let mut cpu = Interpreter::default();
-        // TODO: Implement async logging.
-        cpu.logging_enabled = true;
+        Self {
+            cpu: Interpreter::default(),
+            bus,
+            lcd,
+            apu,
+            io,
+            interrupts,
+            dma,
+            keypad,
+            ewram,
+            iwram,
+            rom,
+            backup,
+            save_file: None,
+            autosave_interval: None,
+            last_autosave: Instant::now(),
+            snapshot_interval: None,
+            snapshots: Vec::new(),
+        }
+    }
+
+    /// Loads a cartridge image to be mapped from 0x8000000.
+    pub fn load_rom(&mut self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let data = fs::read(path)
+            .map_err(|e| anyhow!("Unable to read ROM file {}: {e}", path.display()))?;
+        self.rom.borrow_mut().load_data(&data)
+    }
+
+    /// The size of the loaded cartridge in bytes, 0 if there is none.
+    pub fn rom_size(&self) -> usize {
+        self.rom.borrow().size()
+    }
+
+    /// The title in the loaded cartridge's header.
+    pub fn rom_title(&self) -> Option<String> {
+        self.rom.borrow().title()
+    }
+
+    /// Resets the CPU so that execution starts over from the BIOS reset vector. Memory and the
+    /// rest of the hardware keep their state. The save is written out first, as it is on drop,
+    /// and failing to write it does not stop the reset.
+    pub fn reset(&mut self) {
+        if let Err(e) = self.flush_save() {
+            println!("Warning: Unable to write save file: {e}");
+        }
+        self.cpu.reset();
+    }
+
+    /// Skips the BIOS boot animation by putting the system in the state the BIOS leaves it in and
+    /// jumping straight to the cartridge entry point. The BIOS stays mapped for SWI calls.
+    pub fn fast_boot(&mut self) -> Result<()> {
+        self.cpu.skip_bios();
+        self.bus.write_byte(POST_BOOT_FLAG_ADDRESS, 1)?;
+        Ok(())
+    }
+
+    /// Copies a blob of raw machine code to `address` and starts executing it in the given state.
+    /// Nothing about cartridge headers or the BIOS is assumed, so this is only meant for tests and
+    /// experiments.
+    pub fn load_raw(
+        &mut self,
+        address: u32,
+        code: &[u8],
+        entry_mode: InstructionMode,
+    ) -> Result<()> {
+        if (ROM_START..=ROM_END).contains(&address) {
+            self.rom.borrow_mut().patch(address, code);
+        } else {
+            for (offset, byte) in code.iter().enumerate() {
+                self.bus.write_byte(address + offset as u32, *byte)?;
+            }
+        }
+        self.cpu.jump_to(address, entry_mode);
+        Ok(())
+    }
+
+    /// Presses or releases a button. A keypad interrupt is requested if the buttons now held
+    /// meet the condition in KEYCNT.
+    pub fn set_button(&mut self, button: Button, pressed: bool) {
+        let mut keypad = self.keypad.borrow_mut();
+        keypad.set_button(button, pressed);
+        if keypad.interrupt_requested() {
+            self.interrupts
+                .borrow_mut()
+                .request(InterruptKind::Keypad.mask());
+        }
+    }
+
+    /// Sets the interrupt's bit in IF as if the hardware had requested it. It is serviced before
+    /// the next instruction if IME is set, the interrupt is enabled in IE and IRQs are not masked
+    /// in the CPSR.
+    pub fn force_interrupt(&mut self, kind: InterruptKind) {
+        self.interrupts.borrow_mut().request(kind.mask());
+    }
+
+    /// Logs every I/O register access, naming the register when it is a known one.
+    pub fn set_io_trace(&mut self, enabled: bool) {
+        self.io.borrow_mut().trace_enabled = enabled;
+    }
+
+    pub fn set_strict_alignment(&mut self, enabled: bool) {
+        self.bus.set_strict_alignment(enabled);
+    }
+
+    /// Chooses what happens when the CPU reaches an opcode the interpreter does not support.
+    pub fn set_unimplemented_opcode_policy(&mut self, policy: UnimplementedOpcodePolicy) {
+        self.cpu.unimplemented_opcode_policy = policy;
+    }
+
+    /// Swaps in save memory of the given kind. Anything stored in the old memory that was not
+    /// flushed is lost, and the new memory is loaded from the save file if one is set.
+    pub fn set_backup_type(&mut self, backup_type: BackupType) -> Result<()> {
+        *self.backup.borrow_mut() = Backup::new(backup_type);
+        if let Some(path) = &self.save_file {
+            self.backup.borrow_mut().load(path)?;
+        }
+        Ok(())
+    }
+
+    /// Loads the cartridge save memory from `path` and remembers it as the place to flush the
+    /// save memory back to.
+    pub fn set_save_file(&mut self, path: impl AsRef<Path>) -> Result<()> {
+        self.backup.borrow_mut().load(path.as_ref())?;
+        self.save_file = Some(path.as_ref().to_path_buf());
+        Ok(())
+    }
+
+    /// Flushes modified save memory every `interval` while emulating. The check happens once per
+    /// frame.
+    pub fn set_autosave_interval(&mut self, interval: Option<Duration>) {
+        self.autosave_interval = interval;
+        self.last_autosave = Instant::now();
+    }
+
+    /// Writes the save memory to the save file if it was modified. Returns whether anything was
+    /// written.
+    pub fn flush_save(&mut self) -> Result<bool> {
+        let Some(path) = &self.save_file else {
+            return Ok(false);
+        };
+        self.backup.borrow_mut().flush(path)
+    }
+
+    /// The most recently rendered frame as 0x00RRGGBB pixels, row by row.
+    pub fn framebuffer(&self) -> Ref<'_, [u32]> {
+        Ref::map(self.lcd.borrow(), |lcd| lcd.framebuffer())
+    }
+
+    pub fn framebuffer_rgba(&self) -> Vec<u8> {
+        self.lcd.borrow().framebuffer_rgba()
+    }
+
+    /// Sets how many audio samples are generated per second of emulated time. 0 generates none.
+    pub fn set_sample_rate(&mut self, sample_rate: u32) {
+        self.apu.borrow_mut().set_sample_rate(sample_rate);
+    }
+
+    /// Takes up to `count` of the audio samples generated so far, oldest first.
+    pub fn pull_samples(&mut self, count: usize) -> Vec<i16> {
+        self.apu.borrow_mut().pull_samples(count)
+    }
+
+    pub fn registers(&self) -> &RegisterBank {
+        self.cpu.registers()
+    }
+
+    pub fn set_flag(&mut self, flag: StatusFlag, value: bool) {
+        self.cpu.set_flag(flag, value);
+    }
+
+    /// Traces every executed instruction to the file at `path`, or stops tracing for `None`.
+    pub fn set_trace_output(&mut self, path: Option<&Path>) -> Result<()> {
+        Ok(self.cpu.set_trace_output(path)?)
+    }
+
+    /// Carries out common BIOS calls such as Div, Sqrt and CpuSet directly instead of running
+    /// the BIOS's code for them.
+    pub fn set_high_level_bios(&mut self, enabled: bool) {
+        self.cpu.high_level_bios = enabled;
+    }
+
+    pub fn set_trace_format(&mut self, trace_format: TraceFormat) {
+        self.cpu.trace_format = trace_format;
+    }
+
+    /// Ends each disassembly trace line with the N, Z, C and V flags after the instruction ran.
+    pub fn set_trace_flags(&mut self, trace_flags: bool) {
+        self.cpu.trace_flags = trace_flags;
+    }
+
+    pub fn set_cpu_mode(&mut self, mode: CpuMode) {
+        self.cpu.set_cpu_mode(mode);
+    }
+
+    pub fn set_instruction_mode(&mut self, instruction_mode: InstructionMode) {
+        self.cpu.set_instruction_mode(instruction_mode);
+    }
 
-        Ok(Self { cpu, bus })
+    pub fn save_state(&self) -> Result<Vec<u8>> {
+        self.snapshot().serialize()
     }
 
-    pub fn emulate(&mut self, cycles: Option<usize>) -> Result<()> {
+    pub fn load_state(&mut self, data: &[u8]) -> Result<()> {
+        self.restore(SaveState::deserialize(data)?)?;
+        Ok(())
+    }
+
+    fn snapshot(&self) -> SaveState {
+        SaveState {
+            cpu: self.cpu.state(),
+            lcd: self.lcd.borrow().clone(),
+            system_control: self.io.borrow().system_control.clone(),
+            interrupts: self.interrupts.borrow().clone(),
+            dma: self.dma.borrow().clone(),
+            keypad: self.keypad.borrow().clone(),
+            apu: self.apu.borrow().clone(),
+            backup: self.backup.borrow().clone(),
+            ewram: self.ewram.borrow().data().to_vec(),
+            iwram: self.iwram.borrow().data().to_vec(),
+        }
+    }
+
+    fn restore(&mut self, state: SaveState) -> Result<(), CoreError> {
+        self.cpu.restore(&state.cpu)?;
+        *self.lcd.borrow_mut() = state.lcd;
+        self.io.borrow_mut().system_control = state.system_control;
+        *self.interrupts.borrow_mut() = state.interrupts;
+        *self.dma.borrow_mut() = state.dma;
+        *self.keypad.borrow_mut() = state.keypad;
+        self.apu.borrow_mut().restore(state.apu);
+        self.backup.borrow_mut().restore(state.backup);
+        self.ewram.borrow_mut().load_data(&state.ewram);
+        self.iwram.borrow_mut().load_data(&state.iwram);
+        Ok(())
+    }
+
+    pub fn instruction_count(&self) -> u64 {
+        self.cpu.instruction_count()
+    }
+
+    /// Keeps a save state every `interval` instructions so that `rewind_to` can go back to any
+    /// point after this is called. Passing None stops taking snapshots and drops the ones taken.
+    pub fn set_snapshot_interval(&mut self, interval: Option<u64>) {
+        self.snapshot_interval = interval;
+        self.snapshots.clear();
+        if interval.is_some() {
+            self.snapshots
+                .push((self.cpu.instruction_count(), self.snapshot()));
+        }
+    }
+
+    /// Returns to the state the system was in when `instruction_count` instructions had been
+    /// executed, by loading the closest snapshot before it and running forward from there.
+    pub fn rewind_to(&mut self, instruction_count: u64) -> Result<()> {
+        if instruction_count > self.cpu.instruction_count() {
+            return Err(anyhow!(
+                "Cannot rewind forward to instruction {instruction_count}"
+            ));
+        }
+        let Some(index) = self
+            .snapshots
+            .iter()
+            .rposition(|(count, _)| *count <= instruction_count)
+        else {
+            return Err(anyhow!(
+                "No snapshot from before instruction {instruction_count}"
+            ));
+        };
+
+        self.snapshots.truncate(index + 1);
+        self.restore(self.snapshots[index].1.clone())?;
+        while self.cpu.instruction_count() < instruction_count {
+            self.tick()?;
+            // Breakpoints and watchpoints were already reported the first time through.
+            self.take_debug_stop();
+        }
+
+        Ok(())
+    }
+
+    fn take_snapshot(&mut self) {
+        let Some(interval) = self.snapshot_interval else {
+            return;
+        };
+        let count = self.cpu.instruction_count();
+        let is_new = self.snapshots.last().is_none_or(|(last, _)| *last < count);
+        if is_new && count.is_multiple_of(interval) {
+            self.snapshots.push((count, self.snapshot()));
+        }
+    }
+
+    /// Compares two serialized save states and reports the registers, flags and memory ranges
+    /// that differ between them.
+    pub fn diff_states(left: &[u8], right: &[u8]) -> Result<Vec<StateDiff>> {
+        Ok(SaveState::deserialize(left)?.diff(&SaveState::deserialize(right)?))
+    }
+
+    /// Reads `length` bytes from `address` for display. Bytes nothing is mapped at are `None`.
+    pub fn read_memory(&mut self, address: u32, length: usize) -> Vec<Option<u8>> {
+        (0..length as u32)
+            .map(|offset| self.bus.peek_byte(address.wrapping_add(offset)).ok())
+            .collect()
+    }
+
+    /// Disassembles `count` instructions from `address` in the CPU's current state.
+    pub fn disassemble_at(&mut self, address: u32, count: usize) -> Result<Vec<(u32, String)>> {
+        let instruction_mode = self.cpu.registers().cpsr.instruction_mode;
+        self.disassemble(address, count, instruction_mode)
+    }
+
+    /// Disassembles `count` instructions from `address` as ARM or THUMB code.
+    pub fn disassemble(
+        &mut self,
+        address: u32,
+        count: usize,
+        instruction_mode: InstructionMode,
+    ) -> Result<Vec<(u32, String)>> {
+        Ok(self
+            .cpu
+            .disassemble_at(&mut self.bus, address, instruction_mode, count)?)
+    }
+
+    /// Stops execution before the instruction at `address`.
+    pub fn add_breakpoint(&mut self, address: u32) {
+        self.cpu.add_breakpoint(address);
+    }
+
+    /// Returns whether there was a breakpoint at `address` to remove.
+    pub fn remove_breakpoint(&mut self, address: u32) -> bool {
+        self.cpu.remove_breakpoint(address)
+    }
+
+    /// Stops execution before any instruction whose opcode matches `pattern` in the bits set in
+    /// `mask`, whatever its address.
+    pub fn add_opcode_breakpoint(&mut self, mask: u32, pattern: u32) {
+        self.cpu.add_opcode_breakpoint(mask, pattern);
+    }
+
+    /// Stops execution after any read or write, as chosen by `kind`, that touches `region`.
+    pub fn add_watchpoint(&mut self, region: RangeInclusive<u32>, kind: WatchKind) {
+        self.bus.add_watchpoint(region, kind);
+    }
+
+    /// Collects the breakpoint or watchpoint the last tick hit, if any.
+    fn take_debug_stop(&mut self) -> Option<StopReason> {
+        match self.cpu.take_breakpoint() {
+            Some(BreakpointHit::Address(address)) => return Some(StopReason::Breakpoint(address)),
+            Some(BreakpointHit::Opcode(address)) => {
+                return Some(StopReason::OpcodeBreakpoint(address))
+            }
+            None => {}
+        }
+        self.bus
+            .take_watchpoint_hit()
+            .map(|(address, kind)| StopReason::Watchpoint { address, kind })
+    }
+
+    pub fn emulate(&mut self, cycles: Option<usize>) -> Result<StopReason> {
         let start = Instant::now();
         let mut cycles_done = 0;
-        loop {
-            cycles_done += match self.cpu.tick(&mut self.bus) {
-                Ok(cycles) => cycles,
+        let stop_reason = loop {
+            cycles_done += match self.tick() {
+                Ok((cycles, _)) => cycles,
                 Err(e) => return Err(anyhow!("{}", e)),
             };
 
+            if let Some(stop_reason) = self.take_debug_stop() {
+                break stop_reason;
+            }
+
             if let Some(cycles) = cycles {
                 if cycles_done >= cycles {
-                    break;
+                    break StopReason::CyclesElapsed;
                 }
             }
-        }
+        };
         let elapsed = start.elapsed();
         let speed = cycles_done as f64 / elapsed.as_secs_f64();
 
@@ -97,6 +549,113 @@ impl Gba {
             );
         }
 
-        Ok(())
+        Ok(stop_reason)
+    }
+
+    /// Runs until the LCD enters the next VBlank period or a breakpoint or watchpoint is hit.
+    pub fn step_frame(&mut self) -> Result<StopReason> {
+        loop {
+            let (_, vblank_started) = self.tick()?;
+            if let Some(stop_reason) = self.take_debug_stop() {
+                return Ok(stop_reason);
+            }
+            if vblank_started {
+                return Ok(StopReason::FrameCompleted);
+            }
+        }
+    }
+
+    /// Runs until the LCD enters the next VBlank period and returns the frame it finished. Like
+    /// [`Gba::step_frame`] this stops early at a breakpoint or watchpoint, in which case the frame
+    /// is only partly drawn.
+    pub fn run_frame(&mut self) -> Result<Ref<'_, [u32]>> {
+        self.step_frame()?;
+        Ok(self.framebuffer())
+    }
+
+    /// Runs until `count` more instructions have executed or a breakpoint or watchpoint is hit.
+    /// Instructions whose condition fails still count.
+    pub fn step_instructions(&mut self, count: u64) -> Result<StopReason> {
+        let target = self.cpu.instruction_count() + count;
+        while self.cpu.instruction_count() < target {
+            self.tick()?;
+            if let Some(stop_reason) = self.take_debug_stop() {
+                return Ok(stop_reason);
+            }
+        }
+        Ok(StopReason::InstructionsCompleted)
+    }
+
+    /// Carries out every DMA transfer that is due. The CPU is not stalled for the time the
+    /// transfers would take on hardware.
+    fn run_dma(&mut self) -> Result<(), CoreError> {
+        loop {
+            let Some(transfer) = self.dma.borrow_mut().next_transfer() else {
+                return Ok(());
+            };
+            let (source, destination) = transfer.run(&mut self.bus)?;
+            if (EEPROM_START..=EEPROM_END).contains(&transfer.destination) {
+                self.backup.borrow_mut().end_transfer();
+            }
+            let requests = self
+                .dma
+                .borrow_mut()
+                .complete(transfer.channel, source, destination);
+            self.interrupts.borrow_mut().request(requests);
+        }
+    }
+
+    fn autosave(&mut self) {
+        let Some(interval) = self.autosave_interval else {
+            return;
+        };
+        if self.last_autosave.elapsed() < interval {
+            return;
+        }
+        self.last_autosave = Instant::now();
+        // Failing to save should not stop the game; the next attempt may well succeed.
+        if let Err(e) = self.flush_save() {
+            println!("Warning: Unable to write save file: {e}");
+        }
+    }
+
+    /// Executes a single CPU step and advances the LCD by the cycles it took. Returns the cycles
+    /// taken and whether VBlank started.
+    fn tick(&mut self) -> Result<(usize, bool), CoreError> {
+        self.cpu
+            .set_irq_line(self.interrupts.borrow().irq_pending());
+        self.bus
+            .set_wait_control(self.io.borrow().system_control.wait_control);
+        let cycles = self.cpu.tick(&mut self.bus)? + self.bus.take_wait_cycles();
+        let vblank_started = self.lcd.borrow_mut().step(cycles);
+        self.apu.borrow_mut().step(cycles);
+        let hblank_started = self.lcd.borrow_mut().take_hblank_started();
+        let requests = self.lcd.borrow_mut().take_interrupt_requests();
+        self.interrupts.borrow_mut().request(requests);
+        if vblank_started {
+            self.dma.borrow_mut().trigger(DmaTiming::VBlank);
+        }
+        if hblank_started {
+            self.dma.borrow_mut().trigger(DmaTiming::HBlank);
+        }
+        self.run_dma()?;
+        if vblank_started {
+            self.autosave();
+        }
+        self.take_snapshot();
+        Ok((cycles, vblank_started))
     }
 }
+
+impl Drop for Gba {
+    /// Makes sure progress a game saved is not lost when the emulator is closed between
+    /// autosaves.
+    fn drop(&mut self) {
+        if let Err(e) = self.flush_save() {
+            println!("Warning: Unable to write save file: {e}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,160 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::ops::RangeInclusive;
+
+use super::{
+    memory::{
+        backup::Backup, dma::DmaController, interrupt::InterruptController, io::SystemControl,
+        keypad::Keypad,
+    },
+    Apu, InterpreterState, Lcd, EWRAM_START, IWRAM_START,
+};
+
+/// Everything that changes while the system runs. The BIOS and cartridge ROM are read only and
+/// are left out, but the cartridge's save memory is kept.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SaveState {
+    pub cpu: InterpreterState,
+    pub lcd: Lcd,
+    pub system_control: SystemControl,
+    pub interrupts: InterruptController,
+    pub dma: DmaController,
+    pub keypad: Keypad,
+    pub apu: Apu,
+    pub backup: Backup,
+    pub ewram: Vec<u8>,
+    pub iwram: Vec<u8>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum StateDiff {
+    Register {
+        name: String,
+        left: u32,
+        right: u32,
+    },
+    Flag {
+        name: &'static str,
+        left: bool,
+        right: bool,
+    },
+    Pipeline,
+    Memory(RangeInclusive<u32>),
+}
+
+impl SaveState {
+    pub fn serialize(&self) -> Result<Vec<u8>> {
+        Ok(bincode::serialize(self)?)
+    }
+
+    pub fn deserialize(data: &[u8]) -> Result<Self> {
+        Ok(bincode::deserialize(data)?)
+    }
+
+    /// Lists everything that differs between two states. Runs of differing memory are merged
+    /// into a single range.
+    pub fn diff(&self, other: &SaveState) -> Vec<StateDiff> {
+        let mut diffs = Vec::new();
+
+        let (left, right) = (&self.cpu.registers, &other.cpu.registers);
+        for ((name, left), (_, right)) in left
+            .named_registers()
+            .into_iter()
+            .zip(right.named_registers())
+        {
+            if left != right {
+                diffs.push(StateDiff::Register { name, left, right });
+            }
+        }
+        for ((name, left), (_, right)) in left.cpsr.flags().into_iter().zip(right.cpsr.flags()) {
+            if left != right {
+                diffs.push(StateDiff::Flag { name, left, right });
+            }
+        }
+        if left.cpsr.mode as u32 != right.cpsr.mode as u32 {
+            diffs.push(StateDiff::Register {
+                name: "cpsr_mode".into(),
+                left: left.cpsr.mode as u32,
+                right: right.cpsr.mode as u32,
+            });
+        }
+
+        if self.cpu.fetched_instruction != other.cpu.fetched_instruction
+            || self.cpu.decoded_instruction != other.cpu.decoded_instruction
+        {
+            diffs.push(StateDiff::Pipeline);
+        }
+
+        for (name, left, right) in [
+            (
+                "vcount",
+                self.lcd.vcount() as u32,
+                other.lcd.vcount() as u32,
+            ),
+            (
+                "scanline_cycles",
+                self.lcd.scanline_cycles() as u32,
+                other.lcd.scanline_cycles() as u32,
+            ),
+            (
+                "postflg",
+                self.system_control.post_boot as u32,
+                other.system_control.post_boot as u32,
+            ),
+            (
+                "ime",
+                self.interrupts.master_enable as u32,
+                other.interrupts.master_enable as u32,
+            ),
+            (
+                "ie",
+                self.interrupts.enable as u32,
+                other.interrupts.enable as u32,
+            ),
+            (
+                "if",
+                self.interrupts.flags as u32,
+                other.interrupts.flags as u32,
+            ),
+        ] {
+            if left != right {
+                diffs.push(StateDiff::Register {
+                    name: name.into(),
+                    left,
+                    right,
+                });
+            }
+        }
+
+        for (start, left, right) in [
+            (EWRAM_START, &self.ewram, &other.ewram),
+            (IWRAM_START, &self.iwram, &other.iwram),
+        ] {
+            diffs.extend(memory_diff(left, right).into_iter().map(|range| {
+                StateDiff::Memory(start + *range.start() as u32..=start + *range.end() as u32)
+            }));
+        }
+
+        diffs
+    }
+}
+
+fn memory_diff(left: &[u8], right: &[u8]) -> Vec<RangeInclusive<usize>> {
+    let mut ranges = Vec::new();
+    let mut start = None;
+    for (i, (left, right)) in left.iter().zip(right).enumerate() {
+        match (left != right, start) {
+            (true, None) => start = Some(i),
+            (false, Some(s)) => {
+                ranges.push(s..=i - 1);
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(s) = start {
+        ranges.push(s..=left.len().min(right.len()) - 1);
+    }
+
+    ranges
+}
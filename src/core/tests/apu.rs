@@ -0,0 +1,53 @@
+use crate::core::{Addressable, Apu};
+
+#[test]
+fn square_wave_has_expected_period() {
+    let mut apu = Apu::new(32768);
+    // Master enable.
+    apu.write_byte(0x4000084, 0x80);
+    // 50% duty at full volume.
+    apu.write_word(0x4000062, 0xF080);
+    // 131072 / (2048 - 1792) = 512Hz, restarting the channel.
+    apu.write_word(0x4000064, 0x8000 | 1792);
+
+    // Four periods of 64 samples each.
+    apu.step(512 * 64 * 4);
+    let samples = apu.pull_samples(usize::MAX);
+    assert_eq!(samples.len(), 64 * 4);
+    assert!(apu.pull_samples(1).is_empty());
+
+    let rising_edges: Vec<usize> = samples
+        .windows(2)
+        .enumerate()
+        .filter(|(_, pair)| pair[0] < 0 && pair[1] > 0)
+        .map(|(i, _)| i + 1)
+        .collect();
+    assert_eq!(rising_edges.len(), 4);
+    assert!(rising_edges.windows(2).all(|pair| pair[1] - pair[0] == 64));
+
+    let period = &samples[rising_edges[0]..rising_edges[0] + 64];
+    assert_eq!(period.iter().filter(|sample| **sample > 0).count(), 32);
+    assert!(period[..32].iter().all(|sample| *sample > 0));
+}
+
+#[test]
+fn silent_without_master_enable() {
+    let mut apu = Apu::new(32768);
+    apu.write_word(0x4000062, 0xF080);
+    apu.write_word(0x4000064, 0x8000 | 1792);
+
+    apu.step(512 * 64);
+    assert!(apu.pull_samples(64).iter().all(|sample| *sample == 0));
+}
+
+#[test]
+fn zero_sample_rate_generates_nothing() {
+    let mut apu = Apu::new(32768);
+    apu.set_sample_rate(0);
+    apu.write_byte(0x4000084, 0x80);
+    apu.write_word(0x4000062, 0xF080);
+    apu.write_word(0x4000064, 0x8000 | 1792);
+
+    apu.step(512 * 64);
+    assert!(apu.pull_samples(64).is_empty());
+}
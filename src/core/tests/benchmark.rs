@@ -0,0 +1,47 @@
+use anyhow::Result;
+use std::time::Instant;
+
+use crate::core::{Bios, Gba, InstructionMode};
+
+const INSTRUCTIONS: u64 = 2_000_000;
+
+/// Times a loop of loads running from the cartridge like a game would. Every fetch and load has
+/// to find the component behind its address, which is most of the work.
+/// Ignored by default; run with `cargo test --release -- --ignored --nocapture`.
+#[test]
+#[ignore]
+fn load_loop_speed() -> Result<()> {
+    let mut gba = Gba::with_bios(Bios::from_buffer(&[0; 0x4000])?);
+
+    let code: Vec<u8> = [
+        // mov r0, #0x8000000
+        0xE3A00408u32,
+        // loop: ldr r1, [r0]
+        0xE5901000,
+        // ldrh r2, [r0]
+        0xE1D020B0,
+        // ldrb r3, [r0]
+        0xE5D03000,
+        // b loop
+        0xEAFFFFFB,
+    ]
+    .iter()
+    .flat_map(|opcode| opcode.to_le_bytes())
+    .collect();
+    gba.load_raw(0x8000000, &code, InstructionMode::Arm)?;
+
+    let start = Instant::now();
+    while gba.instruction_count() < INSTRUCTIONS {
+        gba.tick()?;
+    }
+    let elapsed = start.elapsed();
+
+    assert_eq!(gba.registers().reg(1), 0xE3A00408);
+    println!(
+        "{INSTRUCTIONS} instructions in {}ms, {:.0} instructions per second",
+        elapsed.as_millis(),
+        INSTRUCTIONS as f64 / elapsed.as_secs_f64()
+    );
+
+    Ok(())
+}
@@ -0,0 +1,55 @@
+use anyhow::Result;
+
+use crate::core::{Bios, CpuMode, Gba, InstructionMode};
+
+#[test]
+fn fast_boot_lands_at_cartridge_entry() -> Result<()> {
+    let mut gba = Gba::with_bios(Bios::from_buffer(&[0; 0x4000])?);
+    gba.fast_boot()?;
+
+    let registers = gba.registers();
+    assert_eq!(registers.pc(), 0x8000000);
+    assert!(matches!(registers.cpsr.mode, CpuMode::System));
+    assert_eq!(registers.reg(13), 0x3007F00);
+    assert_eq!(registers.reg_with_mode(13, CpuMode::Supervisor), 0x3007FE0);
+    assert_eq!(registers.reg_with_mode(13, CpuMode::Irq), 0x3007FA0);
+    let irq_view = registers.registers_with_mode(CpuMode::Irq);
+    assert_eq!(irq_view[13], 0x3007FA0);
+    assert_eq!(irq_view[15], 0x8000000);
+
+    // The BIOS is still mapped so SWIs can be serviced by it.
+    assert_eq!(gba.bus.read_dword(0)?, 0);
+
+    Ok(())
+}
+
+#[test]
+fn reset_starts_at_reset_vector_in_supervisor_mode() -> Result<()> {
+    let mut gba = Gba::with_bios(Bios::from_buffer(&[0; 0x4000])?);
+    gba.fast_boot()?;
+    gba.emulate(Some(10))?;
+    gba.reset();
+
+    let registers = gba.registers();
+    assert_eq!(registers.pc(), 0);
+    assert!(matches!(registers.cpsr.mode, CpuMode::Supervisor));
+    assert!(matches!(
+        registers.cpsr.instruction_mode,
+        InstructionMode::Arm
+    ));
+    assert!(registers.cpsr.irq_disable);
+    assert!(registers.cpsr.fiq_disable);
+    assert_eq!(registers.reg_with_mode(13, CpuMode::Supervisor), 0);
+    assert_eq!(registers.reg_with_mode(13, CpuMode::Irq), 0);
+    assert_eq!(registers.reg_with_mode(13, CpuMode::System), 0);
+    assert_eq!(gba.cpu.instruction_count(), 0);
+
+    // The first instruction executed is the one at the reset vector.
+    gba.tick()?;
+    gba.tick()?;
+    gba.tick()?;
+    assert_eq!(gba.cpu.instruction_count(), 1);
+    assert_eq!(gba.registers().pc(), 0xC);
+
+    Ok(())
+}
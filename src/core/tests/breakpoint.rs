@@ -0,0 +1,87 @@
+use anyhow::Result;
+
+use crate::core::{Bios, CpuMode, Gba, InstructionMode, StopReason};
+
+#[test]
+fn opcode_breakpoint_stops_at_swi() -> Result<()> {
+    let mut gba = Gba::with_bios(Bios::from_buffer(&[0; 0x4000])?);
+
+    let mut code = Vec::new();
+    // mov r0, #1
+    code.extend_from_slice(&0xE3A00001u32.to_le_bytes());
+    // mov r1, #2
+    code.extend_from_slice(&0xE3A01002u32.to_le_bytes());
+    // swi 0x060000
+    code.extend_from_slice(&0xEF060000u32.to_le_bytes());
+    gba.load_raw(0x3000000, &code, InstructionMode::Arm)?;
+
+    // Any SWI, whatever the condition or comment.
+    gba.add_opcode_breakpoint(0x0F00_0000, 0x0F00_0000);
+
+    assert_eq!(
+        gba.emulate(Some(100))?,
+        StopReason::OpcodeBreakpoint(0x3000008)
+    );
+    assert_eq!(gba.registers().reg(0), 1);
+    assert_eq!(gba.registers().reg(1), 2);
+    assert!(matches!(gba.registers().cpsr.mode, CpuMode::User));
+
+    // Resuming runs the SWI instead of stopping on it again.
+    assert_eq!(gba.emulate(Some(100))?, StopReason::CyclesElapsed);
+    assert!(matches!(gba.registers().cpsr.mode, CpuMode::Supervisor));
+
+    Ok(())
+}
+
+#[test]
+fn address_breakpoint_stops_at_pc() -> Result<()> {
+    let mut gba = Gba::with_bios(Bios::from_buffer(&[0; 0x4000])?);
+
+    let mut code = Vec::new();
+    // mov r0, #1
+    code.extend_from_slice(&0xE3A00001u32.to_le_bytes());
+    // add r0, r0, #1
+    code.extend_from_slice(&0xE2800001u32.to_le_bytes());
+    // b #-0x8
+    code.extend_from_slice(&0xEAFFFFFDu32.to_le_bytes());
+    gba.load_raw(0x3000000, &code, InstructionMode::Arm)?;
+
+    gba.add_breakpoint(0x3000004);
+
+    assert_eq!(gba.emulate(Some(100))?, StopReason::Breakpoint(0x3000004));
+    assert_eq!(gba.registers().reg(0), 1);
+
+    // The loop comes back around to the same breakpoint.
+    assert_eq!(gba.emulate(Some(100))?, StopReason::Breakpoint(0x3000004));
+    assert_eq!(gba.registers().reg(0), 2);
+
+    assert!(gba.remove_breakpoint(0x3000004));
+    assert_eq!(gba.emulate(Some(100))?, StopReason::CyclesElapsed);
+
+    Ok(())
+}
+
+#[test]
+fn step_instructions_counts_instructions() -> Result<()> {
+    let mut gba = Gba::with_bios(Bios::from_buffer(&[0; 0x4000])?);
+
+    let mut code = Vec::new();
+    for register in 0..5u32 {
+        // mov rN, #1
+        code.extend_from_slice(&(0xE3A00001u32 | (register << 12)).to_le_bytes());
+    }
+    gba.load_raw(0x3000000, &code, InstructionMode::Arm)?;
+
+    assert_eq!(gba.step_instructions(3)?, StopReason::InstructionsCompleted);
+    assert_eq!(gba.cpu.instruction_count(), 3);
+    assert_eq!(gba.registers().reg(2), 1);
+    assert_eq!(gba.registers().reg(3), 0);
+    // Two instructions past the last one executed have been fetched.
+    assert_eq!(gba.registers().pc(), 0x3000014);
+
+    assert_eq!(gba.step_instructions(1)?, StopReason::InstructionsCompleted);
+    assert_eq!(gba.registers().reg(3), 1);
+    assert_eq!(gba.registers().pc(), 0x3000018);
+
+    Ok(())
+}
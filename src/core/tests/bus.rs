@@ -0,0 +1,183 @@
+use std::{cell::RefCell, rc::Rc};
+
+use crate::core::{memory::wram::Wram, Addressable, Bus, CoreError};
+
+fn setup() -> Result<Bus, CoreError> {
+    let mut bus = Bus::default();
+    bus.register_region(0..=1023, Rc::new(RefCell::new(Wram::new(0, 1024))));
+    bus.write_dword(0, 0x44332211)?;
+
+    Ok(bus)
+}
+
+#[test]
+fn unaligned_word_read_rotates() -> Result<(), CoreError> {
+    let mut bus = setup()?;
+
+    assert_eq!(bus.read_word(0)?, 0x2211);
+    assert_eq!(bus.read_word(1)?, 0x1122);
+
+    Ok(())
+}
+
+#[test]
+fn unaligned_dword_read_rotates() -> Result<(), CoreError> {
+    let mut bus = setup()?;
+    bus.write_dword(4, 0x88776655)?;
+
+    assert_eq!(bus.read_dword(0)?, 0x44332211);
+    assert_eq!(bus.read_dword(1)?, 0x11443322);
+    assert_eq!(bus.read_dword(2)?, 0x22114433);
+    assert_eq!(bus.read_dword(3)?, 0x33221144);
+    assert_eq!(bus.read_dword(7)?, 0x77665588);
+
+    Ok(())
+}
+
+#[test]
+fn unaligned_dword_write_is_aligned() -> Result<(), CoreError> {
+    let mut bus = setup()?;
+
+    bus.write_dword(6, 0xDDCCBBAA)?;
+    assert_eq!(bus.read_dword(4)?, 0xDDCCBBAA);
+    assert_eq!(bus.read_dword(0)?, 0x44332211);
+    assert_eq!(bus.read_dword(8)?, 0);
+
+    Ok(())
+}
+
+#[test]
+fn strict_alignment_flags_unaligned_reads() -> Result<(), CoreError> {
+    let mut bus = setup()?;
+    bus.set_strict_alignment(true);
+
+    assert_eq!(bus.read_word(2)?, 0x4433);
+    assert_eq!(bus.read_word(1), Err(CoreError::UnalignedAccess(1)));
+    assert_eq!(bus.read_dword(2), Err(CoreError::UnalignedAccess(2)));
+
+    Ok(())
+}
+
+/// Answers byte reads with 0xFF and halfword reads with 0x1234, and records how wide each
+/// write was.
+#[derive(Default)]
+struct WidthSensitive {
+    writes: Vec<(u32, u32, usize)>,
+}
+
+impl Addressable for WidthSensitive {
+    fn read_byte(&mut self, _address: u32) -> Result<u8, CoreError> {
+        Ok(0xFF)
+    }
+
+    fn write_byte(&mut self, address: u32, data: u8) {
+        self.writes.push((address, data as u32, 1));
+    }
+
+    fn read_word(&mut self, _address: u32) -> Result<u16, CoreError> {
+        Ok(0x1234)
+    }
+
+    fn write_word(&mut self, address: u32, data: u16) {
+        self.writes.push((address, data as u32, 2));
+    }
+}
+
+#[test]
+fn wide_accesses_reach_component() -> Result<(), CoreError> {
+    let component = Rc::new(RefCell::new(WidthSensitive::default()));
+    let mut bus = Bus::default();
+    bus.register_region(0x100..=0x1FF, component.clone());
+
+    assert_eq!(bus.read_byte(0x100)?, 0xFF);
+    assert_eq!(bus.read_word(0x100)?, 0x1234);
+    assert_eq!(bus.read_word(0x101)?, 0x3412);
+    assert_eq!(bus.read_dword(0x100)?, 0x12341234);
+
+    bus.write_byte(0x100, 0xAB)?;
+    bus.write_word(0x102, 0xCDEF)?;
+    bus.write_dword(0x104, 0x89ABCDEF)?;
+    assert_eq!(
+        component.borrow().writes,
+        [
+            (0x100, 0xAB, 1),
+            (0x102, 0xCDEF, 2),
+            (0x104, 0xCDEF, 2),
+            (0x106, 0x89AB, 2),
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn first_registered_region_wins() -> Result<(), CoreError> {
+    let mut bus = Bus::default();
+    let region = |start, size| Rc::new(RefCell::new(Wram::new(start, size)));
+    // A whole page first, then a smaller region inside it that is never reached.
+    let whole = region(0x3000000, 0x1000000);
+    bus.register_region(0x3000000..=0x3FFFFFF, whole.clone());
+    bus.register_region(0x3000000..=0x30000FF, region(0x3000000, 0x100));
+    // A small region first, then a whole page behind it.
+    bus.register_region(0x5000000..=0x50000FF, region(0x5000000, 0x100));
+    bus.register_region(0x5000000..=0x5FFFFFF, region(0x5000000, 0x1000000));
+    // Regions past the top of the memory map still work.
+    bus.register_region(0x10000000..=0x100000FF, region(0x10000000, 0x100));
+
+    bus.write_byte(0x3000010, 0x11)?;
+    assert_eq!(whole.borrow().data()[0x10], 0x11);
+
+    bus.write_byte(0x5000010, 0x22)?;
+    bus.write_byte(0x5000110, 0x33)?;
+    assert_eq!(bus.read_byte(0x5000010)?, 0x22);
+    assert_eq!(bus.read_byte(0x5000110)?, 0x33);
+
+    bus.write_byte(0x10000010, 0x44)?;
+    assert_eq!(bus.read_byte(0x10000010)?, 0x44);
+    assert_eq!(
+        bus.read_byte(0x10000100),
+        Err(CoreError::InvalidRegion(0x10000100))
+    );
+    // Nothing has been fetched, so open bus reads as 0.
+    assert_eq!(bus.read_byte(0x4000000)?, 0);
+
+    Ok(())
+}
+
+/// Faults on reads from the upper half of its region.
+struct Protected;
+
+impl Addressable for Protected {
+    fn read_byte(&mut self, address: u32) -> Result<u8, CoreError> {
+        if address >= 0x180 {
+            Err(CoreError::InvalidRegion(address))
+        } else {
+            Ok(0x5A)
+        }
+    }
+
+    fn write_byte(&mut self, _address: u32, _data: u8) {}
+}
+
+#[test]
+fn component_read_errors_propagate() -> Result<(), CoreError> {
+    let mut bus = Bus::default();
+    bus.register_region(0x100..=0x1FF, Rc::new(RefCell::new(Protected)));
+
+    assert_eq!(bus.read_byte(0x100)?, 0x5A);
+    assert_eq!(bus.read_byte(0x180), Err(CoreError::InvalidRegion(0x180)));
+    assert_eq!(bus.read_word(0x180), Err(CoreError::InvalidRegion(0x180)));
+    assert_eq!(bus.read_dword(0x17C)?, 0x5A5A5A5A);
+    assert_eq!(bus.read_dword(0x180), Err(CoreError::InvalidRegion(0x180)));
+    // Unmapped addresses past the end of the memory map fault rather than reading open bus.
+    assert_eq!(
+        bus.read_byte(0x10000000),
+        Err(CoreError::InvalidRegion(0x10000000))
+    );
+    assert_eq!(
+        bus.read_dword(0x10000000),
+        Err(CoreError::InvalidRegion(0x10000000))
+    );
+
+    Ok(())
+}
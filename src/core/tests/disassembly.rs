@@ -0,0 +1,48 @@
+use anyhow::Result;
+
+use crate::core::{Bios, Gba, InstructionMode, Interpreter};
+
+#[test]
+fn disassembly_does_not_run_code() -> Result<()> {
+    let mut gba = Gba::with_bios(Bios::from_buffer(&[0; 0x4000])?);
+
+    let mut code = Vec::new();
+    // mov r0, #1
+    code.extend_from_slice(&0xE3A00001u32.to_le_bytes());
+    // moveq r1, #2
+    code.extend_from_slice(&0x03A01002u32.to_le_bytes());
+    // b .
+    code.extend_from_slice(&0xEAFFFFFEu32.to_le_bytes());
+    gba.load_raw(0x3000000, &code, InstructionMode::Arm)?;
+
+    let lines = gba.disassemble_at(0x3000000, 3)?;
+    assert_eq!(lines[0].0, 0x3000000);
+    assert!(lines[0].1.starts_with("mov r0"));
+    assert!(lines[1].1.starts_with("mov.eq r1"));
+    // Branch targets are relative to the instruction being disassembled, not the CPU's PC.
+    assert_eq!(lines[2], (0x3000008, "b #-0x8 (=$03000008)".to_string()));
+
+    assert_eq!(gba.registers().pc(), 0x3000000);
+    assert_eq!(gba.registers().reg(0), 0);
+
+    Ok(())
+}
+
+#[test]
+fn opcodes_disassemble_without_memory() -> Result<()> {
+    let arm = |opcode| Interpreter::disassemble_opcode(opcode, 0x8000000, InstructionMode::Arm);
+    let thumb = |opcode| Interpreter::disassemble_opcode(opcode, 0x8000000, InstructionMode::Thumb);
+
+    // add r0, r1, r2
+    assert_eq!(arm(0xE0810002)?, "add r0, r1, r2, LSL, #0");
+    // b $8000010, relative to the address given rather than any PC
+    assert_eq!(arm(0xEA000002)?, "b #0x8 (=$08000010)");
+    // bl $7FFFFF8
+    assert_eq!(arm(0xEBFFFFFC)?, "bl #-0x10 (=$07FFFFF8)");
+    // mov r0, #1
+    assert_eq!(thumb(0x2001)?, "movs r0, r0, #0x1");
+    // beq $8000000
+    assert_eq!(thumb(0xD0FE)?, "b.eq #-0x4 (=$08000000)");
+
+    Ok(())
+}
@@ -0,0 +1,90 @@
+use anyhow::Result;
+
+use crate::core::{Bios, Gba, InstructionMode, InterruptKind};
+
+const SOURCE: u32 = 0x3001000;
+const DESTINATION: u32 = 0x3002000;
+
+fn looping_gba() -> Result<Gba> {
+    let mut gba = Gba::with_bios(Bios::from_buffer(&[0; 0x4000])?);
+
+    // loop: b loop
+    gba.load_raw(
+        0x3000000,
+        &0xEAFFFFFEu32.to_le_bytes(),
+        InstructionMode::Arm,
+    )?;
+    for _ in 0..3 {
+        gba.tick()?;
+    }
+
+    Ok(gba)
+}
+
+#[test]
+fn immediate_fixed_source_word_copy() -> Result<()> {
+    let mut gba = looping_gba()?;
+    gba.bus.write_dword(SOURCE, 0xDEADBEEF)?;
+    gba.bus.write_dword(SOURCE + 4, 0x12345678)?;
+
+    // DMA3: 8 words, source fixed, destination incrementing, IRQ on completion.
+    gba.bus.write_dword(0x40000D4, SOURCE)?;
+    gba.bus.write_dword(0x40000D8, DESTINATION)?;
+    gba.bus.write_word(0x40000DC, 8)?;
+    gba.bus.write_word(0x40000DE, 0xC500)?;
+    gba.tick()?;
+
+    for offset in (0..32).step_by(4) {
+        assert_eq!(gba.bus.read_dword(DESTINATION + offset)?, 0xDEADBEEF);
+    }
+    assert_eq!(gba.bus.read_dword(DESTINATION + 32)?, 0);
+    // Transfers that do not repeat switch the channel off when they finish.
+    assert_eq!(gba.bus.read_word(0x40000DE)?, 0x4500);
+    assert_eq!(gba.bus.read_word(0x4000202)?, InterruptKind::Dma3.mask());
+
+    Ok(())
+}
+
+#[test]
+fn vblank_transfer_waits_for_vblank() -> Result<()> {
+    let mut gba = looping_gba()?;
+    gba.bus.write_word(SOURCE, 0x1111)?;
+    gba.bus.write_word(SOURCE + 2, 0x2222)?;
+
+    // DMA1: 2 halfwords, both incrementing, on VBlank.
+    gba.bus.write_dword(0x40000BC, SOURCE)?;
+    gba.bus.write_dword(0x40000C0, DESTINATION)?;
+    gba.bus.write_word(0x40000C4, 2)?;
+    gba.bus.write_word(0x40000C6, 0x9000)?;
+    for _ in 0..10 {
+        gba.tick()?;
+    }
+    assert_eq!(gba.bus.read_dword(DESTINATION)?, 0);
+
+    gba.step_frame()?;
+    assert_eq!(gba.bus.read_dword(DESTINATION)?, 0x22221111);
+    assert_eq!(gba.bus.read_word(0x40000C6)?, 0x1000);
+
+    Ok(())
+}
+
+#[test]
+fn count_of_0x4000_is_the_largest_transfer() -> Result<()> {
+    const EWRAM: u32 = 0x2000000;
+
+    let mut gba = looping_gba()?;
+    gba.bus.write_word(SOURCE, 0xBEEF)?;
+
+    // DMA0: 0x4000 halfwords, source fixed, destination incrementing.
+    gba.bus.write_dword(0x40000B0, SOURCE)?;
+    gba.bus.write_dword(0x40000B4, EWRAM)?;
+    gba.bus.write_word(0x40000B8, 0x4000)?;
+    gba.bus.write_word(0x40000BA, 0x8100)?;
+    gba.tick()?;
+
+    assert_eq!(gba.bus.read_word(EWRAM)?, 0xBEEF);
+    assert_eq!(gba.bus.read_word(EWRAM + 0x7FFE)?, 0xBEEF);
+    assert_eq!(gba.bus.read_word(EWRAM + 0x8000)?, 0);
+
+    Ok(())
+}
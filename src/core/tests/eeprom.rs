@@ -0,0 +1,103 @@
+use anyhow::Result;
+
+use crate::core::{BackupType, Bios, Gba, InstructionMode};
+
+const BUFFER: u32 = 0x3001000;
+const EEPROM: u32 = 0xD000000;
+
+fn eeprom_gba() -> Result<Gba> {
+    let mut gba = Gba::with_bios(Bios::from_buffer(&[0; 0x4000])?);
+    gba.set_backup_type(BackupType::Eeprom)?;
+
+    // loop: b loop
+    gba.load_raw(
+        0x3000000,
+        &0xEAFFFFFEu32.to_le_bytes(),
+        InstructionMode::Arm,
+    )?;
+    for _ in 0..3 {
+        gba.tick()?;
+    }
+
+    Ok(gba)
+}
+
+/// Copies halfwords with DMA3 the way games talk to the EEPROM.
+fn dma3(gba: &mut Gba, source: u32, destination: u32, count: u16) -> Result<()> {
+    gba.bus.write_dword(0x40000D4, source)?;
+    gba.bus.write_dword(0x40000D8, destination)?;
+    gba.bus.write_word(0x40000DC, count)?;
+    gba.bus.write_word(0x40000DE, 0x8000)?;
+    gba.tick()?;
+    Ok(())
+}
+
+fn send(gba: &mut Gba, bits: &[bool]) -> Result<()> {
+    for (index, bit) in bits.iter().enumerate() {
+        gba.bus.write_word(BUFFER + index as u32 * 2, *bit as u16)?;
+    }
+    dma3(gba, BUFFER, EEPROM, bits.len() as u16)
+}
+
+fn bits(value: u64, count: usize) -> impl Iterator<Item = bool> {
+    (0..count).rev().map(move |bit| value & (1 << bit) > 0)
+}
+
+fn write_request(address: u64, address_bits: usize, data: u64) -> Vec<bool> {
+    [true, false]
+        .into_iter()
+        .chain(bits(address, address_bits))
+        .chain(bits(data, 64))
+        .chain([false])
+        .collect()
+}
+
+fn read_request(address: u64, address_bits: usize) -> Vec<bool> {
+    [true, true]
+        .into_iter()
+        .chain(bits(address, address_bits))
+        .chain([false])
+        .collect()
+}
+
+fn read_block(gba: &mut Gba, address: u64, address_bits: usize) -> Result<u64> {
+    send(gba, &read_request(address, address_bits))?;
+    dma3(gba, EEPROM, BUFFER, 68)?;
+
+    let mut data = 0;
+    for index in 0..68 {
+        let bit = gba.bus.read_word(BUFFER + index * 2)? & 1;
+        if index < 4 {
+            assert_eq!(bit, 0);
+        }
+        data = (data << 1) | bit as u64;
+    }
+    Ok(data)
+}
+
+#[test]
+fn write_then_read_small() -> Result<()> {
+    let mut gba = eeprom_gba()?;
+
+    send(&mut gba, &write_request(0x15, 6, 0x0123456789ABCDEF))?;
+    // Ready once the write has finished.
+    assert_eq!(gba.bus.read_word(EEPROM)? & 1, 1);
+
+    assert_eq!(read_block(&mut gba, 0x15, 6)?, 0x0123456789ABCDEF);
+    assert_eq!(read_block(&mut gba, 0x16, 6)?, u64::MAX);
+
+    Ok(())
+}
+
+#[test]
+fn write_then_read_large() -> Result<()> {
+    let mut gba = eeprom_gba()?;
+
+    send(&mut gba, &write_request(0x3FF, 14, 0xFEDCBA9876543210))?;
+    send(&mut gba, &write_request(0x001, 14, 0x1122334455667788))?;
+
+    assert_eq!(read_block(&mut gba, 0x3FF, 14)?, 0xFEDCBA9876543210);
+    assert_eq!(read_block(&mut gba, 0x001, 14)?, 0x1122334455667788);
+
+    Ok(())
+}
@@ -0,0 +1,49 @@
+use anyhow::Result;
+
+use crate::core::{Bios, CpuMode, Gba, InstructionMode, StatusFlag};
+
+#[test]
+fn set_flag_changes_execution() -> Result<()> {
+    let mut gba = Gba::with_bios(Bios::from_buffer(&[0; 0x4000])?);
+
+    let mut code = Vec::new();
+    // adc r0, r0, #0
+    code.extend_from_slice(&0xE2A00000u32.to_le_bytes());
+    // b .
+    code.extend_from_slice(&0xEAFFFFFEu32.to_le_bytes());
+    gba.load_raw(0x3000000, &code, InstructionMode::Arm)?;
+
+    gba.set_flag(StatusFlag::Carry, true);
+    assert!(gba.registers().cpsr.carry);
+    gba.emulate(Some(3))?;
+    assert_eq!(gba.registers().reg(0), 1);
+
+    gba.set_flag(StatusFlag::Carry, false);
+    assert!(!gba.registers().cpsr.carry);
+
+    Ok(())
+}
+
+#[test]
+fn set_modes_round_trip() -> Result<()> {
+    let mut gba = Gba::with_bios(Bios::from_buffer(&[0; 0x4000])?);
+
+    gba.fast_boot()?;
+    // mov r0, #7; b .
+    gba.load_raw(0x3000000, &[0x07, 0x20, 0xFE, 0xE7], InstructionMode::Arm)?;
+
+    gba.set_cpu_mode(CpuMode::Irq);
+    assert!(matches!(gba.registers().cpsr.mode, CpuMode::Irq));
+    assert_eq!(gba.registers().reg(13), 0x3007FA0);
+
+    // The code is only valid as Thumb, so it runs correctly only if the pipeline was refilled.
+    gba.set_instruction_mode(InstructionMode::Thumb);
+    assert!(matches!(
+        gba.registers().cpsr.instruction_mode,
+        InstructionMode::Thumb
+    ));
+    gba.emulate(Some(3))?;
+    assert_eq!(gba.registers().reg(0), 7);
+
+    Ok(())
+}
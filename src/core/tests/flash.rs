@@ -0,0 +1,101 @@
+use anyhow::Result;
+use std::{env, fs, process};
+
+use crate::core::{BackupType, Bios, Gba};
+
+fn flash_gba(backup_type: BackupType) -> Result<Gba> {
+    let mut gba = Gba::with_bios(Bios::from_buffer(&[0; 0x4000])?);
+    gba.set_backup_type(backup_type)?;
+    Ok(gba)
+}
+
+fn command(gba: &mut Gba, command: u8) -> Result<()> {
+    gba.bus.write_byte(0xE005555, 0xAA)?;
+    gba.bus.write_byte(0xE002AAA, 0x55)?;
+    gba.bus.write_byte(0xE005555, command)?;
+    Ok(())
+}
+
+fn program(gba: &mut Gba, address: u32, data: u8) -> Result<()> {
+    command(gba, 0xA0)?;
+    gba.bus.write_byte(address, data)?;
+    Ok(())
+}
+
+fn erase_sector(gba: &mut Gba, sector: u32) -> Result<()> {
+    command(gba, 0x80)?;
+    gba.bus.write_byte(0xE005555, 0xAA)?;
+    gba.bus.write_byte(0xE002AAA, 0x55)?;
+    gba.bus.write_byte(0xE000000 + sector * 0x1000, 0x30)?;
+    Ok(())
+}
+
+#[test]
+fn erase_then_program() -> Result<()> {
+    let mut gba = flash_gba(BackupType::Flash64K)?;
+
+    program(&mut gba, 0xE001234, 0x42)?;
+    program(&mut gba, 0xE002000, 0x24)?;
+    assert_eq!(gba.bus.read_byte(0xE001234)?, 0x42);
+
+    // Writes without the command sequence are ignored.
+    gba.bus.write_byte(0xE001235, 0x99)?;
+    assert_eq!(gba.bus.read_byte(0xE001235)?, 0xFF);
+
+    erase_sector(&mut gba, 1)?;
+    for offset in 0..0x1000 {
+        assert_eq!(gba.bus.read_byte(0xE001000 + offset)?, 0xFF);
+    }
+    assert_eq!(gba.bus.read_byte(0xE002000)?, 0x24);
+
+    program(&mut gba, 0xE001234, 0x17)?;
+    assert_eq!(gba.bus.read_byte(0xE001234)?, 0x17);
+
+    command(&mut gba, 0x80)?;
+    command(&mut gba, 0x10)?;
+    assert_eq!(gba.bus.read_byte(0xE001234)?, 0xFF);
+    assert_eq!(gba.bus.read_byte(0xE002000)?, 0xFF);
+
+    Ok(())
+}
+
+#[test]
+fn id_mode_reports_chip() -> Result<()> {
+    let mut gba = flash_gba(BackupType::Flash128K)?;
+    program(&mut gba, 0xE000000, 0x00)?;
+
+    command(&mut gba, 0x90)?;
+    assert_eq!(gba.bus.read_byte(0xE000000)?, 0x62);
+    assert_eq!(gba.bus.read_byte(0xE000001)?, 0x13);
+
+    command(&mut gba, 0xF0)?;
+    assert_eq!(gba.bus.read_byte(0xE000000)?, 0x00);
+
+    Ok(())
+}
+
+#[test]
+fn banks_are_separate_and_persist() -> Result<()> {
+    let path = env::temp_dir().join(format!("rgba-flash-{}.sav", process::id()));
+    let _ = fs::remove_file(&path);
+
+    let mut gba = flash_gba(BackupType::Flash128K)?;
+    gba.set_save_file(&path)?;
+    program(&mut gba, 0xE000010, 0x01)?;
+    command(&mut gba, 0xB0)?;
+    gba.bus.write_byte(0xE000000, 1)?;
+    assert_eq!(gba.bus.read_byte(0xE000010)?, 0xFF);
+    program(&mut gba, 0xE000010, 0x02)?;
+    assert!(gba.flush_save()?);
+    assert_eq!(fs::metadata(&path)?.len(), 0x20000);
+
+    let mut reloaded = flash_gba(BackupType::Flash128K)?;
+    reloaded.set_save_file(&path)?;
+    assert_eq!(reloaded.bus.read_byte(0xE000010)?, 0x01);
+    command(&mut reloaded, 0xB0)?;
+    reloaded.bus.write_byte(0xE000000, 1)?;
+    assert_eq!(reloaded.bus.read_byte(0xE000010)?, 0x02);
+
+    fs::remove_file(&path)?;
+    Ok(())
+}
@@ -0,0 +1,141 @@
+use anyhow::Result;
+
+use crate::core::{
+    Bios, Gba, InstructionMode, Lcd, FRAME_CYCLES, HDRAW_CYCLES, SCANLINE_CYCLES, SCREEN_HEIGHT,
+    SCREEN_WIDTH, TOTAL_SCANLINES, VISIBLE_SCANLINES,
+};
+
+fn idle_loop_gba() -> Result<Gba> {
+    // b #-8, branches to itself forever.
+    let mut bios = [0; 0x4000];
+    bios[0..4].copy_from_slice(&0xEAFFFFFEu32.to_le_bytes());
+    Ok(Gba::with_bios(Bios::from_buffer(&bios)?))
+}
+
+#[test]
+fn step_frame_stops_at_vblank() -> Result<()> {
+    let mut gba = idle_loop_gba()?;
+
+    for _ in 0..2 {
+        gba.step_frame()?;
+
+        let lcd = gba.lcd.borrow();
+        assert_eq!(lcd.vcount(), VISIBLE_SCANLINES);
+        assert!(lcd.is_vblank());
+        // The branch takes 3 cycles so VBlank must have started during the last instruction.
+        assert!(lcd.scanline_cycles() < 3);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn scanline_wraps_after_a_full_frame() {
+    let mut lcd = Lcd::default();
+
+    assert!(lcd.step(FRAME_CYCLES - SCANLINE_CYCLES - 1));
+    assert_eq!(lcd.vcount(), TOTAL_SCANLINES - 2);
+    lcd.step(1);
+    assert_eq!(lcd.vcount(), TOTAL_SCANLINES - 1);
+    assert!(lcd.is_vblank());
+
+    lcd.step(SCANLINE_CYCLES);
+    assert_eq!(lcd.vcount(), 0);
+    assert_eq!(lcd.scanline_cycles(), 0);
+    assert!(!lcd.is_vblank());
+}
+
+#[test]
+fn hblank_covers_only_the_end_of_a_line() {
+    let mut lcd = Lcd::default();
+
+    lcd.step(HDRAW_CYCLES - 1);
+    assert!(!lcd.is_hblank());
+    assert_eq!(lcd.display_status() & 0b10, 0);
+
+    lcd.step(1);
+    assert!(lcd.is_hblank());
+    assert_eq!(lcd.display_status() & 0b10, 0b10);
+
+    lcd.step((SCANLINE_CYCLES - HDRAW_CYCLES) / 2);
+    assert!(lcd.is_hblank());
+    assert_eq!(lcd.display_status() & 0b10, 0b10);
+
+    lcd.step(SCANLINE_CYCLES - HDRAW_CYCLES - (SCANLINE_CYCLES - HDRAW_CYCLES) / 2);
+    assert_eq!(lcd.vcount(), 1);
+    assert!(!lcd.is_hblank());
+    assert_eq!(lcd.display_status() & 0b10, 0);
+}
+
+#[test]
+fn dispstat_reports_timing() -> Result<()> {
+    let mut gba = idle_loop_gba()?;
+
+    // Ask for a VCOUNT match on line 1 and enable all three interrupts.
+    gba.bus.write_word(0x4000004, 0x0138)?;
+    assert_eq!(gba.bus.read_word(0x4000004)?, 0x0138);
+
+    gba.lcd.borrow_mut().step(HDRAW_CYCLES);
+    assert_eq!(gba.bus.read_word(0x4000004)?, 0x013A);
+
+    gba.lcd.borrow_mut().step(SCANLINE_CYCLES - HDRAW_CYCLES);
+    assert_eq!(gba.bus.read_word(0x4000004)?, 0x013C);
+
+    gba.lcd
+        .borrow_mut()
+        .step(SCANLINE_CYCLES * (VISIBLE_SCANLINES as usize - 1));
+    assert_eq!(gba.bus.read_word(0x4000004)?, 0x0139);
+
+    // The status flags cannot be written.
+    gba.bus.write_word(0x4000004, 0x0007)?;
+    assert_eq!(gba.bus.read_word(0x4000004)?, 0x0001);
+
+    Ok(())
+}
+
+#[test]
+fn vblank_polling_loop_finishes() -> Result<()> {
+    let mut gba = idle_loop_gba()?;
+    let mut code = Vec::new();
+    // mov r0, #0x4000000
+    code.extend_from_slice(&0xE3A00301u32.to_le_bytes());
+    // ldrh r1, [r0, #4]
+    code.extend_from_slice(&0xE1D010B4u32.to_le_bytes());
+    // tst r1, #1
+    code.extend_from_slice(&0xE3110001u32.to_le_bytes());
+    // beq to the ldrh
+    code.extend_from_slice(&0x0AFFFFFCu32.to_le_bytes());
+    // mov r2, #1
+    code.extend_from_slice(&0xE3A02001u32.to_le_bytes());
+    // b .
+    code.extend_from_slice(&0xEAFFFFFEu32.to_le_bytes());
+    gba.load_raw(0x3000000, &code, InstructionMode::Arm)?;
+
+    gba.step_frame()?;
+    assert_eq!(gba.registers().reg(2), 0);
+    gba.step_instructions(10)?;
+
+    assert_eq!(gba.registers().reg(2), 1);
+    assert!(gba.lcd.borrow().is_vblank());
+
+    Ok(())
+}
+
+#[test]
+fn run_frame_covers_every_scanline() -> Result<()> {
+    let mut gba = idle_loop_gba()?;
+    let last_line = TOTAL_SCANLINES - 1;
+    // Interrupt on the last line of the frame.
+    gba.bus.write_word(0x4000004, (last_line << 8) | 0x20)?;
+
+    let frame_length = gba.run_frame()?.len();
+    assert_eq!(frame_length, SCREEN_WIDTH * SCREEN_HEIGHT);
+    assert_eq!(gba.lcd.borrow().vcount(), VISIBLE_SCANLINES);
+    assert_eq!(gba.bus.read_word(0x4000202)? & 0b100, 0);
+
+    gba.run_frame()?;
+    assert_eq!(gba.lcd.borrow().vcount(), VISIBLE_SCANLINES);
+    assert_eq!(gba.bus.read_word(0x4000202)? & 0b100, 0b100);
+
+    Ok(())
+}
@@ -0,0 +1,168 @@
+use anyhow::Result;
+
+use crate::core::{Bios, CpuMode, Gba, InstructionMode, InterruptKind};
+
+fn looping_gba() -> Result<Gba> {
+    // The IRQ vector branches to itself.
+    let mut bios = [0; 0x4000];
+    bios[0x18..0x1C].copy_from_slice(&0xEAFFFFFEu32.to_le_bytes());
+    let mut gba = Gba::with_bios(Bios::from_buffer(&bios)?);
+
+    // loop: add r0, r0, #1 ; b loop
+    let mut code = Vec::new();
+    code.extend_from_slice(&0xE2800001u32.to_le_bytes());
+    code.extend_from_slice(&0xEAFFFFFDu32.to_le_bytes());
+    gba.load_raw(0x3000000, &code, InstructionMode::Arm)?;
+    for _ in 0..10 {
+        gba.tick()?;
+    }
+
+    Ok(gba)
+}
+
+#[test]
+fn forced_vblank_enters_handler() -> Result<()> {
+    let mut gba = looping_gba()?;
+    gba.bus
+        .write_word(0x4000200, InterruptKind::VBlank.mask())?;
+    gba.bus.write_byte(0x4000208, 1)?;
+
+    gba.force_interrupt(InterruptKind::VBlank);
+    assert_eq!(gba.bus.read_word(0x4000202)?, 0x0001);
+    gba.tick()?;
+
+    let mut registers = gba.registers().clone();
+    assert!(matches!(registers.cpsr.mode, CpuMode::Irq));
+    assert!(matches!(
+        registers.cpsr.instruction_mode,
+        InstructionMode::Arm
+    ));
+    assert!(registers.cpsr.irq_disable);
+    // The vector has been fetched.
+    assert_eq!(registers.pc(), 0x1C);
+    // Returning with subs pc, lr, #4 goes back into the loop.
+    assert!(matches!(registers.reg(14) - 4, 0x3000000 | 0x3000004));
+    assert!(matches!(registers.spsr().mode, CpuMode::User));
+
+    // Acknowledging the interrupt clears its flag.
+    gba.bus
+        .write_word(0x4000202, InterruptKind::VBlank.mask())?;
+    assert_eq!(gba.bus.read_word(0x4000202)?, 0);
+
+    Ok(())
+}
+
+#[test]
+fn forced_interrupt_needs_ie_and_ime() -> Result<()> {
+    let mut gba = looping_gba()?;
+
+    gba.force_interrupt(InterruptKind::Timer0);
+    gba.bus
+        .write_word(0x4000200, InterruptKind::Timer0.mask())?;
+    gba.tick()?;
+    assert!(matches!(gba.registers().cpsr.mode, CpuMode::User));
+
+    gba.bus.write_byte(0x4000208, 1)?;
+    gba.bus
+        .write_word(0x4000200, InterruptKind::VBlank.mask())?;
+    gba.tick()?;
+    assert!(matches!(gba.registers().cpsr.mode, CpuMode::User));
+
+    gba.bus
+        .write_word(0x4000200, InterruptKind::Timer0.mask())?;
+    gba.tick()?;
+    assert!(matches!(gba.registers().cpsr.mode, CpuMode::Irq));
+
+    Ok(())
+}
+
+#[test]
+fn lcd_raises_vblank_irq() -> Result<()> {
+    let mut gba = looping_gba()?;
+    gba.bus.write_word(0x4000004, 0x0008)?;
+    gba.bus
+        .write_word(0x4000200, InterruptKind::VBlank.mask())?;
+    gba.bus.write_byte(0x4000208, 1)?;
+
+    gba.step_frame()?;
+    assert_eq!(gba.bus.read_word(0x4000202)?, InterruptKind::VBlank.mask());
+    assert!(matches!(gba.registers().cpsr.mode, CpuMode::User));
+
+    gba.tick()?;
+    assert!(matches!(gba.registers().cpsr.mode, CpuMode::Irq));
+    assert_eq!(gba.registers().pc(), 0x1C);
+
+    Ok(())
+}
+
+#[test]
+fn if_bits_are_cleared_by_writing_one() -> Result<()> {
+    let mut gba = looping_gba()?;
+    gba.force_interrupt(InterruptKind::VBlank);
+    gba.force_interrupt(InterruptKind::Timer1);
+    gba.force_interrupt(InterruptKind::Keypad);
+    assert_eq!(gba.bus.read_word(0x4000202)?, 0x1011);
+
+    // Zero bits leave their flags alone.
+    gba.bus.write_word(0x4000202, 0x0000)?;
+    assert_eq!(gba.bus.read_word(0x4000202)?, 0x1011);
+
+    gba.bus.write_byte(0x4000202, 0x10)?;
+    assert_eq!(gba.bus.read_word(0x4000202)?, 0x1001);
+
+    gba.bus.write_byte(0x4000203, 0x10)?;
+    assert_eq!(gba.bus.read_word(0x4000202)?, 0x0001);
+
+    // Writing IE or IME does not touch IF.
+    gba.bus.write_word(0x4000200, 0xFFFF)?;
+    gba.bus.write_word(0x4000208, 0x0001)?;
+    assert_eq!(gba.bus.read_word(0x4000200)?, 0x3FFF);
+    assert_eq!(gba.bus.read_word(0x4000202)?, 0x0001);
+    assert!(gba.interrupts.borrow().irq_pending());
+
+    Ok(())
+}
+
+#[test]
+fn irq_returns_to_thumb_code() -> Result<()> {
+    // The IRQ handler returns straight away with subs pc, lr, #4.
+    let mut bios = [0; 0x4000];
+    bios[0x18..0x1C].copy_from_slice(&0xE25EF004u32.to_le_bytes());
+    let mut gba = Gba::with_bios(Bios::from_buffer(&bios)?);
+
+    // loop: adds r0, #1 ; b loop
+    let mut code = Vec::new();
+    code.extend_from_slice(&0x3001u16.to_le_bytes());
+    code.extend_from_slice(&0xE7FDu16.to_le_bytes());
+    gba.load_raw(0x3000000, &code, InstructionMode::Thumb)?;
+    for _ in 0..10 {
+        gba.tick()?;
+    }
+
+    gba.bus
+        .write_word(0x4000200, InterruptKind::VBlank.mask())?;
+    gba.bus.write_byte(0x4000208, 1)?;
+    gba.force_interrupt(InterruptKind::VBlank);
+    gba.tick()?;
+    assert!(matches!(gba.registers().cpsr.mode, CpuMode::Irq));
+    gba.bus
+        .write_word(0x4000202, InterruptKind::VBlank.mask())?;
+
+    // Run the handler and let the loop go around a few more times.
+    let count = gba.registers().reg(0);
+    for _ in 0..12 {
+        gba.tick()?;
+    }
+
+    let registers = gba.registers();
+    assert!(matches!(registers.cpsr.mode, CpuMode::User));
+    assert!(matches!(
+        registers.cpsr.instruction_mode,
+        InstructionMode::Thumb
+    ));
+    assert!(!registers.cpsr.irq_disable);
+    assert!((0x3000000..0x3000008).contains(&registers.pc()));
+    assert!(registers.reg(0) > count);
+
+    Ok(())
+}
@@ -0,0 +1,22 @@
+use anyhow::Result;
+
+use crate::core::{Bios, Gba, SCANLINE_CYCLES};
+
+#[test]
+fn io_reads_dispatch_to_components() -> Result<()> {
+    let mut gba = Gba::with_bios(Bios::from_buffer(&[0; 0x4000])?);
+
+    gba.lcd.borrow_mut().step(SCANLINE_CYCLES * 3);
+    assert_eq!(gba.bus.read_byte(0x4000006)?, 3);
+
+    gba.fast_boot()?;
+    assert_eq!(gba.bus.read_byte(0x4000300)?, 1);
+    assert_eq!(gba.bus.read_byte(0x4000208)?, 0);
+
+    gba.bus.write_byte(0x4000208, 1)?;
+    assert_eq!(gba.bus.read_byte(0x4000208)?, 1);
+    // Writing a system control register leaves the LCD's registers alone.
+    assert_eq!(gba.bus.read_byte(0x4000006)?, 3);
+
+    Ok(())
+}
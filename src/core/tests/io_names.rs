@@ -0,0 +1,27 @@
+use crate::core::memory::io_names::{describe_io_access, io_register_name};
+
+#[test]
+fn dispcnt_write_is_named() {
+    assert_eq!(
+        describe_io_access(0x4000000, 0x03, true),
+        "write DISPCNT = 0x03"
+    );
+    assert_eq!(
+        describe_io_access(0x4000001, 0x04, true),
+        "write DISPCNT+1 = 0x04"
+    );
+}
+
+#[test]
+fn registers_are_found_by_range() {
+    assert_eq!(io_register_name(0x40000B0).as_deref(), Some("DMA0SAD"));
+    assert_eq!(io_register_name(0x40000B3).as_deref(), Some("DMA0SAD+3"));
+    assert_eq!(io_register_name(0x4000100).as_deref(), Some("TM0CNT_L"));
+    assert_eq!(io_register_name(0x4000208).as_deref(), Some("IME"));
+    // Unused gap between BLDY and the sound registers.
+    assert_eq!(io_register_name(0x4000056), None);
+    assert_eq!(
+        describe_io_access(0x4000056, 0, false),
+        "read 0x04000056 = 0x00"
+    );
+}
@@ -0,0 +1,33 @@
+use anyhow::Result;
+
+use crate::core::{Bios, Button, Gba, InterruptKind};
+
+#[test]
+fn buttons_are_active_low() -> Result<()> {
+    let mut gba = Gba::with_bios(Bios::from_buffer(&[0; 0x4000])?);
+    assert_eq!(gba.bus.read_word(0x4000130)?, 0x3FF);
+
+    gba.set_button(Button::A, true);
+    gba.set_button(Button::L, true);
+    assert_eq!(gba.bus.read_word(0x4000130)?, 0x1FE);
+
+    gba.set_button(Button::A, false);
+    assert_eq!(gba.bus.read_word(0x4000130)?, 0x1FF);
+
+    Ok(())
+}
+
+#[test]
+fn keycnt_requests_interrupt() -> Result<()> {
+    let mut gba = Gba::with_bios(Bios::from_buffer(&[0; 0x4000])?);
+    // Interrupt once both Start and Select are held.
+    gba.bus.write_word(0x4000132, 0xC00C)?;
+    assert_eq!(gba.bus.read_word(0x4000132)?, 0xC00C);
+
+    gba.set_button(Button::Start, true);
+    assert_eq!(gba.interrupts.borrow().flags, 0);
+    gba.set_button(Button::Select, true);
+    assert_eq!(gba.interrupts.borrow().flags, InterruptKind::Keypad.mask());
+
+    Ok(())
+}
@@ -0,0 +1,262 @@
+use anyhow::Result;
+
+use crate::core::{Bios, Gba, SCREEN_WIDTH};
+
+fn idle_loop_gba() -> Result<Gba> {
+    // b #-8, branches to itself forever.
+    let mut bios = [0; 0x4000];
+    bios[0..4].copy_from_slice(&0xEAFFFFFEu32.to_le_bytes());
+    Ok(Gba::with_bios(Bios::from_buffer(&bios)?))
+}
+
+#[test]
+fn mode3_pixel_is_rendered() -> Result<()> {
+    let mut gba = idle_loop_gba()?;
+
+    // Mode 3 with BG2 enabled.
+    gba.bus.write_word(0x4000000, 0x0403)?;
+    assert_eq!(gba.bus.read_word(0x4000000)?, 0x0403);
+    let pixel = 5 * SCREEN_WIDTH + 10;
+    gba.bus.write_word(0x6000000 + pixel as u32 * 2, 0x001F)?;
+
+    gba.step_frame()?;
+
+    let framebuffer = gba.framebuffer();
+    assert_eq!(framebuffer[pixel], 0xFF0000);
+    assert_eq!(framebuffer[pixel + 1], 0x000000);
+
+    let rgba = gba.framebuffer_rgba();
+    assert_eq!(rgba.len(), framebuffer.len() * 4);
+    assert_eq!(
+        rgba[pixel * 4..pixel * 4 + 8],
+        [0xFF, 0, 0, 0xFF, 0, 0, 0, 0xFF]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn mode4_uses_palette_and_page() -> Result<()> {
+    let mut gba = idle_loop_gba()?;
+
+    // Mode 4 with BG2 enabled, showing the second page.
+    gba.bus.write_word(0x4000000, 0x0414)?;
+    gba.bus.write_word(0x5000000 + 3 * 2, 0x7C00)?;
+    gba.bus.write_byte(0x6000000 + 0xA000 + 7, 3)?;
+    gba.bus.write_byte(0x6000000 + 8, 3)?;
+
+    gba.step_frame()?;
+
+    let framebuffer = gba.framebuffer();
+    assert_eq!(framebuffer[7], 0x0000FF);
+    assert_eq!(framebuffer[8], 0x000000);
+
+    Ok(())
+}
+
+#[test]
+fn object_index_zero_shows_background() -> Result<()> {
+    let mut gba = idle_loop_gba()?;
+
+    // Mode 3 with BG2 and objects enabled, 1D object mapping.
+    gba.bus.write_word(0x4000000, 0x1443)?;
+    for x in 0..16 {
+        gba.bus.write_word(0x6000000 + x * 2, 0x001F)?;
+    }
+    // Object palette 0, entry 1 is green.
+    gba.bus.write_word(0x5000200 + 2, 0x03E0)?;
+    // Tile 512 has its even pixels set to index 1 and its odd pixels transparent.
+    for offset in (0..32).step_by(2) {
+        gba.bus.write_word(0x6014000 + offset, 0x0101)?;
+    }
+    // 8x8 object at the origin using tile 512.
+    gba.bus.write_word(0x7000000, 0x0000)?;
+    gba.bus.write_word(0x7000002, 0x0000)?;
+    gba.bus.write_word(0x7000004, 512)?;
+    // Hide the rest of the objects.
+    for index in 1..128 {
+        gba.bus.write_word(0x7000000 + index * 8, 0x0200)?;
+    }
+
+    gba.step_frame()?;
+
+    let framebuffer = gba.framebuffer();
+    assert_eq!(framebuffer[0], 0x00FF00);
+    assert_eq!(framebuffer[1], 0xFF0000);
+    assert_eq!(framebuffer[6], 0x00FF00);
+    assert_eq!(framebuffer[7], 0xFF0000);
+    // Past the object only the background remains.
+    assert_eq!(framebuffer[8], 0xFF0000);
+
+    Ok(())
+}
+
+#[test]
+fn mode4_index_zero_shows_backdrop() -> Result<()> {
+    let mut gba = idle_loop_gba()?;
+
+    gba.bus.write_word(0x4000000, 0x0404)?;
+    gba.bus.write_word(0x5000000, 0x7C00)?;
+    gba.bus.write_word(0x5000002, 0x001F)?;
+    gba.bus.write_word(0x6000000, 0x0100)?;
+
+    gba.step_frame()?;
+
+    let framebuffer = gba.framebuffer();
+    assert_eq!(framebuffer[0], 0x0000FF);
+    assert_eq!(framebuffer[1], 0xFF0000);
+
+    Ok(())
+}
+
+#[test]
+fn vram_mirrors_last_32kb() -> Result<()> {
+    let mut gba = idle_loop_gba()?;
+
+    gba.bus.write_word(0x6010000, 0x1234)?;
+    // The 32KiB past the end of VRAM repeats the object tiles.
+    assert_eq!(gba.bus.read_word(0x6018000)?, 0x1234);
+    // The whole 96KiB repeats every 128KiB.
+    assert_eq!(gba.bus.read_word(0x6030000)?, 0x1234);
+    assert_eq!(gba.bus.read_word(0x6038000)?, 0x1234);
+
+    gba.bus.write_word(0x601FFFE, 0xABCD)?;
+    assert_eq!(gba.bus.read_word(0x6017FFE)?, 0xABCD);
+
+    Ok(())
+}
+
+#[test]
+fn video_memory_byte_writes() -> Result<()> {
+    let mut gba = idle_loop_gba()?;
+
+    // Palette byte writes fill the whole halfword.
+    gba.bus.write_byte(0x5000003, 0x1F)?;
+    assert_eq!(gba.bus.read_word(0x5000002)?, 0x1F1F);
+    gba.bus.write_word(0x5000004, 0x7C00)?;
+    assert_eq!(gba.bus.read_word(0x5000004)?, 0x7C00);
+
+    // The same for background VRAM, but object VRAM and OAM ignore them.
+    gba.bus.write_byte(0x6000000, 0x42)?;
+    assert_eq!(gba.bus.read_word(0x6000000)?, 0x4242);
+    gba.bus.write_byte(0x6010000, 0x42)?;
+    assert_eq!(gba.bus.read_word(0x6010000)?, 0);
+    gba.bus.write_byte(0x7000000, 0x42)?;
+    assert_eq!(gba.bus.read_word(0x7000000)?, 0);
+
+    // In the bitmap modes the background takes VRAM up to 0x14000.
+    gba.bus.write_word(0x4000000, 0x0003)?;
+    gba.bus.write_byte(0x6010000, 0x42)?;
+    assert_eq!(gba.bus.read_word(0x6010000)?, 0x4242);
+
+    Ok(())
+}
+
+/// Sets up a 4bpp tile 1 whose top row starts with palette indices 1 and 2 and is transparent
+/// after that, with palette entry 1 red, 2 green and a blue backdrop.
+fn write_test_tile(gba: &mut Gba) -> Result<()> {
+    gba.bus.write_word(0x5000000, 0x7C00)?;
+    gba.bus.write_word(0x5000002, 0x001F)?;
+    gba.bus.write_word(0x5000004, 0x03E0)?;
+    gba.bus.write_word(0x6000020, 0x0021)?;
+    Ok(())
+}
+
+#[test]
+fn mode0_text_background_is_rendered() -> Result<()> {
+    let mut gba = idle_loop_gba()?;
+    write_test_tile(&mut gba)?;
+
+    // Mode 0 with BG0 enabled, tiles from block 0 and the map from screen block 8.
+    gba.bus.write_word(0x4000000, 0x0100)?;
+    gba.bus.write_word(0x4000008, 0x0800)?;
+    // The top left map entry uses tile 1.
+    gba.bus.write_word(0x6004000, 0x0001)?;
+
+    gba.step_frame()?;
+    {
+        let framebuffer = gba.framebuffer();
+        assert_eq!(framebuffer[0], 0xFF0000);
+        assert_eq!(framebuffer[1], 0x00FF00);
+        assert_eq!(framebuffer[2], 0x0000FF);
+        // The second row of the tile is empty.
+        assert_eq!(framebuffer[SCREEN_WIDTH], 0x0000FF);
+    }
+
+    // Scrolling one pixel right and flipping the tile horizontally.
+    gba.bus.write_word(0x4000010, 1)?;
+    gba.bus.write_word(0x6004000, 0x0401)?;
+    gba.step_frame()?;
+    {
+        let framebuffer = gba.framebuffer();
+        assert_eq!(framebuffer[5], 0x00FF00);
+        assert_eq!(framebuffer[6], 0xFF0000);
+    }
+
+    // The map wraps around at 256 pixels, so scrolling 250 pixels brings its first column back
+    // in at x = 6.
+    gba.bus.write_word(0x4000010, 250)?;
+    gba.bus.write_word(0x6004000, 0x0001)?;
+    gba.step_frame()?;
+    let framebuffer = gba.framebuffer();
+    assert_eq!(framebuffer[5], 0x0000FF);
+    assert_eq!(framebuffer[6], 0xFF0000);
+    assert_eq!(framebuffer[7], 0x00FF00);
+    assert_eq!(framebuffer[8], 0x0000FF);
+
+    Ok(())
+}
+
+#[test]
+fn lower_priority_value_is_in_front() -> Result<()> {
+    let mut gba = idle_loop_gba()?;
+    write_test_tile(&mut gba)?;
+    // Tile 2 is a solid row of index 2.
+    gba.bus.write_word(0x6000040, 0x2222)?;
+    gba.bus.write_word(0x6000042, 0x2222)?;
+
+    // BG0 and BG1 enabled, BG0 at priority 1 and BG1 at priority 0 with its own map.
+    gba.bus.write_word(0x4000000, 0x0300)?;
+    gba.bus.write_word(0x4000008, 0x0801)?;
+    gba.bus.write_word(0x400000A, 0x0900)?;
+    gba.bus.write_word(0x6004000, 0x0002)?;
+    gba.bus.write_word(0x6004800, 0x0001)?;
+
+    gba.step_frame()?;
+
+    let framebuffer = gba.framebuffer();
+    assert_eq!(framebuffer[0], 0xFF0000);
+    assert_eq!(framebuffer[1], 0x00FF00);
+    // BG1 is transparent here so BG0 shows through.
+    assert_eq!(framebuffer[2], 0x00FF00);
+
+    Ok(())
+}
+
+#[test]
+fn mode2_affine_background_is_scaled() -> Result<()> {
+    let mut gba = idle_loop_gba()?;
+    gba.bus.write_word(0x5000000, 0x7C00)?;
+    gba.bus.write_word(0x5000002, 0x001F)?;
+    // 8bpp tile 1 with index 1 in its first pixel.
+    gba.bus.write_word(0x6000040, 0x0001)?;
+
+    // Mode 2 with BG2 enabled, a 128x128 map in screen block 8 starting with tile 1.
+    gba.bus.write_word(0x4000000, 0x0402)?;
+    gba.bus.write_word(0x400000C, 0x0800)?;
+    gba.bus.write_word(0x6004000, 0x0001)?;
+    // Half scale horizontally, so each texel is two pixels wide.
+    gba.bus.write_word(0x4000020, 0x0080)?;
+    gba.bus.write_word(0x4000026, 0x0100)?;
+
+    gba.step_frame()?;
+
+    let framebuffer = gba.framebuffer();
+    assert_eq!(framebuffer[0], 0xFF0000);
+    assert_eq!(framebuffer[1], 0xFF0000);
+    assert_eq!(framebuffer[2], 0x0000FF);
+    // Without wraparound nothing is drawn past the 128 pixel map.
+    assert_eq!(framebuffer[SCREEN_WIDTH * 130], 0x0000FF);
+
+    Ok(())
+}
@@ -0,0 +1,21 @@
+use anyhow::Result;
+
+use crate::core::{Bios, Gba, WatchKind};
+
+#[test]
+fn read_memory_marks_unmapped_bytes() -> Result<()> {
+    let mut gba = Gba::with_bios(Bios::from_buffer(&[0; 0x4000])?);
+    gba.bus.write_dword(0x3000000, 0x44332211)?;
+    gba.add_watchpoint(0x3000000..=0x3000003, WatchKind::Read);
+
+    assert_eq!(
+        gba.read_memory(0x3000000, 4),
+        vec![Some(0x11), Some(0x22), Some(0x33), Some(0x44)]
+    );
+    // Looking at memory is not an access the debugger should stop on.
+    assert_eq!(gba.bus.take_watchpoint_hit(), None);
+
+    assert_eq!(gba.read_memory(0xFFFFFFFE, 2), vec![None, None]);
+
+    Ok(())
+}
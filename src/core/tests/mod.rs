@@ -0,0 +1,28 @@
+pub mod apu;
+pub mod benchmark;
+pub mod boot;
+pub mod breakpoint;
+pub mod bus;
+pub mod disassembly;
+pub mod dma;
+pub mod eeprom;
+pub mod flags;
+pub mod flash;
+pub mod frame;
+pub mod interrupt;
+pub mod io;
+pub mod io_names;
+pub mod keypad;
+pub mod lcd;
+pub mod memory_view;
+pub mod open_bus;
+pub mod raw;
+pub mod reference;
+pub mod rewind;
+pub mod rom;
+pub mod save;
+pub mod state;
+pub mod trace;
+pub mod waitstate;
+pub mod watchpoint;
+pub mod wram;
@@ -0,0 +1,82 @@
+use anyhow::Result;
+
+use crate::core::{Bios, Gba, InstructionMode};
+
+fn gba_running(address: u32, code: &[u16], instruction_mode: InstructionMode) -> Result<Gba> {
+    let mut gba = Gba::with_bios(Bios::from_buffer(&[0; 0x4000])?);
+    let code: Vec<u8> = code
+        .iter()
+        .flat_map(|halfword| halfword.to_le_bytes())
+        .collect();
+    gba.load_raw(address, &code, instruction_mode)?;
+    Ok(gba)
+}
+
+#[test]
+fn arm_fetch_latches_opcode() -> Result<()> {
+    // mov r0, #5
+    let mut gba = gba_running(0x3000000, &[0x0005, 0xE3A0], InstructionMode::Arm)?;
+    gba.tick()?;
+
+    let open_bus: Vec<u8> = (0..4).map(|i| gba.bus.open_bus_byte(i)).collect();
+    assert_eq!(open_bus, 0xE3A00005u32.to_le_bytes());
+
+    Ok(())
+}
+
+#[test]
+fn thumb_fetch_duplicates_opcode() -> Result<()> {
+    // mov r0, #5
+    let mut gba = gba_running(0x8000000, &[0x2005], InstructionMode::Thumb)?;
+    gba.tick()?;
+
+    assert_eq!(gba.bus.open_bus_byte(0), 0x05);
+    assert_eq!(gba.bus.open_bus_byte(1), 0x20);
+    assert_eq!(gba.bus.open_bus_byte(2), 0x05);
+    assert_eq!(gba.bus.open_bus_byte(3), 0x20);
+
+    Ok(())
+}
+
+#[test]
+fn thumb_iwram_fetch_pairs_with_previous() -> Result<()> {
+    // mov r0, #5 ; mov r1, #6
+    let mut gba = gba_running(0x3000000, &[0x2005, 0x2106], InstructionMode::Thumb)?;
+    gba.tick()?;
+    gba.tick()?;
+
+    // The second fetch is from the upper half of the word, the first fetch stays in the lower.
+    assert_eq!(gba.bus.open_bus_byte(0), 0x05);
+    assert_eq!(gba.bus.open_bus_byte(1), 0x20);
+    assert_eq!(gba.bus.open_bus_byte(2), 0x06);
+    assert_eq!(gba.bus.open_bus_byte(3), 0x21);
+
+    Ok(())
+}
+
+#[test]
+fn unmapped_read_returns_prefetch() -> Result<()> {
+    let mut gba = gba_running(
+        0x3000000,
+        &[
+            // mov r1, #0x4000
+            0x1901, 0xE3A0, //
+            // ldr r0, [r1]
+            0x0000, 0xE591, //
+            // ldrh r2, [r1, #2]
+            0x20B2, 0xE1D1, //
+            // mov r3, #7
+            0x3007, 0xE3A0, //
+            // mov r3, #8
+            0x3008, 0xE3A0,
+        ],
+        InstructionMode::Arm,
+    )?;
+    gba.step_instructions(3)?;
+
+    // Each load sees the opcode fetched while it executes, the one at $+8.
+    assert_eq!(gba.cpu.registers().reg(0), 0xE3A03007);
+    assert_eq!(gba.cpu.registers().reg(2), 0xE3A0);
+
+    Ok(())
+}
@@ -0,0 +1,28 @@
+use anyhow::Result;
+
+use crate::core::{Bios, Gba, InstructionMode};
+
+const IWRAM_START: u32 = 0x3000000;
+
+#[test]
+fn load_raw_arm_blob() -> Result<()> {
+    let mut gba = Gba::with_bios(Bios::from_buffer(&[0; 0x4000])?);
+
+    let mut code = Vec::new();
+    // mov r0, #5
+    code.extend_from_slice(&0xE3A00005u32.to_le_bytes());
+    // add r1, r0, #3
+    code.extend_from_slice(&0xE2801003u32.to_le_bytes());
+    gba.load_raw(IWRAM_START, &code, InstructionMode::Arm)?;
+    assert_eq!(gba.registers().pc(), IWRAM_START);
+
+    // Two ticks to fill the pipeline, then one per instruction.
+    for _ in 0..4 {
+        gba.tick()?;
+    }
+
+    assert_eq!(gba.registers().reg(0), 5);
+    assert_eq!(gba.registers().reg(1), 8);
+
+    Ok(())
+}
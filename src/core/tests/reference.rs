@@ -0,0 +1,75 @@
+use anyhow::Result;
+
+use crate::core::{PixelDifference, ReferenceFrame, SCREEN_HEIGHT, SCREEN_WIDTH};
+
+fn gradient() -> Vec<u16> {
+    (0..SCREEN_WIDTH * SCREEN_HEIGHT)
+        .map(|index| {
+            let (x, y) = (index % SCREEN_WIDTH, index / SCREEN_WIDTH);
+            ((x & 0x1F) | ((y & 0x1F) << 5) | (((x + y) & 0x1F) << 10)) as u16
+        })
+        .collect()
+}
+
+fn encode_png(frame: &[u16]) -> Result<Vec<u8>> {
+    let mut data = Vec::new();
+    let mut encoder = png::Encoder::new(&mut data, SCREEN_WIDTH as u32, SCREEN_HEIGHT as u32);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    let rgb: Vec<u8> = frame
+        .iter()
+        .flat_map(|pixel| {
+            [
+                ((pixel & 0x1F) << 3) as u8,
+                (((pixel >> 5) & 0x1F) << 3) as u8,
+                (((pixel >> 10) & 0x1F) << 3) as u8,
+            ]
+        })
+        .collect();
+    encoder.write_header()?.write_image_data(&rgb)?;
+    Ok(data)
+}
+
+#[test]
+fn matching_frame_has_no_differences() -> Result<()> {
+    let frame = gradient();
+    let raw: Vec<u8> = frame.iter().flat_map(|pixel| pixel.to_le_bytes()).collect();
+
+    let reference = ReferenceFrame::from_bgr555(&raw)?;
+    assert!(reference.compare(&frame).is_empty());
+    let reference = ReferenceFrame::from_png(&encode_png(&frame)?)?;
+    assert_eq!(reference.pixels(), frame.as_slice());
+
+    Ok(())
+}
+
+#[test]
+fn swapped_channels_are_counted() -> Result<()> {
+    let reference = ReferenceFrame::from_png(&encode_png(&gradient())?)?;
+
+    // Simulate a renderer that swaps red and blue on scanline 10.
+    let mut frame = gradient();
+    for pixel in &mut frame[10 * SCREEN_WIDTH..11 * SCREEN_WIDTH] {
+        *pixel = (*pixel & 0x3E0) | ((*pixel & 0x1F) << 10) | (*pixel >> 10);
+    }
+
+    let differences = reference.compare(&frame);
+    // Blue is always red plus ten on that line so every pixel changes.
+    assert_eq!(differences.len(), SCREEN_WIDTH);
+    assert_eq!(
+        differences[0],
+        PixelDifference {
+            x: 0,
+            y: 10,
+            actual: 10 | (10 << 5),
+            expected: (10 << 5) | (10 << 10),
+        }
+    );
+
+    Ok(())
+}
+
+#[test]
+fn wrong_sized_reference_is_rejected() {
+    assert!(ReferenceFrame::from_bgr555(&[0; 100]).is_err());
+}
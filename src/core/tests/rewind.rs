@@ -0,0 +1,61 @@
+use anyhow::Result;
+
+use crate::core::{Bios, Gba, InstructionMode};
+
+fn counting_gba() -> Result<Gba> {
+    let mut gba = Gba::with_bios(Bios::from_buffer(&[0; 0x4000])?);
+
+    // mov r1, #0x3000000
+    // loop: add r0, r0, #1 ; str r0, [r1, #0x100] ; b loop
+    let mut code = Vec::new();
+    for opcode in [0xE3A01403u32, 0xE2800001, 0xE5810100, 0xEAFFFFFC] {
+        code.extend_from_slice(&opcode.to_le_bytes());
+    }
+    gba.load_raw(0x3000000, &code, InstructionMode::Arm)?;
+
+    Ok(gba)
+}
+
+fn run_until(gba: &mut Gba, instruction_count: u64) -> Result<()> {
+    while gba.instruction_count() < instruction_count {
+        gba.tick()?;
+    }
+    Ok(())
+}
+
+#[test]
+fn rewind_reproduces_earlier_state() -> Result<()> {
+    let mut gba = counting_gba()?;
+    gba.set_snapshot_interval(Some(16));
+
+    run_until(&mut gba, 37)?;
+    let expected = gba.save_state()?;
+    run_until(&mut gba, 120)?;
+    assert_ne!(gba.save_state()?, expected);
+
+    gba.rewind_to(37)?;
+    assert_eq!(gba.instruction_count(), 37);
+    assert_eq!(Gba::diff_states(&gba.save_state()?, &expected)?, vec![]);
+    assert_eq!(gba.save_state()?, expected);
+
+    // Running forward again takes the same path as the first time.
+    run_until(&mut gba, 120)?;
+    gba.rewind_to(5)?;
+    assert_eq!(gba.instruction_count(), 5);
+
+    Ok(())
+}
+
+#[test]
+fn rewind_needs_an_earlier_snapshot() -> Result<()> {
+    let mut gba = counting_gba()?;
+    run_until(&mut gba, 10)?;
+    gba.set_snapshot_interval(Some(16));
+    run_until(&mut gba, 40)?;
+
+    assert!(gba.rewind_to(5).is_err());
+    assert!(gba.rewind_to(50).is_err());
+    gba.rewind_to(10)?;
+
+    Ok(())
+}
@@ -0,0 +1,60 @@
+use anyhow::Result;
+use std::{env, fs, process};
+
+use crate::core::{Bios, Gba};
+
+#[test]
+fn rom_is_mapped_at_cartridge_region() -> Result<()> {
+    let path = env::temp_dir().join(format!("rgba-rom-{}.gba", process::id()));
+    fs::write(&path, [0x78, 0x56, 0x34, 0x12, 0xAA, 0xBB])?;
+
+    let mut gba = Gba::with_bios(Bios::from_buffer(&[0; 0x4000])?);
+    gba.load_rom(&path)?;
+    fs::remove_file(&path)?;
+
+    assert_eq!(gba.bus.read_dword(0x8000000)?, 0x12345678);
+    // The wait state regions mirror the same ROM.
+    assert_eq!(gba.bus.read_dword(0xA000000)?, 0x12345678);
+    assert_eq!(gba.bus.read_word(0xC000004)?, 0xBBAA);
+
+    // Writes do not reach the ROM.
+    gba.bus.write_dword(0x8000000, 0)?;
+    assert_eq!(gba.bus.read_dword(0x8000000)?, 0x12345678);
+
+    // Past the end of the ROM reads return the halfword address. Without an EEPROM that goes
+    // for the top half of the last mirror too.
+    assert_eq!(gba.bus.read_word(0x8000010)?, 0x0008);
+    assert_eq!(gba.bus.read_word(0xD000010)?, 0x0008);
+
+    Ok(())
+}
+
+#[test]
+fn rom_title_comes_from_header() -> Result<()> {
+    let path = env::temp_dir().join(format!("rgba-rom-title-{}.gba", process::id()));
+    let mut data = vec![0; 0xC0];
+    data[0xA0..0xA7].copy_from_slice(b"TESTING");
+    fs::write(&path, &data)?;
+
+    let mut gba = Gba::with_bios(Bios::from_buffer(&[0; 0x4000])?);
+    assert_eq!(gba.rom_title(), None);
+    gba.load_rom(&path)?;
+    fs::remove_file(&path)?;
+
+    assert_eq!(gba.rom_title().as_deref(), Some("TESTING"));
+
+    Ok(())
+}
+
+#[test]
+fn no_rom_allocates_nothing() -> Result<()> {
+    let mut gba = Gba::with_bios(Bios::from_buffer(&[0; 0x4000])?);
+    assert_eq!(gba.rom_size(), 0);
+
+    // The whole cartridge space reads the address lines instead.
+    assert_eq!(gba.bus.read_word(0x8000000)?, 0x0000);
+    assert_eq!(gba.bus.read_word(0x9FFFFFE)?, 0xFFFF);
+    assert_eq!(gba.rom_size(), 0);
+
+    Ok(())
+}
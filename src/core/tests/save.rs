@@ -0,0 +1,82 @@
+use anyhow::Result;
+use std::{env, fs, process};
+
+use crate::core::{Bios, Gba};
+
+fn new_gba() -> Result<Gba> {
+    Ok(Gba::with_bios(Bios::from_buffer(&[0; 0x4000])?))
+}
+
+#[test]
+fn sram_survives_flush_and_reload() -> Result<()> {
+    let path = env::temp_dir().join(format!("rgba-sram-{}.sav", process::id()));
+    let _ = fs::remove_file(&path);
+
+    let mut gba = new_gba()?;
+    gba.set_save_file(&path)?;
+    assert!(!gba.flush_save()?);
+    gba.bus.write_byte(0xE000010, 0x42)?;
+    assert!(gba.flush_save()?);
+    assert!(!gba.flush_save()?);
+
+    let mut reloaded = new_gba()?;
+    assert_eq!(reloaded.bus.read_byte(0xE000010)?, 0xFF);
+    reloaded.set_save_file(&path)?;
+    assert_eq!(reloaded.bus.read_byte(0xE000010)?, 0x42);
+    // The 64KiB of SRAM is mirrored through the rest of the region.
+    assert_eq!(reloaded.bus.read_byte(0xE010010)?, 0x42);
+
+    fs::remove_file(&path)?;
+    Ok(())
+}
+
+#[test]
+fn sram_is_flushed_on_drop() -> Result<()> {
+    let path = env::temp_dir().join(format!("rgba-sram-drop-{}.sav", process::id()));
+    let _ = fs::remove_file(&path);
+
+    let mut gba = new_gba()?;
+    gba.set_save_file(&path)?;
+    gba.bus.write_byte(0xE000123, 0x5A)?;
+    drop(gba);
+
+    let mut reloaded = new_gba()?;
+    reloaded.set_save_file(&path)?;
+    assert_eq!(reloaded.bus.read_byte(0xE000123)?, 0x5A);
+
+    fs::remove_file(&path)?;
+    Ok(())
+}
+
+#[test]
+fn sram_is_flushed_on_reset() -> Result<()> {
+    let path = env::temp_dir().join(format!("rgba-sram-reset-{}.sav", process::id()));
+    let _ = fs::remove_file(&path);
+
+    let mut gba = new_gba()?;
+    gba.set_save_file(&path)?;
+    gba.bus.write_byte(0xE000042, 0xA5)?;
+    gba.reset();
+
+    let mut reloaded = new_gba()?;
+    reloaded.set_save_file(&path)?;
+    assert_eq!(reloaded.bus.read_byte(0xE000042)?, 0xA5);
+
+    drop(gba);
+    fs::remove_file(&path)?;
+    Ok(())
+}
+
+#[test]
+fn unwritable_save_file_is_an_error() -> Result<()> {
+    let mut gba = new_gba()?;
+    // A directory can never be written as a file.
+    gba.set_save_file(env::temp_dir())
+        .expect_err("reading a directory as a save file should fail");
+
+    gba.save_file = Some(env::temp_dir());
+    gba.bus.write_byte(0xE000000, 0x01)?;
+    assert!(gba.flush_save().is_err());
+
+    Ok(())
+}
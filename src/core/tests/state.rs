@@ -0,0 +1,135 @@
+use anyhow::Result;
+
+use crate::core::{BackupType, Bios, Gba, InstructionMode, SaveState, StateDiff};
+
+fn running_gba() -> Result<Gba> {
+    let mut gba = Gba::with_bios(Bios::from_buffer(&[0; 0x4000])?);
+
+    // mov r0, #5 ; b #-8
+    let mut code = Vec::new();
+    code.extend_from_slice(&0xE3A00005u32.to_le_bytes());
+    code.extend_from_slice(&0xEAFFFFFEu32.to_le_bytes());
+    gba.load_raw(0x3000000, &code, InstructionMode::Arm)?;
+    for _ in 0..10 {
+        gba.tick()?;
+    }
+
+    Ok(gba)
+}
+
+#[test]
+fn diff_reports_single_register() -> Result<()> {
+    let gba = running_gba()?;
+    let left = gba.save_state()?;
+
+    let mut state = SaveState::deserialize(&left)?;
+    *state.cpu.registers.reg_mut(3) = 0x1234;
+    let right = state.serialize()?;
+
+    assert_eq!(
+        Gba::diff_states(&left, &right)?,
+        vec![StateDiff::Register {
+            name: "r3".into(),
+            left: 0,
+            right: 0x1234,
+        }]
+    );
+    assert!(Gba::diff_states(&left, &left)?.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn diff_merges_memory_ranges() -> Result<()> {
+    let mut gba = running_gba()?;
+    let left = gba.save_state()?;
+
+    for address in 0x3000100..0x3000104 {
+        gba.bus.write_byte(address, 0xFF)?;
+    }
+    gba.bus.write_byte(0x3000200, 0xFF)?;
+    let right = gba.save_state()?;
+
+    assert_eq!(
+        Gba::diff_states(&left, &right)?,
+        vec![
+            StateDiff::Memory(0x3000100..=0x3000103),
+            StateDiff::Memory(0x3000200..=0x3000200),
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn load_state_restores_pipeline() -> Result<()> {
+    let mut gba = running_gba()?;
+    let state = gba.save_state()?;
+
+    for _ in 0..3 {
+        gba.tick()?;
+    }
+    gba.load_state(&state)?;
+
+    assert!(Gba::diff_states(&state, &gba.save_state()?)?.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn save_state_round_trip() -> Result<()> {
+    let mut gba = Gba::with_bios(Bios::from_buffer(&[0; 0x4000])?);
+    // add r0, r0, #1 ; b #-8
+    let mut code = Vec::new();
+    code.extend_from_slice(&0xE2800001u32.to_le_bytes());
+    code.extend_from_slice(&0xEAFFFFFDu32.to_le_bytes());
+    gba.load_raw(0x3000000, &code, InstructionMode::Arm)?;
+
+    gba.emulate(Some(10000))?;
+    let state = gba.save_state()?;
+    let registers = gba.registers().clone();
+
+    gba.emulate(Some(10000))?;
+    assert_ne!(gba.registers().reg(0), registers.reg(0));
+    gba.load_state(&state)?;
+
+    assert_eq!(
+        gba.registers().named_registers(),
+        registers.named_registers()
+    );
+    assert_eq!(gba.registers().cpsr.to_u32(), registers.cpsr.to_u32());
+    assert!(Gba::diff_states(&state, &gba.save_state()?)?.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn save_memory_is_part_of_the_state() -> Result<()> {
+    let mut gba = running_gba()?;
+    gba.bus.write_byte(0xE000010, 0x11)?;
+    let state = gba.save_state()?;
+
+    gba.bus.write_byte(0xE000010, 0x22)?;
+    gba.load_state(&state)?;
+    assert_eq!(gba.bus.read_byte(0xE000010)?, 0x11);
+
+    Ok(())
+}
+
+#[test]
+fn flash_command_survives_state_load() -> Result<()> {
+    let mut gba = running_gba()?;
+    gba.set_backup_type(BackupType::Flash64K)?;
+    // Unlock and start a byte program, then take the state before the byte is written.
+    gba.bus.write_byte(0xE005555, 0xAA)?;
+    gba.bus.write_byte(0xE002AAA, 0x55)?;
+    gba.bus.write_byte(0xE005555, 0xA0)?;
+    let state = gba.save_state()?;
+
+    let mut restored = running_gba()?;
+    restored.load_state(&state)?;
+    restored.bus.write_byte(0xE000020, 0x42)?;
+    assert_eq!(restored.bus.read_byte(0xE000020)?, 0x42);
+
+    Ok(())
+}
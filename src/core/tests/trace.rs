@@ -0,0 +1,102 @@
+use anyhow::Result;
+use std::{env, fs, process};
+
+use crate::core::{Bios, Gba, InstructionMode, TraceFormat};
+
+#[test]
+fn trace_is_written_to_file() -> Result<()> {
+    let path = env::temp_dir().join(format!("rgba-trace-{}.log", process::id()));
+
+    let mut gba = Gba::with_bios(Bios::from_buffer(&[0; 0x4000])?);
+    let mut code = Vec::new();
+    // mov r0, #1
+    code.extend_from_slice(&0xE3A00001u32.to_le_bytes());
+    // movne r1, #2
+    code.extend_from_slice(&0x13A01002u32.to_le_bytes());
+    // b .
+    code.extend_from_slice(&0xEAFFFFFEu32.to_le_bytes());
+    gba.load_raw(0x3000000, &code, InstructionMode::Arm)?;
+
+    gba.set_trace_output(Some(&path))?;
+    for _ in 0..4 {
+        gba.tick()?;
+    }
+    gba.set_trace_output(None)?;
+    // Nothing is traced once the trace is stopped.
+    gba.tick()?;
+
+    let trace = fs::read_to_string(&path)?;
+    fs::remove_file(&path)?;
+    let lines: Vec<&str> = trace.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].starts_with("$03000000: E3A00001 mov r0"));
+    assert!(lines[1].starts_with("$03000004: 13A01002 mov.ne r1"));
+
+    Ok(())
+}
+
+#[test]
+fn register_state_trace() -> Result<()> {
+    let path = env::temp_dir().join(format!("rgba-register-trace-{}.log", process::id()));
+
+    let mut gba = Gba::with_bios(Bios::from_buffer(&[0; 0x4000])?);
+    let mut code = Vec::new();
+    // mov r0, #1
+    code.extend_from_slice(&0xE3A00001u32.to_le_bytes());
+    // mov r1, #2
+    code.extend_from_slice(&0xE3A01002u32.to_le_bytes());
+    gba.load_raw(0x3000000, &code, InstructionMode::Arm)?;
+
+    gba.set_trace_format(TraceFormat::RegisterState);
+    gba.set_trace_output(Some(&path))?;
+    for _ in 0..4 {
+        gba.tick()?;
+    }
+    gba.set_trace_output(None)?;
+
+    let trace = fs::read_to_string(&path)?;
+    fs::remove_file(&path)?;
+    let lines: Vec<&str> = trace.lines().collect();
+    let zeros = "00000000 ".repeat(15);
+    assert_eq!(lines[0], format!("{zeros}03000008 cpsr: 00000010"));
+    assert_eq!(
+        lines[1],
+        format!("00000001 {}0300000C cpsr: 00000010", "00000000 ".repeat(14))
+    );
+
+    Ok(())
+}
+
+#[test]
+fn trace_shows_flags_after_execute() -> Result<()> {
+    let path = env::temp_dir().join(format!("rgba-flags-trace-{}.log", process::id()));
+
+    let mut gba = Gba::with_bios(Bios::from_buffer(&[0; 0x4000])?);
+    let mut code = Vec::new();
+    // mov r0, #1
+    code.extend_from_slice(&0xE3A00001u32.to_le_bytes());
+    // cmp r0, #2
+    code.extend_from_slice(&0xE3500002u32.to_le_bytes());
+    // cmp r0, #1
+    code.extend_from_slice(&0xE3500001u32.to_le_bytes());
+    gba.load_raw(0x3000000, &code, InstructionMode::Arm)?;
+
+    gba.set_trace_flags(true);
+    gba.set_trace_output(Some(&path))?;
+    for _ in 0..5 {
+        gba.tick()?;
+    }
+    gba.set_trace_output(None)?;
+
+    let trace = fs::read_to_string(&path)?;
+    fs::remove_file(&path)?;
+    let lines: Vec<&str> = trace.lines().collect();
+    assert!(lines[0].ends_with(" [----]"));
+    // 1 - 2 is negative and borrows.
+    assert!(lines[1].starts_with("$03000004: E3500002 cmps r0"));
+    assert!(lines[1].ends_with(" [N---]"));
+    // 1 - 1 is zero and does not borrow.
+    assert!(lines[2].ends_with(" [-ZC-]"));
+
+    Ok(())
+}
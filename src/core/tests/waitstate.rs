@@ -0,0 +1,60 @@
+use anyhow::Result;
+
+use crate::core::{Bios, Gba, InstructionMode};
+
+/// Runs `ldr r0, [r1]` from IWRAM with r1 set by `mov_r1` and returns the cycles it took.
+fn load_cycles(mov_r1: u32, wait_control: u16) -> Result<usize> {
+    let mut gba = Gba::with_bios(Bios::from_buffer(&[0; 0x4000])?);
+    let mut code = Vec::new();
+    code.extend_from_slice(&mov_r1.to_le_bytes());
+    // ldr r0, [r1]
+    code.extend_from_slice(&0xE5910000u32.to_le_bytes());
+    gba.load_raw(0x3000000, &code, InstructionMode::Arm)?;
+    gba.bus.write_word(0x4000204, wait_control)?;
+
+    gba.step_instructions(1)?;
+    let (cycles, _) = gba.tick()?;
+    Ok(cycles)
+}
+
+#[test]
+fn gamepak_load_is_slower_than_iwram() -> Result<()> {
+    // mov r1, #0x3000000
+    let iwram = load_cycles(0xE3A01403, 0)?;
+    // mov r1, #0x8000000
+    let gamepak = load_cycles(0xE3A01302, 0)?;
+
+    // A non-sequential 32 bit access to WS0 is 4 waits for the first half and 2 for the second.
+    assert_eq!(gamepak, iwram + 7);
+
+    Ok(())
+}
+
+#[test]
+fn ewram_load_is_slower_than_iwram() -> Result<()> {
+    // mov r1, #0x3000000
+    let iwram = load_cycles(0xE3A01403, 0)?;
+    // mov r1, #0x2000000
+    let ewram = load_cycles(0xE3A01402, 0)?;
+
+    // Two halfword accesses with 2 waits each.
+    assert_eq!(ewram, iwram + 5);
+
+    Ok(())
+}
+
+#[test]
+fn waitcnt_changes_gamepak_timing() -> Result<()> {
+    // mov r1, #0x8000000
+    let slow = load_cycles(0xE3A01302, 0)?;
+    // WS0 with 2 non-sequential waits and 1 sequential wait.
+    let fast = load_cycles(0xE3A01302, (0b10 << 2) | (1 << 4))?;
+
+    assert_eq!(slow - fast, 3);
+    // The gamepak type bit is read only.
+    let mut gba = Gba::with_bios(Bios::from_buffer(&[0; 0x4000])?);
+    gba.bus.write_word(0x4000204, 0xFFFF)?;
+    assert_eq!(gba.bus.read_word(0x4000204)?, 0x5FFF);
+
+    Ok(())
+}
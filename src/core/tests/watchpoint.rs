@@ -0,0 +1,39 @@
+use anyhow::Result;
+
+use crate::core::{Bios, Gba, InstructionMode, StopReason, WatchKind};
+
+#[test]
+fn write_watchpoint_trips_once() -> Result<()> {
+    let mut gba = Gba::with_bios(Bios::from_buffer(&[0; 0x4000])?);
+
+    let mut code = Vec::new();
+    // mov r1, #0x3000000
+    code.extend_from_slice(&0xE3A01403u32.to_le_bytes());
+    // add r1, r1, #0x1000
+    code.extend_from_slice(&0xE2811A01u32.to_le_bytes());
+    // mov r0, #5
+    code.extend_from_slice(&0xE3A00005u32.to_le_bytes());
+    // str r0, [r1]
+    code.extend_from_slice(&0xE5810000u32.to_le_bytes());
+    // b .
+    code.extend_from_slice(&0xEAFFFFFEu32.to_le_bytes());
+    gba.load_raw(0x3000000, &code, InstructionMode::Arm)?;
+
+    gba.add_watchpoint(0x3001000..=0x3001003, WatchKind::Write);
+    // Fetching the code is not a read the debugger should stop on.
+    gba.add_watchpoint(0x3000000..=0x3000013, WatchKind::Read);
+
+    assert_eq!(
+        gba.emulate(Some(100))?,
+        StopReason::Watchpoint {
+            address: 0x3001000,
+            kind: WatchKind::Write
+        }
+    );
+    assert_eq!(gba.registers().reg(0), 5);
+
+    // The program only spins from here, so nothing else is hit.
+    assert_eq!(gba.emulate(Some(100))?, StopReason::CyclesElapsed);
+
+    Ok(())
+}
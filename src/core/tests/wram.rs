@@ -0,0 +1,31 @@
+use anyhow::Result;
+
+use crate::core::{Bios, Gba};
+
+#[test]
+fn ewram_mirrors_every_256kb() -> Result<()> {
+    let mut gba = Gba::with_bios(Bios::from_buffer(&[0; 0x4000])?);
+
+    gba.bus.write_byte(0x2040000, 0x5A)?;
+    assert_eq!(gba.bus.read_byte(0x2000000)?, 0x5A);
+
+    // The last word of the region and the same word in the last mirror.
+    gba.bus.write_dword(0x203FFFC, 0x12345678)?;
+    assert_eq!(gba.bus.read_dword(0x207FFFC)?, 0x12345678);
+    assert_eq!(gba.bus.read_dword(0x2FFFFFC)?, 0x12345678);
+    // The next word wraps around to the start of the region.
+    assert_eq!(gba.bus.read_dword(0x2040000)?, 0x5A);
+
+    Ok(())
+}
+
+#[test]
+fn iwram_mirrors_every_32kb() -> Result<()> {
+    let mut gba = Gba::with_bios(Bios::from_buffer(&[0; 0x4000])?);
+
+    gba.bus.write_word(0x3007FFE, 0xBEEF)?;
+    assert_eq!(gba.bus.read_word(0x300FFFE)?, 0xBEEF);
+    assert_eq!(gba.bus.read_word(0x3FFFFFE)?, 0xBEEF);
+
+    Ok(())
+}
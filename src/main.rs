@@ -1,23 +1,175 @@
-mod core;
-use crate::core::Gba;
+use rgba::core::{
+    BackupType, Gba, InstructionMode, StopReason, TraceFormat, UnimplementedOpcodePolicy,
+};
 
 use anyhow::Result;
-use clap::Parser;
+use clap::{Parser, ValueEnum};
+use std::{path::Path, time::Duration};
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 struct Args {
     #[arg(short, long)]
     bios: String,
+    /// Cartridge image to run.
+    #[arg(short, long)]
+    rom: Option<String>,
     #[arg(short, long)]
     cycles: Option<usize>,
+    /// Skip the BIOS boot animation and start at the cartridge entry point.
+    #[arg(long)]
+    fast_boot: bool,
+    /// Emulate common BIOS calls instead of running the BIOS's code for them.
+    #[arg(long)]
+    hle_bios: bool,
+    /// Report unaligned halfword and word accesses as errors.
+    #[arg(long)]
+    strict_alignment: bool,
+    /// Write every executed instruction to this file.
+    #[arg(long)]
+    trace: Option<String>,
+    /// What each line of the instruction trace shows.
+    #[arg(long, value_enum, default_value_t = Trace::Disassembly)]
+    trace_format: Trace,
+    /// End each disassembly trace line with the flags the instruction left behind.
+    #[arg(long)]
+    trace_flags: bool,
+    /// Log every I/O register access.
+    #[arg(long)]
+    trace_io: bool,
+    /// Battery save file to load on start and write back on exit.
+    #[arg(short, long)]
+    save: Option<String>,
+    /// Also write the save file every this many seconds while running.
+    #[arg(long, requires = "save")]
+    autosave_seconds: Option<u64>,
+    /// The kind of save memory the cartridge has.
+    #[arg(long, value_enum, default_value_t = Backup::Sram)]
+    backup: Backup,
+    /// What to do when the CPU reaches an opcode the emulator does not support.
+    #[arg(long, value_enum, default_value_t = OnUnimplemented::Abort)]
+    on_unimplemented: OnUnimplemented,
+    /// Print COUNT instructions from ADDRESS (hex) instead of running anything.
+    #[arg(long, num_args = 2, value_names = ["ADDRESS", "COUNT"])]
+    disasm: Option<Vec<String>>,
+    /// Disassemble THUMB code rather than ARM code.
+    #[arg(long, requires = "disasm")]
+    disasm_thumb: bool,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum Backup {
+    Sram,
+    Flash64,
+    Flash128,
+    Eeprom,
+}
+
+impl From<Backup> for BackupType {
+    fn from(value: Backup) -> Self {
+        match value {
+            Backup::Sram => BackupType::Sram,
+            Backup::Flash64 => BackupType::Flash64K,
+            Backup::Flash128 => BackupType::Flash128K,
+            Backup::Eeprom => BackupType::Eeprom,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum Trace {
+    /// The address, opcode and disassembly of each instruction.
+    Disassembly,
+    /// r0 to r15 and the CPSR before each instruction, for diffing against mGBA.
+    Registers,
+}
+
+impl From<Trace> for TraceFormat {
+    fn from(value: Trace) -> Self {
+        match value {
+            Trace::Disassembly => TraceFormat::Disassembly,
+            Trace::Registers => TraceFormat::RegisterState,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum OnUnimplemented {
+    /// Stop emulation with an error.
+    Abort,
+    /// Log the opcode and continue as if it were a NOP.
+    Skip,
+    /// Take the undefined instruction exception.
+    Undefined,
+}
+
+impl From<OnUnimplemented> for UnimplementedOpcodePolicy {
+    fn from(value: OnUnimplemented) -> Self {
+        match value {
+            OnUnimplemented::Abort => UnimplementedOpcodePolicy::Abort,
+            OnUnimplemented::Skip => UnimplementedOpcodePolicy::Skip,
+            OnUnimplemented::Undefined => UnimplementedOpcodePolicy::UndefinedException,
+        }
+    }
 }
 
 fn main() -> Result<()> {
     let args = Args::parse();
 
     let mut gba = Gba::new(&args.bios)?;
-    gba.emulate(args.cycles)?;
+    if let Some(rom) = &args.rom {
+        gba.load_rom(rom)?;
+        if let Some(title) = gba.rom_title() {
+            println!("Loaded {title}");
+        }
+    }
+    if let Some(disasm) = &args.disasm {
+        let address = u32::from_str_radix(disasm[0].trim_start_matches("0x"), 16)?;
+        let count = disasm[1].parse()?;
+        let instruction_mode = if args.disasm_thumb {
+            InstructionMode::Thumb
+        } else {
+            InstructionMode::Arm
+        };
+        for (address, line) in gba.disassemble(address, count, instruction_mode)? {
+            println!("{address:08X}: {line}");
+        }
+        return Ok(());
+    }
+    if args.fast_boot {
+        gba.fast_boot()?;
+    }
+    gba.set_high_level_bios(args.hle_bios);
+    gba.set_strict_alignment(args.strict_alignment);
+    gba.set_io_trace(args.trace_io);
+    gba.set_trace_format(args.trace_format.into());
+    gba.set_trace_flags(args.trace_flags);
+    gba.set_trace_output(args.trace.as_deref().map(Path::new))?;
+    gba.set_unimplemented_opcode_policy(args.on_unimplemented.into());
+    gba.set_backup_type(args.backup.into())?;
+    if let Some(save) = &args.save {
+        gba.set_save_file(save)?;
+        gba.set_autosave_interval(args.autosave_seconds.map(Duration::from_secs));
+    }
+    match gba.emulate(args.cycles)? {
+        StopReason::Breakpoint(address) | StopReason::OpcodeBreakpoint(address) => {
+            println!("Stopped at breakpoint at 0x{address:08X}");
+        }
+        StopReason::Watchpoint { address, kind } => {
+            println!("Stopped at watchpoint: {kind:?} of 0x{address:08X}");
+        }
+        _ => {}
+    }
+    print_registers(&gba);
 
     Ok(())
 }
+
+/// Prints the registers of the current mode so runs can be compared against other emulators.
+fn print_registers(gba: &Gba) {
+    let registers = gba.registers();
+    for i in 0..16 {
+        println!("r{i:<2} = 0x{:08X}", registers.reg(i));
+    }
+    println!("cpsr = 0x{:08X}", registers.cpsr.to_u32());
+}